@@ -11,6 +11,9 @@ pub struct StabilityConfig {
     pub interval_ms: u64,
     #[serde(default = "StabilityConfig::default_timeout_secs")]
     pub timeout_secs: u64,
+    /// Files untouched for this long are treated as already stable (0 disables the fast path).
+    #[serde(default = "StabilityConfig::default_settled_after_secs")]
+    pub settled_after_secs: u64,
 }
 
 impl StabilityConfig {
@@ -23,6 +26,9 @@ impl StabilityConfig {
     pub fn default_timeout_secs() -> u64 {
         120
     }
+    pub fn default_settled_after_secs() -> u64 {
+        60
+    }
 }
 
 impl Default for StabilityConfig {
@@ -31,6 +37,7 @@ impl Default for StabilityConfig {
             consecutive_checks: Self::default_checks(),
             interval_ms: Self::default_interval_ms(),
             timeout_secs: Self::default_timeout_secs(),
+            settled_after_secs: Self::default_settled_after_secs(),
         }
     }
 }
@@ -46,6 +53,130 @@ pub struct AppConfig {
     pub auto_delete_source: bool,
     #[serde(default)]
     pub suppress_delete_prompt: bool,
+    /// Ask before a forced reimport deletes files that are not part of the archive.
+    #[serde(default = "default_true")]
+    pub confirm_overwrite_extras: bool,
+    #[serde(default)]
+    pub desktop_notifications: bool,
+    /// Closing the window hides it to the tray icon instead of exiting.
+    #[serde(default)]
+    pub minimize_to_tray: bool,
+    /// Substrings or `*`/`?` globs; matching files are ignored instead of queued for import.
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// Move the source into `Downloads/_imported/<date>/` after a successful import instead of deleting it.
+    #[serde(default)]
+    pub archive_source_after_import: bool,
+    /// Rename the source to `<name>.osz.imported` after a successful import, keeping it in place.
+    #[serde(default)]
+    pub mark_source_imported: bool,
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    /// Skip the recycle bin and remove sources with `fs::remove_file` directly.
+    #[serde(default)]
+    pub permanent_delete: bool,
+    /// Address the beatmap preview server listens on; loopback unless remote viewing is wanted.
+    #[serde(default = "default_preview_bind")]
+    pub preview_bind: String,
+    /// Fixed preview port; falls back to an OS-assigned one when unset or taken.
+    #[serde(default)]
+    pub preview_port: Option<u16>,
+    /// Size budget for `cache/preview`; least recently used maps are evicted first (0 = unlimited).
+    #[serde(default = "default_preview_cache_max_mb")]
+    pub preview_cache_max_mb: u64,
+    /// Download speed cap in kilobits per second; `None` downloads at full speed.
+    #[serde(default)]
+    pub max_download_kbps: Option<u32>,
+    /// User-Agent for searches and downloads; empty sends `McOsuImporter/<version>`.
+    #[serde(default)]
+    pub user_agent: String,
+    /// Removes completed/duplicate queue entries this many seconds after they finish; `None` keeps them.
+    #[serde(default)]
+    pub auto_clear_completed_after_secs: Option<u64>,
+    /// Scales audio previews towards a common loudness.
+    #[serde(default)]
+    pub normalize_preview_volume: bool,
+    /// Size budget for `cache/audio`; bulk pre-caching stops once it is reached (0 = unlimited).
+    #[serde(default = "default_audio_cache_max_mb")]
+    pub audio_cache_max_mb: u64,
+    /// Folder names that identify a cloud-synced location (matched as a prefix of any path component).
+    #[serde(default = "default_cloud_sync_markers")]
+    pub cloud_sync_markers: Vec<String>,
+    /// A file's last write must be at least this many seconds old before it is processed (0 = off).
+    #[serde(default)]
+    pub min_file_age_secs: u64,
+    /// Require a parseable `.osu` in every archive; when off, archives without one are imported under their file name.
+    #[serde(default = "default_true")]
+    pub strict_archive_validation: bool,
+    /// How many maps may be extracted into Songs at the same time (auto, bulk and manual imports).
+    #[serde(default = "default_max_parallel_imports")]
+    pub max_parallel_imports: usize,
+    /// Size cap for cached audio and thumbnails together; least recently used files are deleted
+    /// first (0 = unlimited).
+    #[serde(default = "default_media_cache_max_mb")]
+    pub media_cache_max_mb: u64,
+    /// More folders watched for new `.osz` files besides `downloads_dir` (not recursive).
+    #[serde(default)]
+    pub extra_watch_dirs: Vec<PathBuf>,
+    /// File extensions picked up from watched folders; anything but `osz` must contain a `.osu`.
+    #[serde(default = "default_accepted_extensions")]
+    pub accepted_extensions: Vec<String>,
+    /// Show titles and artists in their original script (`TitleUnicode`) instead of romanized.
+    #[serde(default)]
+    pub show_unicode_metadata: bool,
+    /// Leave background videos (`.mp4`, `.avi`, ...) out when extracting into Songs.
+    #[serde(default)]
+    pub skip_video_on_import: bool,
+    /// Every Songs folder maps can be imported into; `songs_dir` is the default and listed first.
+    #[serde(default)]
+    pub songs_dirs: Vec<PathBuf>,
+    /// Last beatmap search, put back in the search box at startup (not run again).
+    #[serde(default)]
+    pub last_query: Option<String>,
+}
+
+/// Layout of the import queue: detailed rows or a grid of thumbnail cards.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    #[default]
+    List,
+    Grid,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_parallel_imports() -> usize {
+    crate::concurrency::DEFAULT_IMPORT_LIMIT
+}
+
+fn default_preview_bind() -> String {
+    "127.0.0.1".into()
+}
+
+fn default_preview_cache_max_mb() -> u64 {
+    1024
+}
+
+fn default_audio_cache_max_mb() -> u64 {
+    512
+}
+
+fn default_media_cache_max_mb() -> u64 {
+    500
+}
+
+fn default_accepted_extensions() -> Vec<String> {
+    vec!["osz".into(), "zip".into()]
+}
+
+fn default_cloud_sync_markers() -> Vec<String> {
+    ["OneDrive", "Dropbox", "Google Drive", "GoogleDrive", "My Drive", "iCloudDrive", "iCloud Drive", "pCloud"]
+        .iter()
+        .map(|m| m.to_string())
+        .collect()
 }
 
 impl Default for AppConfig {
@@ -54,6 +185,7 @@ impl Default for AppConfig {
             .and_then(|u| u.download_dir().map(|p| p.to_path_buf()))
             .unwrap_or_else(|| PathBuf::from("."));
         let songs = downloads.join("McOsuSongs");
+        let songs_dir_default = songs.clone();
         Self {
             downloads_dir: downloads,
             songs_dir: songs,
@@ -61,10 +193,62 @@ impl Default for AppConfig {
             stability: StabilityConfig::default(),
             auto_delete_source: false,
             suppress_delete_prompt: false,
+            confirm_overwrite_extras: true,
+            desktop_notifications: false,
+            minimize_to_tray: false,
+            ignore_patterns: Vec::new(),
+            archive_source_after_import: false,
+            mark_source_imported: false,
+            view_mode: ViewMode::List,
+            permanent_delete: false,
+            preview_bind: default_preview_bind(),
+            preview_port: None,
+            preview_cache_max_mb: default_preview_cache_max_mb(),
+            max_download_kbps: None,
+            user_agent: String::new(),
+            auto_clear_completed_after_secs: None,
+            normalize_preview_volume: false,
+            audio_cache_max_mb: default_audio_cache_max_mb(),
+            media_cache_max_mb: default_media_cache_max_mb(),
+            cloud_sync_markers: default_cloud_sync_markers(),
+            min_file_age_secs: 0,
+            strict_archive_validation: true,
+            max_parallel_imports: default_max_parallel_imports(),
+            extra_watch_dirs: Vec::new(),
+            accepted_extensions: default_accepted_extensions(),
+            show_unicode_metadata: false,
+            skip_video_on_import: false,
+            songs_dirs: vec![songs_dir_default],
+            last_query: None,
         }
     }
 }
 
+impl AppConfig {
+    /// Every Songs destination without repeats, the default (`songs_dir`) first.
+    pub fn songs_destinations(&self) -> Vec<PathBuf> {
+        crate::watcher::unique_dirs(
+            std::iter::once(self.songs_dir.clone()).chain(self.songs_dirs.iter().cloned()),
+        )
+    }
+
+    /// Makes `dir` the default Songs folder, replacing the previous default in `songs_dirs`
+    /// and keeping the other destinations.
+    pub fn set_default_songs_dir(&mut self, dir: PathBuf) {
+        let old = std::mem::replace(&mut self.songs_dir, dir);
+        self.songs_dirs
+            .retain(|d| !crate::path_utils::same_file(d, &old));
+        self.songs_dirs = self.songs_destinations();
+    }
+
+    /// Every folder the watcher and the startup scan cover: Downloads first, then the extras.
+    pub fn watch_dirs(&self) -> Vec<PathBuf> {
+        crate::watcher::unique_dirs(
+            std::iter::once(self.downloads_dir.clone()).chain(self.extra_watch_dirs.iter().cloned()),
+        )
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ImportStatus {
     Detected,
@@ -74,6 +258,8 @@ pub enum ImportStatus {
     Completed,
     DuplicateSkipped,
     Failed,
+    /// Stopped by the user while importing; nothing was left in Songs.
+    Cancelled,
 }
 
 impl ImportStatus {
@@ -86,6 +272,32 @@ impl ImportStatus {
             ImportStatus::Completed => "Concluido",
             ImportStatus::DuplicateSkipped => "Duplicado",
             ImportStatus::Failed => "Falhou",
+            ImportStatus::Cancelled => "Cancelado",
+        }
+    }
+}
+
+/// Why an entry failed, used to group failures after a batch.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureKind {
+    Permission,
+    Extraction,
+    Write,
+    Metadata,
+    InvalidArchive,
+    Other,
+}
+
+impl FailureKind {
+    /// Completes "N falha(s) ...".
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureKind::Permission => "de permissao",
+            FailureKind::Extraction => "de extracao",
+            FailureKind::Write => "de gravacao",
+            FailureKind::Metadata => "de metadados",
+            FailureKind::InvalidArchive => "de arquivo invalido",
+            FailureKind::Other => "por outros motivos",
         }
     }
 }
@@ -101,12 +313,176 @@ pub struct BeatmapMetadata {
     pub background_file: Option<String>,
     #[serde(default)]
     pub audio_file: Option<String>,
+    #[serde(default)]
+    pub has_video: bool,
+    #[serde(default)]
+    pub difficulty_info: Vec<DifficultyInfo>,
+    /// The `AudioFilename` file exists in the archive; older cache entries assume it does.
+    #[serde(default = "default_true")]
+    pub audio_present: bool,
+    /// Game modes found across the difficulties, sorted and without repeats.
+    #[serde(default)]
+    pub modes: Vec<u8>,
+    /// Where audio previews start (`PreviewTime`), in milliseconds.
+    #[serde(default)]
+    pub preview_time_ms: Option<u32>,
+    /// Original-script `TitleUnicode`/`ArtistUnicode`, kept only when they differ from the romanized ones.
+    #[serde(default)]
+    pub title_unicode: Option<String>,
+    #[serde(default)]
+    pub artist_unicode: Option<String>,
+    /// `Tags` of every difficulty, without repeats (case-insensitive), in first-seen order.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// `Source` of the song, from the first difficulty that has one.
+    #[serde(default)]
+    pub source: Option<String>,
 }
 
 impl BeatmapMetadata {
-    pub fn display_title(&self) -> String {
-        format!("{} - {}", self.artist, self.title)
+    /// Difficulties paired with their beatmap IDs, falling back to names only for older cache entries.
+    pub fn difficulty_links(&self) -> Vec<(String, Option<i32>)> {
+        if self.difficulty_info.is_empty() {
+            return self.difficulties.iter().map(|d| (d.clone(), None)).collect();
+        }
+        self.difficulty_info
+            .iter()
+            .map(|d| (d.version.clone(), d.beatmap_id))
+            .collect()
+    }
+
+    /// One "Version: AR 9 OD 8 ..." line per difficulty with known settings.
+    pub fn difficulty_settings_lines(&self) -> Vec<String> {
+        self.difficulty_info
+            .iter()
+            .filter_map(|d| {
+                let summary = d.settings.summary();
+                (!summary.is_empty()).then(|| format!("{}: {summary}", d.version))
+            })
+            .collect()
     }
+
+    /// Short mode summary such as "osu!/mania"; empty when unknown (older cache entries).
+    pub fn mode_label(&self) -> String {
+        self.modes
+            .iter()
+            .map(|m| mode_name(*m))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// `AudioFilename` points at a file the archive doesn't contain.
+    pub fn audio_missing(&self) -> bool {
+        self.audio_file.is_some() && !self.audio_present
+    }
+
+    /// Oldest `.osu` format version among the difficulties, when known.
+    pub fn oldest_format_version(&self) -> Option<u32> {
+        self.difficulty_info
+            .iter()
+            .filter_map(|d| d.format_version)
+            .min()
+    }
+
+    /// The title to show: the original script when `unicode` is set and the map has one.
+    pub fn title_for(&self, unicode: bool) -> &str {
+        match &self.title_unicode {
+            Some(original) if unicode => original,
+            _ => &self.title,
+        }
+    }
+
+    pub fn artist_for(&self, unicode: bool) -> &str {
+        match &self.artist_unicode {
+            Some(original) if unicode => original,
+            _ => &self.artist,
+        }
+    }
+
+    /// True when every whitespace-separated word of `filter` appears (case-insensitive) in the
+    /// title, artist, mapper, source or tags, romanized or not. An empty filter matches.
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let haystack = [
+            self.title.as_str(),
+            self.title_unicode.as_deref().unwrap_or_default(),
+            self.artist.as_str(),
+            self.artist_unicode.as_deref().unwrap_or_default(),
+            self.creator.as_str(),
+            self.source.as_deref().unwrap_or_default(),
+        ]
+        .into_iter()
+        .chain(self.tags.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+        filter
+            .split_whitespace()
+            .all(|word| haystack.contains(&word.to_lowercase()))
+    }
+
+    pub fn display_title(&self, unicode: bool) -> String {
+        let title = self.title_for(unicode);
+        let artist = self.artist_for(unicode);
+        if artist.is_empty() {
+            return title.to_string();
+        }
+        format!("{artist} - {title}")
+    }
+}
+
+pub fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        0 => "osu!",
+        1 => "taiko",
+        2 => "catch",
+        3 => "mania",
+        _ => "?",
+    }
+}
+
+/// Raw `[Difficulty]` values of one `.osu` file; `None` where the file doesn't set them.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DifficultySettings {
+    pub hp_drain: Option<f32>,
+    pub circle_size: Option<f32>,
+    pub overall_difficulty: Option<f32>,
+    /// Files older than v8 have no `ApproachRate`; it then equals `OverallDifficulty`.
+    pub approach_rate: Option<f32>,
+    pub slider_multiplier: Option<f32>,
+}
+
+impl DifficultySettings {
+    /// Short summary such as "AR 9 OD 8 CS 4 HP 6 SV 1.4"; empty when nothing was parsed.
+    pub fn summary(&self) -> String {
+        [
+            ("AR", self.approach_rate),
+            ("OD", self.overall_difficulty),
+            ("CS", self.circle_size),
+            ("HP", self.hp_drain),
+            ("SV", self.slider_multiplier),
+        ]
+        .iter()
+        .filter_map(|(label, value)| value.map(|v| format!("{label} {v}")))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// One `.osu` file inside the archive, keyed by its path in the ZIP.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DifficultyInfo {
+    pub version: String,
+    pub file_name: String,
+    /// `BeatmapID` from the `.osu` file; `None` for unsubmitted or legacy difficulties.
+    #[serde(default)]
+    pub beatmap_id: Option<i32>,
+    /// `osu file format vN` of this difficulty.
+    #[serde(default)]
+    pub format_version: Option<u32>,
+    #[serde(default)]
+    pub mode: Option<u8>,
+    #[serde(default)]
+    pub settings: DifficultySettings,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -124,6 +500,21 @@ pub struct BeatmapEntry {
     pub osz_hash: Option<String>,
     #[serde(default)]
     pub audio: AudioPreview,
+    /// Difficulty versions chosen for import; `None` means all of them.
+    #[serde(default)]
+    pub selected_difficulties: Option<Vec<String>>,
+    /// When the entry reached Completed or DuplicateSkipped.
+    #[serde(default)]
+    pub completed_at: Option<SystemTime>,
+    /// Category of the last failure; `None` while not failed or when the cause is not classified.
+    #[serde(default)]
+    pub failure_kind: Option<FailureKind>,
+    /// Extraction progress in percent while importing; not persisted.
+    #[serde(skip)]
+    pub import_progress: Option<u8>,
+    /// Songs folder picked for this entry; `None` imports into the default one.
+    #[serde(default)]
+    pub songs_target: Option<PathBuf>,
 }
 
 impl BeatmapEntry {
@@ -134,6 +525,61 @@ impl BeatmapEntry {
             .to_string_lossy()
             .to_string()
     }
+
+    pub fn is_difficulty_selected(&self, version: &str) -> bool {
+        self.selected_difficulties
+            .as_ref()
+            .map(|sel| sel.iter().any(|v| v == version))
+            .unwrap_or(true)
+    }
+}
+
+/// Detected entry for `osz_path` with nothing filled in; tests override only what they check.
+#[cfg(test)]
+pub fn test_entry(osz_path: impl Into<PathBuf>) -> BeatmapEntry {
+    BeatmapEntry {
+        id: 1,
+        osz_path: osz_path.into(),
+        status: ImportStatus::Detected,
+        message: None,
+        error_detail: None,
+        error_short: None,
+        metadata: None,
+        thumbnail_path: None,
+        detected_at: SystemTime::now(),
+        destination: None,
+        osz_hash: None,
+        audio: AudioPreview::default(),
+        selected_difficulties: None,
+        completed_at: None,
+        failure_kind: None,
+        import_progress: None,
+        songs_target: None,
+    }
+}
+
+/// "Artist - Title (Creator)" with no difficulties or optional fields.
+#[cfg(test)]
+pub fn test_metadata() -> BeatmapMetadata {
+    BeatmapMetadata {
+        title: "Title".into(),
+        artist: "Artist".into(),
+        creator: "Creator".into(),
+        difficulties: vec![],
+        beatmap_set_id: None,
+        beatmap_ids: vec![],
+        background_file: None,
+        audio_file: None,
+        has_video: false,
+        difficulty_info: vec![],
+        audio_present: true,
+        modes: vec![],
+        preview_time_ms: None,
+        title_unicode: None,
+        artist_unicode: None,
+        tags: vec![],
+        source: None,
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -160,6 +606,9 @@ pub struct AudioPreview {
     pub cached_path: Option<PathBuf>,
     #[serde(default)]
     pub last_error: Option<String>,
+    /// Set after the first preview streamed from the `.osz`; later previews use the audio cache.
+    #[serde(default)]
+    pub streamed_from_archive: bool,
 }
 
 impl Default for AudioPreview {
@@ -168,6 +617,7 @@ impl Default for AudioPreview {
             status: AudioPreviewStatus::Unknown,
             cached_path: None,
             last_error: None,
+            streamed_from_archive: false,
         }
     }
 }
@@ -184,6 +634,49 @@ pub fn sanitize_path_component(name: &str) -> String {
     cleaned.trim().trim_matches('.').trim().to_string()
 }
 
+/// Returns the first pattern matching any candidate (case-insensitive).
+/// Patterns with `*` or `?` are globs over the whole text; others match as substrings.
+pub fn find_ignore_match<'a>(patterns: &'a [String], candidates: &[&str]) -> Option<&'a str> {
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .find(|pattern| {
+            let pattern = pattern.to_lowercase();
+            candidates.iter().any(|text| {
+                let text = text.to_lowercase();
+                if pattern.contains(['*', '?']) {
+                    glob_matches(&pattern, &text)
+                } else {
+                    text.contains(&pattern)
+                }
+            })
+        })
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pat: Vec<char> = pattern.chars().collect();
+    let txt: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0usize, 0usize);
+    let mut star: Option<(usize, usize)> = None;
+    while t < txt.len() {
+        if p < pat.len() && (pat[p] == '?' || pat[p] == txt[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pat.len() && pat[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pat[p..].iter().all(|c| *c == '*')
+}
+
 pub fn ensure_dir(path: &Path) -> std::io::Result<()> {
     if !path.exists() {
         std::fs::create_dir_all(path)?;
@@ -195,6 +688,33 @@ pub fn ensure_dir(path: &Path) -> std::io::Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn default_songs_dir_stays_first_destination() {
+        let mut cfg = AppConfig {
+            songs_dir: PathBuf::from("/games/McOsu/Songs"),
+            songs_dirs: vec![],
+            ..AppConfig::default()
+        };
+        // Configs saved before `songs_dirs` existed.
+        assert_eq!(cfg.songs_destinations(), vec![PathBuf::from("/games/McOsu/Songs")]);
+
+        cfg.songs_dirs = vec![
+            PathBuf::from("/games/osu/Songs"),
+            PathBuf::from("/games/McOsu/Songs"),
+        ];
+        assert_eq!(
+            cfg.songs_destinations(),
+            vec![PathBuf::from("/games/McOsu/Songs"), PathBuf::from("/games/osu/Songs")]
+        );
+
+        cfg.songs_dirs = cfg.songs_destinations();
+        cfg.set_default_songs_dir(PathBuf::from("/games/McOsu2/Songs"));
+        assert_eq!(
+            cfg.songs_dirs,
+            vec![PathBuf::from("/games/McOsu2/Songs"), PathBuf::from("/games/osu/Songs")]
+        );
+    }
+
     #[test]
     fn sanitize_path_component_replaces_illegal_chars() {
         let name = sanitize_path_component("Artist:Title?*<>|/\\");
@@ -202,4 +722,20 @@ mod tests {
         assert!(!name.contains(':'));
         assert!(!name.contains('?'));
     }
+
+    #[test]
+    fn ignore_patterns_match_substrings_and_globs() {
+        let patterns = vec!["  ".to_string(), "test map".to_string(), "*[loved].osz".to_string()];
+        assert_eq!(
+            find_ignore_match(&patterns, &["My TEST Map.osz"]),
+            Some("test map")
+        );
+        assert_eq!(
+            find_ignore_match(&patterns, &["Artist - Song [Loved].osz"]),
+            Some("*[loved].osz")
+        );
+        assert_eq!(find_ignore_match(&patterns, &["Artist - Song.osz"]), None);
+        assert!(find_ignore_match(&["a?c".to_string()], &["abc"]).is_some());
+        assert!(find_ignore_match(&["a?c".to_string()], &["abbc"]).is_none());
+    }
 }
@@ -1,9 +1,13 @@
-use std::io::BufReader;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 
 use crate::app_state::AudioPreviewStatus;
 
@@ -15,14 +19,59 @@ pub struct AudioPlayer {
 struct PlayerState {
     stream: Option<OutputStream>,
     handle: Option<OutputStreamHandle>,
+    /// Name of the output device `stream` was opened on, to notice device switches.
+    device_name: Option<String>,
+    /// Last time the default device was looked up; enumerating devices is slow, so it is
+    /// only repeated every `DEVICE_CHECK_INTERVAL`.
+    device_checked: Option<Instant>,
     current: Option<Current>,
+    /// Loudness-matching gain per entry, so a map is only analysed once per session.
+    gains: HashMap<u64, f32>,
+    /// Bumped for every fresh preview, so a watcher only reacts to the playback it started with.
+    generation: u64,
 }
 
+/// RMS level (relative to full scale) previews are matched to.
+const TARGET_RMS: f32 = 0.15;
+const MIN_GAIN: f32 = 0.25;
+const MAX_GAIN: f32 = 2.0;
+/// Only the first seconds of the track are analysed to keep the preview snappy.
+const ANALYSIS_SECS: usize = 20;
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 struct Current {
     entry_id: u64,
+    generation: u64,
     sink: Sink,
 }
 
+/// Where the preview audio is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioSource {
+    File(PathBuf),
+    /// Decoded straight from the `.osz` entry, without writing it to the audio cache.
+    Archive { osz: PathBuf, audio_name: String },
+}
+
+/// Result of a toggle: the new state of the requested entry and, when another preview had to be
+/// stopped to start it, that entry's id so its UI state can be reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToggleOutcome {
+    pub status: AudioPreviewStatus,
+    pub stopped: Option<u64>,
+}
+
+/// What `poll_playback` found for the preview it was asked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackPoll {
+    /// Still playing or paused.
+    Active,
+    /// Reached the end of the track; the player no longer holds it.
+    Finished,
+    /// Stopped, or another preview took its place.
+    Replaced,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlaybackAction {
     StartFresh,
@@ -54,17 +103,36 @@ impl AudioPlayer {
             inner: Arc::new(Mutex::new(PlayerState {
                 stream: None,
                 handle: None,
+                device_name: None,
+                device_checked: None,
                 current: None,
+                gains: HashMap::new(),
+                generation: 0,
             })),
         }
     }
 
-    pub fn toggle(&self, entry_id: u64, path: &Path) -> Result<AudioPreviewStatus> {
+    /// `start_ms` is where a fresh preview begins (the map's `PreviewTime`); resuming a paused
+    /// preview keeps its position.
+    pub fn toggle(
+        &self,
+        entry_id: u64,
+        source: &AudioSource,
+        normalize: bool,
+        start_ms: Option<u32>,
+    ) -> Result<ToggleOutcome> {
         let mut guard = self
             .inner
             .lock()
             .map_err(|e| anyhow::anyhow!("audio lock poisoned: {e}"))?;
-        guard.ensure_stream()?;
+        // A preview cut off by a device change can't be paused/resumed; report it as stopped.
+        let mut stopped = guard.ensure_stream()?;
+        // A preview that already played to the end starts over instead of pausing an empty sink.
+        if let Some(finished) = guard.take_finished() {
+            if finished != entry_id {
+                stopped = Some(finished);
+            }
+        }
 
         let action = decide_playback_action(
             guard
@@ -78,49 +146,368 @@ impl AudioPlayer {
                 match action {
                     PlaybackAction::PauseCurrent => {
                         current.sink.pause();
-                        return Ok(AudioPreviewStatus::Paused);
+                        return Ok(ToggleOutcome {
+                            status: AudioPreviewStatus::Paused,
+                            stopped: None,
+                        });
                     }
                     PlaybackAction::ResumeCurrent => {
                         current.sink.play();
-                        return Ok(AudioPreviewStatus::Playing);
+                        return Ok(ToggleOutcome {
+                            status: AudioPreviewStatus::Playing,
+                            stopped: None,
+                        });
                     }
                     _ => {}
                 }
             }
         }
-        if matches!(action, PlaybackAction::StopThenStart) {
-            if let Some(current) = guard.current.take() {
-                current.sink.stop();
+
+        let known_gain = guard.gains.get(&entry_id).copied();
+        drop(guard);
+
+        // Decoding (and the loudness analysis) runs without the lock, so stop/poll calls from
+        // the UI aren't held up. A failure here leaves the previous preview untouched.
+        let decoded = decode(source)?;
+        let offset = preview_offset(start_ms, decoded.total_duration());
+        let (decoded, gain) = match (normalize, known_gain) {
+            (false, _) => (decoded, None),
+            (true, Some(gain)) => (decoded, Some(gain)),
+            (true, None) => {
+                let (decoded, rms) = measure_rms(decoded);
+                (decoded, Some(rms.map(gain_for_rms).unwrap_or(1.0)))
+            }
+        };
+
+        let mut guard = self
+            .inner
+            .lock()
+            .map_err(|e| anyhow::anyhow!("audio lock poisoned: {e}"))?;
+        let sink = match guard.new_sink() {
+            Ok(sink) => sink,
+            Err(_) => {
+                // The cached stream died (device unplugged or switched); reopen it once.
+                if let Some(lost) = guard.reset_stream() {
+                    stopped = Some(lost);
+                }
+                guard.ensure_stream()?;
+                guard.new_sink().context("criando sink de audio")?
             }
+        };
+        sink.pause();
+        match offset {
+            Some(offset) => sink.append(decoded.skip_duration(offset)),
+            None => sink.append(decoded),
+        }
+        if let Some(gain) = gain {
+            guard.gains.insert(entry_id, gain);
+            sink.set_volume(gain);
         }
 
-        let handle = guard
-            .handle
-            .as_ref()
-            .context("saida de audio indisponivel")?
-            .clone();
-        let file = std::fs::File::open(path).with_context(|| format!("abrindo audio {:?}", path))?;
-        let sink = Sink::try_new(&handle).context("criando sink de audio")?;
-        let source = Decoder::new(BufReader::new(file)).context("decodificando audio")?;
-        sink.append(source);
+        // Whatever the player holds now (the preview `action` saw, or one started while the
+        // lock was released) makes way for this one.
+        if let Some(current) = guard.current.take() {
+            current.sink.stop();
+            if current.entry_id != entry_id {
+                stopped = Some(current.entry_id);
+            }
+        }
         sink.play();
 
+        guard.generation += 1;
+        let generation = guard.generation;
         guard.current = Some(Current {
             entry_id,
+            generation,
             sink,
         });
-        Ok(AudioPreviewStatus::Playing)
+        Ok(ToggleOutcome {
+            status: AudioPreviewStatus::Playing,
+            stopped,
+        })
+    }
+
+    /// Stops `entry_id`'s preview when it is the one the player holds. Either way nothing of
+    /// that entry is playing afterwards, so the returned status is always `Ready`.
+    pub fn stop(&self, entry_id: u64) -> AudioPreviewStatus {
+        if let Ok(mut guard) = self.inner.lock() {
+            if guard.current.as_ref().is_some_and(|c| c.entry_id == entry_id) {
+                if let Some(current) = guard.current.take() {
+                    current.sink.stop();
+                }
+            }
+        }
+        AudioPreviewStatus::Ready
+    }
+
+    /// Stops whatever preview is playing or paused; returns the entry it belonged to.
+    pub fn stop_all(&self) -> Option<u64> {
+        let mut guard = self.inner.lock().ok()?;
+        let current = guard.current.take()?;
+        current.sink.stop();
+        Some(current.entry_id)
+    }
+
+    /// Entry id and generation of the preview the player holds, to pass to `poll_playback`.
+    pub fn current_playback(&self) -> Option<(u64, u64)> {
+        let guard = self.inner.lock().ok()?;
+        guard.current.as_ref().map(|c| (c.entry_id, c.generation))
+    }
+
+    /// Checks on the preview `current_playback` reported; a finished one is released.
+    pub fn poll_playback(&self, entry_id: u64, generation: u64) -> PlaybackPoll {
+        match self.inner.lock() {
+            Ok(mut guard) => guard.poll(entry_id, generation),
+            Err(_) => PlaybackPoll::Replaced,
+        }
+    }
+}
+
+fn decode(source: &AudioSource) -> Result<BoxedSource> {
+    fn boxed<R>(reader: R) -> Result<BoxedSource>
+    where
+        R: Read + Seek + Send + Sync + 'static,
+    {
+        Ok(Box::new(Decoder::new(reader).context("decodificando audio")?))
+    }
+    match source {
+        AudioSource::File(path) => {
+            let file = File::open(path).with_context(|| format!("abrindo audio {:?}", path))?;
+            boxed(BufReader::new(file))
+        }
+        AudioSource::Archive { osz, audio_name } => match open_archive_audio(osz, audio_name)? {
+            ArchiveAudio::Stored(slice) => boxed(BufReader::new(slice)),
+            ArchiveAudio::Buffered(data) => boxed(data),
+        },
+    }
+}
+
+/// Offset a preview starts at; `None` (play from the start) when unset or past the known end.
+fn preview_offset(start_ms: Option<u32>, total: Option<Duration>) -> Option<Duration> {
+    let offset = Duration::from_millis(u64::from(start_ms?));
+    if offset.is_zero() || total.is_some_and(|total| offset >= total) {
+        return None;
+    }
+    Some(offset)
+}
+
+type BoxedSource = Box<dyn Source<Item = i16> + Send>;
+
+/// RMS of the first `ANALYSIS_SECS` of audio, relative to full scale (`None` for silence).
+/// The analysed samples are kept and replayed ahead of the rest, so the track is decoded once.
+fn measure_rms(mut source: BoxedSource) -> (BoxedSource, Option<f32>) {
+    let channels = source.channels();
+    let sample_rate = source.sample_rate();
+    let limit = sample_rate as usize * channels as usize * ANALYSIS_SECS;
+    let head: Vec<i16> = source.by_ref().take(limit).collect();
+    let sum: f64 = head
+        .iter()
+        .map(|sample| {
+            let value = *sample as f64 / i16::MAX as f64;
+            value * value
+        })
+        .sum();
+    let rms = (!head.is_empty())
+        .then(|| (sum / head.len() as f64).sqrt() as f32)
+        .filter(|rms| *rms > f32::EPSILON);
+    let replay: BoxedSource = Box::new(SamplesBuffer::new(channels, sample_rate, head));
+    (Box::new(rodio::source::from_iter([replay, source])), rms)
+}
+
+pub(crate) fn gain_for_rms(rms: f32) -> f32 {
+    (TARGET_RMS / rms).clamp(MIN_GAIN, MAX_GAIN)
+}
+
+/// Extensions decodable with the rodio features enabled in `Cargo.toml` (mp3, wav, vorbis).
+const SUPPORTED_EXTENSIONS: &[&str] = &["mp3", "wav", "ogg"];
+
+/// Returns the lowercase extension of `audio_name` when rodio can't decode it.
+pub(crate) fn unsupported_format(audio_name: &str) -> Option<String> {
+    let ext = Path::new(audio_name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    (!SUPPORTED_EXTENSIONS.contains(&ext.as_str())).then_some(ext)
+}
+
+/// Matches an archive entry against the beatmap's `AudioFilename`, by full path or file name.
+pub(crate) fn is_audio_entry(name_in_zip: &str, audio_name: &str) -> bool {
+    let lower_name = audio_name.to_lowercase();
+    let filename_only = Path::new(name_in_zip)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name_in_zip.to_lowercase().ends_with(&lower_name) || filename_only == lower_name
+}
+
+enum ArchiveAudio {
+    /// Uncompressed entry: read in place from the `.osz` file.
+    Stored(EntrySlice),
+    /// Compressed entry: inflated into memory, never touching the disk.
+    Buffered(Cursor<Vec<u8>>),
+}
+
+fn open_archive_audio(osz: &Path, audio_name: &str) -> Result<ArchiveAudio> {
+    let file = File::open(osz).with_context(|| format!("abrindo {:?}", osz))?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let index = (0..archive.len())
+        .find(|&i| {
+            archive
+                .by_index_raw(i)
+                .map(|item| is_audio_entry(item.name(), audio_name))
+                .unwrap_or(false)
+        })
+        .context("Audio nao encontrado dentro do .osz")?;
+    let mut item = archive.by_index(index)?;
+    if item.compression() == zip::CompressionMethod::Stored {
+        let start = item.data_start();
+        let len = item.size();
+        drop(item);
+        let file = File::open(osz).with_context(|| format!("abrindo {:?}", osz))?;
+        return Ok(ArchiveAudio::Stored(EntrySlice::new(file, start, len)?));
+    }
+    let mut data = Vec::with_capacity(item.size() as usize);
+    item.read_to_end(&mut data)?;
+    Ok(ArchiveAudio::Buffered(Cursor::new(data)))
+}
+
+/// Read/seek view over `len` bytes of `file` starting at `start`.
+struct EntrySlice {
+    file: File,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl EntrySlice {
+    fn new(mut file: File, start: u64, len: u64) -> std::io::Result<Self> {
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Self {
+            file,
+            start,
+            len,
+            pos: 0,
+        })
+    }
+}
+
+impl Read for EntrySlice {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let max = (buf.len() as u64).min(remaining) as usize;
+        if max == 0 {
+            return Ok(0);
+        }
+        let read = self.file.read(&mut buf[..max])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for EntrySlice {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.len as i64 + offset,
+            SeekFrom::Current(offset) => self.pos as i64 + offset,
+        };
+        if target < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek antes do inicio do audio",
+            ));
+        }
+        let target = (target as u64).min(self.len);
+        self.file.seek(SeekFrom::Start(self.start + target))?;
+        self.pos = target;
+        Ok(target)
     }
 }
 
 impl PlayerState {
-    fn ensure_stream(&mut self) -> Result<()> {
+    /// Opens the default output if needed, reopening it when the default device changed since
+    /// the stream was created. Returns the entry whose preview was dropped by a reopen.
+    fn ensure_stream(&mut self) -> Result<Option<u64>> {
+        let mut lost = None;
+        let now = Instant::now();
+        if self.stream.is_some() && device_check_due(self.device_checked, now) {
+            self.device_checked = Some(now);
+            if stream_outdated(self.device_name.as_deref(), default_device_name().as_deref()) {
+                lost = self.reset_stream();
+            }
+        }
         if self.stream.is_none() || self.handle.is_none() {
             let (stream, handle) = OutputStream::try_default().context("nenhum dispositivo de audio encontrado")?;
             self.stream = Some(stream);
             self.handle = Some(handle);
+            self.device_name = default_device_name();
+            self.device_checked = Some(now);
         }
-        Ok(())
+        Ok(lost)
+    }
+
+    fn poll(&mut self, entry_id: u64, generation: u64) -> PlaybackPoll {
+        match self.current.as_ref() {
+            Some(c) if c.entry_id == entry_id && c.generation == generation => {
+                if c.sink.empty() {
+                    self.current = None;
+                    PlaybackPoll::Finished
+                } else {
+                    PlaybackPoll::Active
+                }
+            }
+            _ => PlaybackPoll::Replaced,
+        }
+    }
+
+    /// Releases the current preview if its track has ended, returning its entry id.
+    fn take_finished(&mut self) -> Option<u64> {
+        if self.current.as_ref().is_some_and(|c| c.sink.empty()) {
+            self.current.take().map(|c| c.entry_id)
+        } else {
+            None
+        }
+    }
+
+    fn new_sink(&self) -> Result<Sink> {
+        let handle = self.handle.as_ref().context("saida de audio indisponivel")?;
+        Ok(Sink::try_new(handle)?)
+    }
+
+    /// Drops the cached stream so the next preview opens the current default device.
+    fn reset_stream(&mut self) -> Option<u64> {
+        let lost = self.current.take().map(|current| {
+            current.sink.stop();
+            current.entry_id
+        });
+        self.handle = None;
+        self.stream = None;
+        self.device_name = None;
+        lost
+    }
+}
+
+fn default_device_name() -> Option<String> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+    rodio::cpal::default_host()
+        .default_output_device()?
+        .name()
+        .ok()
+}
+
+/// Whether the default device should be looked up again; a failing sink resets the stream
+/// regardless, so a stale answer only delays noticing a switch to another working device.
+fn device_check_due(last: Option<Instant>, now: Instant) -> bool {
+    last.is_none_or(|last| now.duration_since(last) >= DEVICE_CHECK_INTERVAL)
+}
+
+/// The stream must be reopened when the default device is gone or is a different one.
+/// An unknown name at open time gives no basis for comparison, so it is kept.
+fn stream_outdated(opened_on: Option<&str>, default_now: Option<&str>) -> bool {
+    match (opened_on, default_now) {
+        (Some(opened), Some(now)) => opened != now,
+        (Some(_), None) => true,
+        (None, _) => false,
     }
 }
 
@@ -128,6 +515,91 @@ impl PlayerState {
 mod tests {
     use super::*;
 
+    fn idle_state(entry_id: u64, generation: u64, with_audio: bool) -> PlayerState {
+        let (sink, _queue) = Sink::new_idle();
+        if with_audio {
+            sink.append(rodio::source::SineWave::new(440.0));
+        }
+        PlayerState {
+            stream: None,
+            handle: None,
+            device_name: None,
+            device_checked: None,
+            current: Some(Current {
+                entry_id,
+                generation,
+                sink,
+            }),
+            gains: HashMap::new(),
+            generation,
+        }
+    }
+
+    #[test]
+    fn poll_reports_the_end_of_its_own_playback_only() {
+        let mut playing = idle_state(1, 3, true);
+        assert_eq!(playing.poll(1, 3), PlaybackPoll::Active);
+        assert_eq!(playing.poll(1, 2), PlaybackPoll::Replaced);
+        assert_eq!(playing.poll(2, 3), PlaybackPoll::Replaced);
+        assert_eq!(playing.take_finished(), None);
+
+        let mut ended = idle_state(1, 3, false);
+        assert_eq!(ended.poll(1, 2), PlaybackPoll::Replaced);
+        assert_eq!(ended.poll(1, 3), PlaybackPoll::Finished);
+        assert!(ended.current.is_none());
+        assert_eq!(ended.poll(1, 3), PlaybackPoll::Replaced);
+
+        let mut ended = idle_state(7, 1, false);
+        assert_eq!(ended.take_finished(), Some(7));
+        assert!(ended.current.is_none());
+    }
+
+    #[test]
+    fn stop_only_touches_the_matching_preview() {
+        let player = AudioPlayer {
+            inner: Arc::new(Mutex::new(idle_state(5, 1, true))),
+        };
+        assert_eq!(player.stop(6), AudioPreviewStatus::Ready);
+        assert_eq!(player.current_playback(), Some((5, 1)));
+        assert_eq!(player.stop(5), AudioPreviewStatus::Ready);
+        assert_eq!(player.current_playback(), None);
+
+        let player = AudioPlayer {
+            inner: Arc::new(Mutex::new(idle_state(8, 2, true))),
+        };
+        assert_eq!(player.stop_all(), Some(8));
+        assert_eq!(player.stop_all(), None);
+    }
+
+    #[test]
+    fn stream_reopens_when_default_device_changes_or_vanishes() {
+        assert!(!stream_outdated(Some("Speakers"), Some("Speakers")));
+        assert!(stream_outdated(Some("Speakers"), Some("Headphones")));
+        assert!(stream_outdated(Some("Headphones"), None));
+        assert!(!stream_outdated(None, Some("Speakers")));
+    }
+
+    #[test]
+    fn default_device_is_looked_up_at_most_every_interval() {
+        let now = Instant::now();
+        assert!(device_check_due(None, now));
+        assert!(!device_check_due(Some(now), now + Duration::from_secs(1)));
+        assert!(device_check_due(Some(now), now + DEVICE_CHECK_INTERVAL));
+    }
+
+    #[test]
+    fn loudness_analysis_replays_the_samples_it_read() {
+        // 10 Hz mono: 200 samples are analysed, the remaining 100 still play afterwards.
+        let samples: Vec<i16> = (0..300).map(|i| if i % 2 == 0 { 16384 } else { -16384 }).collect();
+        let source: BoxedSource = Box::new(SamplesBuffer::new(1, 10, samples.clone()));
+        let (replayed, rms) = measure_rms(source);
+        assert!((rms.unwrap() - 0.5).abs() < 0.01);
+        assert_eq!(replayed.collect::<Vec<i16>>(), samples);
+
+        let silent: BoxedSource = Box::new(SamplesBuffer::new(1, 10, vec![0i16; 5]));
+        assert_eq!(measure_rms(silent).1, None);
+    }
+
     #[test]
     fn decide_playback_action_handles_singleton() {
         assert_eq!(decide_playback_action(None, 1), PlaybackAction::StartFresh);
@@ -144,4 +616,68 @@ mod tests {
             PlaybackAction::StopThenStart
         );
     }
+
+    #[test]
+    fn preview_starts_at_preview_time_when_in_range() {
+        let total = Some(Duration::from_secs(90));
+        assert_eq!(preview_offset(Some(30_000), total), Some(Duration::from_secs(30)));
+        assert_eq!(preview_offset(Some(30_000), None), Some(Duration::from_secs(30)));
+        assert_eq!(preview_offset(Some(120_000), total), None);
+        assert_eq!(preview_offset(Some(0), total), None);
+        assert_eq!(preview_offset(None, total), None);
+    }
+
+    #[test]
+    fn gain_matches_target_within_bounds() {
+        assert!((gain_for_rms(TARGET_RMS) - 1.0).abs() < f32::EPSILON);
+        assert!((gain_for_rms(0.3) - 0.5).abs() < 1e-6);
+        assert_eq!(gain_for_rms(0.9), MIN_GAIN);
+        assert_eq!(gain_for_rms(0.001), MAX_GAIN);
+    }
+
+    #[test]
+    fn unsupported_format_reports_extension() {
+        assert_eq!(unsupported_format("audio.mp3"), None);
+        assert_eq!(unsupported_format("Audio.OGG"), None);
+        assert_eq!(unsupported_format("track.flac"), Some("flac".into()));
+        assert_eq!(unsupported_format("audio"), Some(String::new()));
+    }
+
+    #[test]
+    fn audio_entry_matches_path_or_file_name() {
+        assert!(is_audio_entry("audio.mp3", "Audio.MP3"));
+        assert!(is_audio_entry("sub/audio.mp3", "audio.mp3"));
+        assert!(!is_audio_entry("audio.ogg", "audio.mp3"));
+    }
+
+    #[test]
+    fn stored_entry_reads_only_its_bytes() {
+        use std::io::Write;
+        use zip::write::FileOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let osz = dir.path().join("map.osz");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&osz).unwrap());
+            let stored = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+            zip.start_file("map.osu", stored).unwrap();
+            zip.write_all(b"osu file format v14").unwrap();
+            zip.start_file("audio.mp3", stored).unwrap();
+            zip.write_all(b"0123456789").unwrap();
+            zip.start_file("bg.jpg", FileOptions::default()).unwrap();
+            zip.write_all(b"trailing").unwrap();
+            zip.finish().unwrap();
+        }
+        let ArchiveAudio::Stored(mut slice) = open_archive_audio(&osz, "audio.mp3").unwrap() else {
+            panic!("stored entry should be read in place");
+        };
+        let mut all = Vec::new();
+        slice.read_to_end(&mut all).unwrap();
+        assert_eq!(all, b"0123456789");
+        assert_eq!(slice.seek(SeekFrom::End(-3)).unwrap(), 7);
+        let mut tail = String::new();
+        slice.read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "789");
+        assert!(slice.seek(SeekFrom::Current(-20)).is_err());
+    }
 }
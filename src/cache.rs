@@ -1,14 +1,18 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
+use std::time::SystemTime;
 
 use anyhow::{Context, Result, anyhow};
 use directories::ProjectDirs;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::app_state::AppConfig;
+use crate::path_utils::is_within_dir;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CacheData {
@@ -17,13 +21,55 @@ pub struct CacheData {
     pub osz_hashes: HashMap<String, PathBuf>,
     #[serde(default)]
     pub audio_files: HashMap<String, PathBuf>,
+    /// Sources already imported or skipped as duplicates, keyed by path.
+    #[serde(default)]
+    pub processed_sources: HashMap<String, SourceStamp>,
+    /// Last time (unix seconds) a cached thumbnail or audio file was handed out, for LRU eviction.
+    #[serde(default)]
+    pub last_used: HashMap<PathBuf, u64>,
+}
+
+/// Size and mtime of a source file when it was processed; any change means reprocess it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStamp {
+    pub size: u64,
+    pub modified_secs: u64,
+}
+
+impl SourceStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let modified_secs = meta
+            .modified()
+            .ok()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        Some(Self {
+            size: meta.len(),
+            modified_secs,
+        })
+    }
+}
+
+fn source_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
 }
 
 #[derive(Debug)]
 pub struct CacheStore {
     inner: Mutex<CacheData>,
+    /// Cap for cached audio plus thumbnails, in bytes (0 = unlimited).
+    media_limit: AtomicU64,
+    /// Running size of cached audio plus thumbnails, so inserts under the cap skip the folder
+    /// scan. May overshoot (a file cached twice); the scan done when it looks over the cap
+    /// corrects it.
+    media_bytes: AtomicU64,
 }
 
+/// `media_bytes` before the first scan (or after the folders were cleared).
+const UNKNOWN_MEDIA_BYTES: u64 = u64::MAX;
+
 impl CacheStore {
     pub fn load() -> Self {
         let _ = std::fs::create_dir_all(cache_dir());
@@ -32,19 +78,70 @@ impl CacheStore {
         let inner = data
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
-        Self {
+        let store = Self {
             inner: Mutex::new(inner),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        let pruned = store.prune_missing();
+        if pruned > 0 {
+            info!("{pruned} entrada(s) do cache apontavam para pastas removidas");
+            if let Err(err) = store.save() {
+                warn!("Falha ao salvar cache apos limpeza: {err:#}");
+            }
         }
+        store
+    }
+
+    /// Drops entries pointing at folders or files that were deleted (e.g. a beatmap removed
+    /// from Songs by hand), so re-imports aren't reported as duplicates of nothing. Files
+    /// under the app's own cache dir are left alone. Returns how many entries were removed.
+    pub fn prune_missing(&self) -> usize {
+        self.prune_missing_outside(&cache_dir())
+    }
+
+    fn prune_missing_outside(&self, cache_root: &Path) -> usize {
+        let Ok(mut guard) = self.inner.lock() else {
+            return 0;
+        };
+        let keep = |path: &PathBuf| path.exists() || is_within_dir(cache_root, path);
+        let before = guard.thumbnails.len()
+            + guard.beatmap_sets.len()
+            + guard.osz_hashes.len()
+            + guard.audio_files.len();
+        guard.thumbnails.retain(|_, path| keep(path));
+        guard.beatmap_sets.retain(|_, path| keep(path));
+        guard.osz_hashes.retain(|_, path| keep(path));
+        guard.audio_files.retain(|_, path| keep(path));
+        guard.last_used.retain(|path, _| path.exists());
+        let after = guard.thumbnails.len()
+            + guard.beatmap_sets.len()
+            + guard.osz_hashes.len()
+            + guard.audio_files.len();
+        before - after
     }
 
+    /// Cached thumbnail for `key`; an entry whose PNG was deleted (e.g. the cache folder was
+    /// cleared by hand) is dropped and `None` returned so the caller regenerates it.
     pub fn get_thumbnail(&self, key: &str) -> Option<PathBuf> {
-        self.inner.lock().ok()?.thumbnails.get(key).cloned()
+        let mut guard = self.inner.lock().ok()?;
+        let path = guard.thumbnails.get(key).cloned()?;
+        if !path.is_file() {
+            guard.thumbnails.remove(key);
+            guard.last_used.remove(&path);
+            return None;
+        }
+        guard.last_used.insert(path.clone(), unix_secs());
+        Some(path)
     }
 
     pub fn insert_thumbnail(&self, key: String, path: PathBuf) {
         if let Ok(mut guard) = self.inner.lock() {
-            guard.thumbnails.insert(key, path);
+            guard.last_used.insert(path.clone(), unix_secs());
+            guard.thumbnails.insert(key, path.clone());
         }
+        self.track_media(&path);
+        self.evict_media(Some(&path));
     }
 
     pub fn register_beatmap_set(&self, set_id: i32, path: PathBuf) {
@@ -61,8 +158,185 @@ impl CacheStore {
 
     pub fn register_audio(&self, hash: String, path: PathBuf) {
         if let Ok(mut guard) = self.inner.lock() {
-            guard.audio_files.insert(hash, path);
+            guard.last_used.insert(path.clone(), unix_secs());
+            guard.audio_files.insert(hash, path.clone());
         }
+        self.track_media(&path);
+        self.evict_media(Some(&path));
+    }
+
+    /// Sets the combined size cap for cached audio and thumbnails (0 = unlimited).
+    pub fn set_media_limit_mb(&self, mb: u64) {
+        self.media_limit
+            .store(mb.saturating_mul(1024 * 1024), Ordering::SeqCst);
+    }
+
+    /// Deletes the least recently used cached audio and thumbnails until both fit in the
+    /// configured cap; returns the deleted files.
+    pub fn enforce_limits(&self) -> Vec<PathBuf> {
+        self.evict_media(None)
+    }
+
+    /// Deletes every cached thumbnail, audio file and map preview and forgets them; the
+    /// duplicate index is kept. Returns how many bytes were freed.
+    pub fn clear_media(&self) -> u64 {
+        self.clear_dirs(&[thumbnails_dir(), audio_cache_dir(), preview_dir()])
+    }
+
+    fn clear_dirs(&self, roots: &[PathBuf]) -> u64 {
+        let mut freed = 0u64;
+        for root in roots {
+            let before = dir_size(root);
+            if let Ok(read) = fs::read_dir(root) {
+                for entry in read.flatten() {
+                    let path = entry.path();
+                    let removed = if path.is_dir() {
+                        fs::remove_dir_all(&path)
+                    } else {
+                        fs::remove_file(&path)
+                    };
+                    if let Err(err) = removed {
+                        warn!("Nao foi possivel apagar {}: {err}", path.display());
+                    }
+                }
+            }
+            freed += before.saturating_sub(dir_size(root));
+        }
+        if let Ok(mut guard) = self.inner.lock() {
+            guard.thumbnails.clear();
+            guard.audio_files.clear();
+            guard.last_used.clear();
+        }
+        self.media_bytes.store(UNKNOWN_MEDIA_BYTES, Ordering::SeqCst);
+        freed
+    }
+
+    fn track_media(&self, path: &Path) {
+        let Ok(meta) = fs::metadata(path) else {
+            return;
+        };
+        let _ = self
+            .media_bytes
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |bytes| {
+                (bytes != UNKNOWN_MEDIA_BYTES).then(|| bytes.saturating_add(meta.len()))
+            });
+    }
+
+    fn evict_media(&self, keep: Option<&Path>) -> Vec<PathBuf> {
+        let max_bytes = self.media_limit.load(Ordering::SeqCst);
+        self.evict_lru(&[thumbnails_dir(), audio_cache_dir()], max_bytes, keep)
+    }
+
+    /// Files never handed out since this was tracked fall back to their mtime. `keep` (the file
+    /// just cached) is never evicted.
+    fn evict_lru(&self, roots: &[PathBuf], max_bytes: u64, keep: Option<&Path>) -> Vec<PathBuf> {
+        if max_bytes == 0 {
+            return Vec::new();
+        }
+        let known = match self.media_bytes.load(Ordering::SeqCst) {
+            UNKNOWN_MEDIA_BYTES => roots.iter().map(|root| dir_size(root)).sum(),
+            bytes => bytes,
+        };
+        if known <= max_bytes {
+            self.media_bytes.store(known, Ordering::SeqCst);
+            return Vec::new();
+        }
+        let mut files: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
+        for root in roots {
+            collect_files(root, &mut files);
+        }
+        let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+        if total <= max_bytes {
+            self.media_bytes.store(total, Ordering::SeqCst);
+            return Vec::new();
+        }
+        let Ok(mut guard) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let last_used = |path: &Path, modified: SystemTime| {
+            guard.last_used.get(path).copied().unwrap_or_else(|| {
+                modified
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            })
+        };
+        let mut ranked: Vec<(u64, PathBuf, u64)> = files
+            .into_iter()
+            .map(|(path, size, modified)| (last_used(&path, modified), path, size))
+            .collect();
+        ranked.sort_by_key(|(used, _, _)| *used);
+        let mut evicted = Vec::new();
+        for (_, path, size) in ranked {
+            if total <= max_bytes {
+                break;
+            }
+            if keep == Some(path.as_path()) {
+                continue;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+                // Audio lives in one folder per map; drop it once empty (roots stay).
+                if let Some(parent) = path.parent().filter(|p| !roots.iter().any(|r| r == p)) {
+                    let _ = fs::remove_dir(parent);
+                }
+                evicted.push(path);
+            }
+        }
+        guard.thumbnails.retain(|_, path| !evicted.contains(path));
+        guard.audio_files.retain(|_, path| !evicted.contains(path));
+        guard.last_used.retain(|path, _| !evicted.contains(path));
+        self.media_bytes.store(total, Ordering::SeqCst);
+        evicted
+    }
+
+    pub fn mark_processed(&self, path: &Path) {
+        let Some(stamp) = SourceStamp::of(path) else {
+            return;
+        };
+        if let Ok(mut guard) = self.inner.lock() {
+            guard.processed_sources.insert(source_key(path), stamp);
+        }
+    }
+
+    /// True when `path` was processed before and its size and mtime are unchanged.
+    pub fn is_processed(&self, path: &Path) -> bool {
+        let Some(stamp) = SourceStamp::of(path) else {
+            return false;
+        };
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|guard| guard.processed_sources.get(&source_key(path)).copied())
+            == Some(stamp)
+    }
+
+    /// Drops processed entries whose source no longer exists.
+    pub fn prune_processed(&self) {
+        if let Ok(mut guard) = self.inner.lock() {
+            guard
+                .processed_sources
+                .retain(|path, _| Path::new(path).exists());
+        }
+    }
+
+    /// Forgets a source so it's no longer treated as already processed.
+    pub fn unmark_processed(&self, path: &Path) {
+        if let Ok(mut guard) = self.inner.lock() {
+            guard.processed_sources.remove(&source_key(path));
+        }
+    }
+
+    /// Drops set and hash entries pointing at `dest` (or inside it), e.g. after an undone
+    /// import. Returns how many entries were removed.
+    pub fn forget_destination(&self, dest: &Path) -> usize {
+        let Ok(mut guard) = self.inner.lock() else {
+            return 0;
+        };
+        let before = guard.beatmap_sets.len() + guard.osz_hashes.len();
+        guard.beatmap_sets.retain(|_, path| !is_within_dir(dest, path));
+        guard.osz_hashes.retain(|_, path| !is_within_dir(dest, path));
+        before - guard.beatmap_sets.len() - guard.osz_hashes.len()
     }
 
     pub fn find_set(&self, set_id: i32) -> Option<PathBuf> {
@@ -74,7 +348,10 @@ impl CacheStore {
     }
 
     pub fn find_audio(&self, hash: &str) -> Option<PathBuf> {
-        self.inner.lock().ok()?.audio_files.get(hash).cloned()
+        let mut guard = self.inner.lock().ok()?;
+        let path = guard.audio_files.get(hash).cloned()?;
+        guard.last_used.insert(path.clone(), unix_secs());
+        Some(path)
     }
 
     pub fn save(&self) -> Result<()> {
@@ -91,8 +368,17 @@ impl CacheStore {
 pub fn load_config() -> AppConfig {
     migrate_legacy_files().ok();
     let data = fs::read_to_string(config_path()).ok();
-    data.and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_default()
+    let mut cfg: AppConfig = data
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    // Configs from before multiple destinations only have `songs_dir`.
+    cfg.songs_dirs = cfg.songs_destinations();
+    cfg
+}
+
+/// Whether a config was saved before (including a legacy one next to the executable).
+pub fn config_exists() -> bool {
+    config_path().exists() || PathBuf::from("config.json").exists()
 }
 
 pub fn save_config(cfg: &AppConfig) -> Result<()> {
@@ -104,10 +390,85 @@ pub fn save_config(cfg: &AppConfig) -> Result<()> {
     Ok(())
 }
 
+/// Machine-specific settings left out of exported profiles on request.
+const MACHINE_PATH_KEYS: [&str; 4] = ["downloads_dir", "songs_dir", "songs_dirs", "extra_watch_dirs"];
+
+/// Serializes `cfg` as a shareable settings profile, optionally without the Downloads/Songs folders.
+pub fn export_config_profile(cfg: &AppConfig, include_paths: bool) -> Result<String> {
+    let mut value = serde_json::to_value(cfg)?;
+    if !include_paths {
+        if let Some(obj) = value.as_object_mut() {
+            for key in MACHINE_PATH_KEYS {
+                obj.remove(key);
+            }
+        }
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Applies a settings profile on top of `current`; anything the profile leaves out
+/// (such as excluded paths) keeps its current value.
+pub fn merge_config_profile(current: &AppConfig, profile: &str) -> Result<AppConfig> {
+    let imported: serde_json::Value = serde_json::from_str(profile).context("perfil nao e JSON valido")?;
+    let serde_json::Value::Object(imported) = imported else {
+        return Err(anyhow!("perfil deve ser um objeto JSON"));
+    };
+    let mut merged = serde_json::to_value(current)?;
+    if let Some(obj) = merged.as_object_mut() {
+        obj.extend(imported);
+    }
+    serde_json::from_value(merged).context("perfil com valores invalidos")
+}
+
+const DATA_DIR_NAME: &str = "mcosu-importer";
+
+/// Resolved once so config, cache and logs always share the same root.
+static BASE_DIR: Lazy<(PathBuf, Option<String>)> = Lazy::new(|| {
+    resolve_base_dir(
+        ProjectDirs::from("dev", "mcosu", "mcosu-importer").map(|p| p.data_local_dir().to_path_buf()),
+        |name| std::env::var_os(name).map(PathBuf::from),
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf)),
+    )
+});
+
 pub fn base_dir() -> PathBuf {
-    let proj = ProjectDirs::from("dev", "mcosu", "mcosu-importer");
-    proj.map(|p| p.data_local_dir().to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("."))
+    BASE_DIR.0.clone()
+}
+
+/// Set when the platform data directory could not be determined and a fallback is in use.
+pub fn base_dir_warning() -> Option<String> {
+    BASE_DIR.1.clone()
+}
+
+/// Platform data dir first, then a per-user folder from the environment
+/// (`%LOCALAPPDATA%`, `%APPDATA%`, `$HOME/.mcosu-importer`), then a folder next to the
+/// executable; the working directory is the last resort.
+fn resolve_base_dir(
+    project: Option<PathBuf>,
+    env: impl Fn(&str) -> Option<PathBuf>,
+    exe_dir: Option<PathBuf>,
+) -> (PathBuf, Option<String>) {
+    if let Some(dir) = project {
+        return (dir, None);
+    }
+    let per_user = env("LOCALAPPDATA")
+        .or_else(|| env("APPDATA"))
+        .map(|dir| dir.join(DATA_DIR_NAME))
+        .or_else(|| env("HOME").map(|home| home.join(format!(".{DATA_DIR_NAME}"))));
+    let (dir, origin) = if let Some(dir) = per_user.filter(|d| d.is_absolute()) {
+        (dir, "pasta do usuario")
+    } else if let Some(dir) = exe_dir {
+        (dir.join(format!("{DATA_DIR_NAME}-data")), "pasta do executavel")
+    } else {
+        (PathBuf::from("."), "pasta atual")
+    };
+    let warning = format!(
+        "Diretorio de dados do sistema indisponivel; usando {origin}: {}",
+        dir.display()
+    );
+    (dir, Some(warning))
 }
 
 pub fn cache_dir() -> PathBuf {
@@ -157,3 +518,372 @@ fn migrate_legacy_files() -> Result<()> {
     }
     Ok(())
 }
+
+const PREVIEW_STAMP: &str = ".last_used";
+
+/// Marks a preview folder as recently used for LRU eviction.
+pub fn touch_preview(folder: &Path) {
+    let _ = fs::write(folder.join(PREVIEW_STAMP), b"");
+}
+
+/// Removes the cached preview for one `.osz` hash; returns whether anything was deleted.
+pub fn remove_preview(hash: &str) -> Result<bool> {
+    let folder = preview_dir().join(hash);
+    if !folder.is_dir() {
+        return Ok(false);
+    }
+    fs::remove_dir_all(&folder).with_context(|| format!("removendo {}", folder.display()))?;
+    Ok(true)
+}
+
+/// Evicts least-recently-used preview folders until `root` fits in `max_bytes`.
+/// The folder named `keep` (the preview being opened) is never evicted.
+pub fn enforce_preview_budget(root: &Path, max_bytes: u64, keep: &str) -> Result<Vec<PathBuf>> {
+    if max_bytes == 0 || !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut folders = Vec::new();
+    let mut total = 0u64;
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let size = dir_size(&path);
+        total += size;
+        let last_used = fs::metadata(path.join(PREVIEW_STAMP))
+            .or_else(|_| fs::metadata(&path))
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        folders.push((last_used, size, path));
+    }
+    folders.sort_by_key(|(last_used, _, _)| *last_used);
+    let mut evicted = Vec::new();
+    for (_, size, path) in folders {
+        if total <= max_bytes {
+            break;
+        }
+        if path.file_name().map(|n| n == keep).unwrap_or(false) {
+            continue;
+        }
+        if fs::remove_dir_all(&path).is_ok() {
+            total = total.saturating_sub(size);
+            evicted.push(path);
+        }
+    }
+    Ok(evicted)
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Every file below `dir` with its size and mtime.
+fn collect_files(dir: &Path, out: &mut Vec<(PathBuf, u64, SystemTime)>) {
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(read) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in read.flatten() {
+            match entry.metadata() {
+                Ok(meta) if meta.is_dir() => pending.push(entry.path()),
+                Ok(meta) => out.push((
+                    entry.path(),
+                    meta.len(),
+                    meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                )),
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+pub fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(read) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in read.flatten() {
+            match entry.metadata() {
+                Ok(meta) if meta.is_dir() => pending.push(entry.path()),
+                Ok(meta) => total += meta.len(),
+                Err(_) => {}
+            }
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn make_preview(root: &Path, name: &str, bytes: usize, age_secs: u64) {
+        let folder = root.join(name);
+        fs::create_dir_all(folder.join("extracted")).unwrap();
+        fs::write(folder.join("beatmap.osz"), vec![0u8; bytes]).unwrap();
+        touch_preview(&folder);
+        let when = SystemTime::now() - Duration::from_secs(age_secs);
+        fs::File::options()
+            .write(true)
+            .open(folder.join(PREVIEW_STAMP))
+            .unwrap()
+            .set_modified(when)
+            .unwrap();
+    }
+
+    #[test]
+    fn preview_budget_evicts_oldest_first_and_keeps_current() {
+        let root = tempdir().unwrap();
+        make_preview(root.path(), "old", 400, 300);
+        make_preview(root.path(), "mid", 400, 200);
+        make_preview(root.path(), "new", 400, 100);
+        make_preview(root.path(), "current", 400, 1_000);
+
+        let evicted = enforce_preview_budget(root.path(), 900, "current").unwrap();
+        assert_eq!(evicted, vec![root.path().join("old"), root.path().join("mid")]);
+        assert!(root.path().join("current").exists());
+        assert!(root.path().join("new").exists());
+
+        assert!(enforce_preview_budget(root.path(), 0, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn profile_without_paths_keeps_current_folders() {
+        let current = AppConfig {
+            downloads_dir: PathBuf::from("/home/me/Downloads"),
+            songs_dir: PathBuf::from("/games/McOsu/Songs"),
+            ..AppConfig::default()
+        };
+        let shared = AppConfig {
+            downloads_dir: PathBuf::from("C:/Users/friend/Downloads"),
+            songs_dir: PathBuf::from("D:/McOsu/Songs"),
+            auto_delete_source: true,
+            max_download_kbps: Some(800),
+            ..AppConfig::default()
+        };
+        let profile = export_config_profile(&shared, false).unwrap();
+        assert!(!profile.contains("songs_dir"));
+
+        let merged = merge_config_profile(&current, &profile).unwrap();
+        assert_eq!(merged.downloads_dir, current.downloads_dir);
+        assert_eq!(merged.songs_dir, current.songs_dir);
+        assert!(merged.auto_delete_source);
+        assert_eq!(merged.max_download_kbps, Some(800));
+
+        let with_paths = export_config_profile(&shared, true).unwrap();
+        let merged = merge_config_profile(&current, &with_paths).unwrap();
+        assert_eq!(merged.songs_dir, shared.songs_dir);
+
+        assert!(merge_config_profile(&current, "[1, 2]").is_err());
+    }
+
+    #[test]
+    fn processed_sources_detect_changes_and_prune_missing() {
+        let dir = tempdir().unwrap();
+        let osz = dir.path().join("map.osz");
+        fs::write(&osz, b"first").unwrap();
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        assert!(!store.is_processed(&osz));
+        store.mark_processed(&osz);
+        assert!(store.is_processed(&osz));
+
+        fs::write(&osz, b"changed size").unwrap();
+        assert!(!store.is_processed(&osz));
+
+        store.mark_processed(&osz);
+        fs::remove_file(&osz).unwrap();
+        store.prune_processed();
+        assert!(store.inner.lock().unwrap().processed_sources.is_empty());
+    }
+
+    #[test]
+    fn prune_missing_keeps_live_folders_and_cache_files() {
+        let dir = tempdir().unwrap();
+        let cache_root = dir.path().join("cache");
+        let songs = dir.path().join("Songs");
+        let kept = songs.join("1 Artist - Title");
+        fs::create_dir_all(&kept).unwrap();
+        let deleted = songs.join("2 Artist - Gone");
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        store.register_beatmap_set(1, kept.clone());
+        store.register_beatmap_set(2, deleted.clone());
+        store.register_hash("aa".into(), deleted.clone());
+        store.register_audio("bb".into(), deleted.join("audio.mp3"));
+        store.register_audio("cc".into(), cache_root.join("audio").join("cc.mp3"));
+        store.insert_thumbnail("dd".into(), cache_root.join("thumbnails").join("dd.png"));
+
+        assert_eq!(store.prune_missing_outside(&cache_root), 3);
+        assert_eq!(store.find_set(1), Some(kept));
+        assert_eq!(store.find_set(2), None);
+        assert_eq!(store.find_hash("aa"), None);
+        assert_eq!(store.find_audio("bb"), None);
+        assert!(store.find_audio("cc").is_some());
+        assert!(store.inner.lock().unwrap().thumbnails.contains_key("dd"));
+    }
+
+    #[test]
+    fn deleted_thumbnail_is_dropped_from_the_index() {
+        let dir = tempdir().unwrap();
+        let thumb = dir.path().join("abc.png");
+        fs::write(&thumb, b"png").unwrap();
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        store.insert_thumbnail("abc".into(), thumb.clone());
+        assert_eq!(store.get_thumbnail("abc"), Some(thumb.clone()));
+
+        fs::remove_file(&thumb).unwrap();
+        assert_eq!(store.get_thumbnail("abc"), None);
+        let guard = store.inner.lock().unwrap();
+        assert!(!guard.thumbnails.contains_key("abc"));
+        assert!(!guard.last_used.contains_key(&thumb));
+    }
+
+    #[test]
+    fn forgetting_a_destination_drops_its_index_entries() {
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        let undone = PathBuf::from("/songs/1 Artist - Title");
+        let other = PathBuf::from("/songs/2 Artist - Other");
+        store.register_beatmap_set(1, undone.clone());
+        store.register_beatmap_set(2, other.clone());
+        store.register_hash("aa".into(), undone.clone());
+        store.register_hash("bb".into(), other.clone());
+
+        assert_eq!(store.forget_destination(&undone), 2);
+        assert_eq!(store.find_set(1), None);
+        assert_eq!(store.find_hash("aa"), None);
+        assert_eq!(store.find_set(2), Some(other.clone()));
+        assert_eq!(store.find_hash("bb"), Some(other));
+    }
+
+    #[test]
+    fn clearing_media_keeps_the_duplicate_index() {
+        let dir = tempdir().unwrap();
+        let thumbs = dir.path().join("thumbnails");
+        let audio = dir.path().join("audio").join("abc");
+        fs::create_dir_all(&thumbs).unwrap();
+        fs::create_dir_all(&audio).unwrap();
+        fs::write(thumbs.join("t.png"), vec![0u8; 300]).unwrap();
+        fs::write(audio.join("song.mp3"), vec![0u8; 700]).unwrap();
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        store.register_beatmap_set(1, dir.path().join("Songs").join("1 Map"));
+        store.register_hash("h".into(), dir.path().join("Songs").join("1 Map"));
+        store.insert_thumbnail("t".into(), thumbs.join("t.png"));
+        store.register_audio("abc".into(), audio.join("song.mp3"));
+
+        let freed = store.clear_dirs(&[thumbs.clone(), dir.path().join("audio"), dir.path().join("missing")]);
+        assert_eq!(freed, 1000);
+        assert!(thumbs.exists());
+        assert_eq!(fs::read_dir(&thumbs).unwrap().count(), 0);
+        assert!(!audio.exists());
+        assert_eq!(store.get_thumbnail("t"), None);
+        assert_eq!(store.find_audio("abc"), None);
+        assert!(store.find_set(1).is_some());
+        assert!(store.find_hash("h").is_some());
+    }
+
+    #[test]
+    fn media_eviction_removes_least_recently_used_first() {
+        let dir = tempdir().unwrap();
+        let thumbs = dir.path().join("thumbnails");
+        let audio = dir.path().join("audio");
+        fs::create_dir_all(&thumbs).unwrap();
+        fs::create_dir_all(audio.join("old")).unwrap();
+        fs::create_dir_all(audio.join("new")).unwrap();
+        let old_audio = audio.join("old").join("song.mp3");
+        let new_audio = audio.join("new").join("song.mp3");
+        let thumb = thumbs.join("t.png");
+        fs::write(&old_audio, vec![0u8; 400]).unwrap();
+        fs::write(&new_audio, vec![0u8; 400]).unwrap();
+        fs::write(&thumb, vec![0u8; 100]).unwrap();
+
+        let store = CacheStore {
+            inner: Mutex::new(CacheData::default()),
+            media_limit: AtomicU64::new(0),
+            media_bytes: AtomicU64::new(UNKNOWN_MEDIA_BYTES),
+        };
+        {
+            let mut guard = store.inner.lock().unwrap();
+            guard.audio_files.insert("old".into(), old_audio.clone());
+            guard.audio_files.insert("new".into(), new_audio.clone());
+            guard.thumbnails.insert("t".into(), thumb.clone());
+            guard.last_used.insert(old_audio.clone(), 10);
+            guard.last_used.insert(thumb.clone(), 20);
+            guard.last_used.insert(new_audio.clone(), 30);
+        }
+        let roots = [thumbs.clone(), audio.clone()];
+
+        assert!(store.evict_lru(&roots, 0, None).is_empty());
+        assert!(store.evict_lru(&roots, 900, None).is_empty());
+
+        assert_eq!(store.evict_lru(&roots, 600, None), vec![old_audio.clone()]);
+        assert!(!audio.join("old").exists());
+        assert!(store.inner.lock().unwrap().audio_files.get("old").is_none());
+        assert_eq!(store.media_bytes.load(Ordering::SeqCst), 500);
+
+        // Later inserts add to the running total instead of rescanning the folders.
+        let extra = thumbs.join("extra.png");
+        fs::write(&extra, vec![0u8; 50]).unwrap();
+        store.track_media(&extra);
+        assert_eq!(store.media_bytes.load(Ordering::SeqCst), 550);
+        assert!(store.evict_lru(&roots, 600, None).is_empty());
+
+        // The file just cached survives even when it alone is over the cap.
+        assert_eq!(
+            store.evict_lru(&roots, 100, Some(&new_audio)),
+            vec![thumb.clone(), extra.clone()]
+        );
+        assert!(new_audio.exists());
+        assert!(audio.exists() && thumbs.exists());
+    }
+
+    #[test]
+    fn falls_back_to_per_user_then_executable_dir() {
+        let project = PathBuf::from("/data/mcosu-importer");
+        assert_eq!(
+            resolve_base_dir(Some(project.clone()), |_| None, None),
+            (project, None)
+        );
+
+        let home = std::env::temp_dir().join("home");
+        let (dir, warning) = resolve_base_dir(
+            None,
+            |name| (name == "HOME").then(|| home.clone()),
+            None,
+        );
+        assert_eq!(dir, home.join(".mcosu-importer"));
+        assert!(warning.unwrap().contains("pasta do usuario"));
+
+        let exe = std::env::temp_dir().join("app");
+        let (dir, warning) = resolve_base_dir(None, |_| Some(PathBuf::from("relative")), Some(exe.clone()));
+        assert_eq!(dir, exe.join("mcosu-importer-data"));
+        assert!(warning.unwrap().contains("pasta do executavel"));
+    }
+}
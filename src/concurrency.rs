@@ -1,30 +1,104 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Condvar, Mutex};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ImportGuards {
     bulk_running: AtomicBool,
-    entries_running: Mutex<HashSet<u64>>,
+    bulk_cancel: AtomicBool,
+    /// Holds auto-import for this session only; `auto_import` in the config stays as is.
+    auto_import_paused: AtomicBool,
+    /// Entries being imported, each with the flag `CancelImport` raises.
+    entries_running: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+    metadata_slots: Slots,
+    import_slots: Slots,
 }
 
+/// Who asked for an import: clicks on a card go ahead of auto-import and bulk runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportPriority {
+    Interactive,
+    Background,
+}
+
+impl Default for ImportGuards {
+    fn default() -> Self {
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2)
+            .clamp(2, 8);
+        Self {
+            bulk_running: AtomicBool::new(false),
+            bulk_cancel: AtomicBool::new(false),
+            auto_import_paused: AtomicBool::new(false),
+            entries_running: Mutex::new(HashMap::new()),
+            metadata_slots: Slots::new(workers),
+            import_slots: Slots::new(DEFAULT_IMPORT_LIMIT),
+        }
+    }
+}
+
+/// Imports allowed to extract at once unless `max_parallel_imports` says otherwise.
+pub const DEFAULT_IMPORT_LIMIT: usize = 3;
+
 impl ImportGuards {
+    /// Applies `max_parallel_imports`; imports already running finish, new ones wait below the limit.
+    pub fn set_import_limit(&self, limit: usize) {
+        self.import_slots.set_capacity(limit);
+    }
+
+    /// Blocks until one of the bounded metadata-reading slots is free.
+    pub fn metadata_slot(&self) -> SlotPermit<'_> {
+        self.metadata_slots.acquire()
+    }
+
+    /// Blocks until an import slot is free; interactive requests are served before
+    /// background ones that are already waiting.
+    pub fn import_slot(&self, priority: ImportPriority) -> SlotPermit<'_> {
+        self.import_slots
+            .acquire_with(priority == ImportPriority::Interactive)
+    }
+
     pub fn try_start_bulk(&self) -> bool {
-        self.bulk_running
+        let started = self
+            .bulk_running
             .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-            .is_ok()
+            .is_ok();
+        if started {
+            self.bulk_cancel.store(false, Ordering::SeqCst);
+        }
+        started
+    }
+
+    /// Asks the running bulk import to stop before its next entry.
+    pub fn cancel_bulk(&self) {
+        if self.bulk_running.load(Ordering::SeqCst) {
+            self.bulk_cancel.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn bulk_cancelled(&self) -> bool {
+        self.bulk_cancel.load(Ordering::SeqCst)
     }
 
     pub fn finish_bulk(&self) {
         self.bulk_running.store(false, Ordering::SeqCst);
     }
 
+    pub fn set_auto_import_paused(&self, paused: bool) {
+        self.auto_import_paused.store(paused, Ordering::SeqCst);
+    }
+
+    pub fn auto_import_paused(&self) -> bool {
+        self.auto_import_paused.load(Ordering::SeqCst)
+    }
+
     pub fn try_lock_entry(&self, id: u64) -> bool {
         if let Ok(mut guard) = self.entries_running.lock() {
-            if guard.contains(&id) {
+            if guard.contains_key(&id) {
                 false
             } else {
-                guard.insert(id);
+                guard.insert(id, Arc::new(AtomicBool::new(false)));
                 true
             }
         } else {
@@ -32,6 +106,22 @@ impl ImportGuards {
         }
     }
 
+    /// Cancel flag of a running import; `None` when the entry isn't being imported.
+    pub fn import_cancel_flag(&self, id: u64) -> Option<Arc<AtomicBool>> {
+        self.entries_running.lock().ok()?.get(&id).cloned()
+    }
+
+    /// Asks a running import to stop; returns false when nothing was running for `id`.
+    pub fn cancel_import(&self, id: u64) -> bool {
+        match self.import_cancel_flag(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn release_entry(&self, id: u64) {
         if let Ok(mut guard) = self.entries_running.lock() {
             guard.remove(&id);
@@ -39,6 +129,78 @@ impl ImportGuards {
     }
 }
 
+/// Counting semaphore used to cap how many threads do heavy work at once.
+#[derive(Debug)]
+pub struct Slots {
+    state: Mutex<SlotState>,
+    freed: Condvar,
+}
+
+#[derive(Debug)]
+struct SlotState {
+    capacity: usize,
+    in_use: usize,
+    /// Urgent callers currently blocked; regular callers yield to them.
+    urgent_waiting: usize,
+}
+
+impl Slots {
+    pub fn new(count: usize) -> Self {
+        Self {
+            state: Mutex::new(SlotState {
+                capacity: count.max(1),
+                in_use: 0,
+                urgent_waiting: 0,
+            }),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Changes how many permits may be held; lowering it never revokes permits already handed out.
+    pub fn set_capacity(&self, count: usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.capacity = count.max(1);
+        self.freed.notify_all();
+    }
+
+    pub fn acquire(&self) -> SlotPermit<'_> {
+        self.acquire_with(false)
+    }
+
+    pub fn acquire_with(&self, urgent: bool) -> SlotPermit<'_> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if urgent {
+            state.urgent_waiting += 1;
+        }
+        while state.in_use >= state.capacity || (!urgent && state.urgent_waiting > 0) {
+            state = self.freed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if urgent {
+            state.urgent_waiting -= 1;
+        }
+        state.in_use += 1;
+        SlotPermit { slots: self }
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.in_use -= 1;
+        // Wake everyone so a waiting urgent caller is not passed over for a regular one.
+        self.freed.notify_all();
+    }
+}
+
+/// Returns its slot when dropped.
+pub struct SlotPermit<'a> {
+    slots: &'a Slots,
+}
+
+impl Drop for SlotPermit<'_> {
+    fn drop(&mut self) {
+        self.slots.release();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,6 +214,29 @@ mod tests {
         assert!(guard.try_start_bulk());
     }
 
+    #[test]
+    fn bulk_cancel_resets_on_next_start() {
+        let guard = ImportGuards::default();
+        guard.cancel_bulk();
+        assert!(!guard.bulk_cancelled());
+        assert!(guard.try_start_bulk());
+        guard.cancel_bulk();
+        assert!(guard.bulk_cancelled());
+        guard.finish_bulk();
+        assert!(guard.try_start_bulk());
+        assert!(!guard.bulk_cancelled());
+    }
+
+    #[test]
+    fn auto_import_pause_is_off_by_default() {
+        let guard = ImportGuards::default();
+        assert!(!guard.auto_import_paused());
+        guard.set_auto_import_paused(true);
+        assert!(guard.auto_import_paused());
+        guard.set_auto_import_paused(false);
+        assert!(!guard.auto_import_paused());
+    }
+
     #[test]
     fn per_entry_lock_prevents_parallel_import() {
         let guard = ImportGuards::default();
@@ -61,4 +246,101 @@ mod tests {
         guard.release_entry(1);
         assert!(guard.try_lock_entry(1));
     }
+
+    #[test]
+    fn cancel_flag_lives_with_the_entry_lock() {
+        let guard = ImportGuards::default();
+        assert!(!guard.cancel_import(4));
+        assert!(guard.try_lock_entry(4));
+        let flag = guard.import_cancel_flag(4).unwrap();
+        assert!(guard.cancel_import(4));
+        assert!(flag.load(Ordering::SeqCst));
+        guard.release_entry(4);
+        assert!(guard.import_cancel_flag(4).is_none());
+        assert!(guard.try_lock_entry(4));
+        assert!(!guard.import_cancel_flag(4).unwrap().load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn slots_cap_concurrent_permits() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicUsize;
+
+        let slots = Arc::new(Slots::new(2));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let (slots, active, peak) = (slots.clone(), active.clone(), peak.clone());
+                std::thread::spawn(move || {
+                    let _permit = slots.acquire();
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn import_limit_caps_concurrent_imports() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicUsize;
+
+        let guards = Arc::new(ImportGuards::default());
+        guards.set_import_limit(3);
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handles = (0..10)
+            .map(|i| {
+                let (guards, active, peak) = (guards.clone(), active.clone(), peak.clone());
+                std::thread::spawn(move || {
+                    let priority = if i % 3 == 0 {
+                        ImportPriority::Interactive
+                    } else {
+                        ImportPriority::Background
+                    };
+                    let _slot = guards.import_slot(priority);
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(15));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn urgent_waiter_goes_before_earlier_regular_one() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let slots = Arc::new(Slots::new(1));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let held = slots.acquire();
+        let spawn = |name: &'static str, urgent: bool| {
+            let (slots, order) = (slots.clone(), order.clone());
+            std::thread::spawn(move || {
+                let _permit = slots.acquire_with(urgent);
+                order.lock().unwrap().push(name);
+            })
+        };
+        let background = spawn("background", false);
+        std::thread::sleep(Duration::from_millis(30));
+        let interactive = spawn("interactive", true);
+        std::thread::sleep(Duration::from_millis(30));
+        drop(held);
+        background.join().unwrap();
+        interactive.join().unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["interactive", "background"]);
+    }
 }
@@ -1,4 +1,4 @@
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions};
 use std::io::Read;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -8,10 +8,11 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, Context, Result};
 use regex::Regex;
-use reqwest::blocking::Client;
-use reqwest::header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info, warn};
+use tracing::{info, warn};
 
 use crate::app_state::sanitize_path_component;
 
@@ -19,19 +20,33 @@ use crate::app_state::sanitize_path_component;
 pub enum DownloadProvider {
     Gatari,
     BeatConnect,
+    OsuDirect,
+    Chimu,
 }
 
 impl DownloadProvider {
+    /// Providers that can serve a set from its BeatmapSetID alone (BeatConnect needs the
+    /// full link), in the order search downloads fall back to them.
+    pub const SET_ID_MIRRORS: [DownloadProvider; 3] = [
+        DownloadProvider::Gatari,
+        DownloadProvider::OsuDirect,
+        DownloadProvider::Chimu,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             DownloadProvider::Gatari => "Gatari",
             DownloadProvider::BeatConnect => "BeatConnect",
+            DownloadProvider::OsuDirect => "osu.direct",
+            DownloadProvider::Chimu => "Chimu",
         }
     }
 
     pub fn from_index(idx: i32) -> Self {
         match idx {
             1 => DownloadProvider::BeatConnect,
+            2 => DownloadProvider::OsuDirect,
+            3 => DownloadProvider::Chimu,
             _ => DownloadProvider::Gatari,
         }
     }
@@ -40,6 +55,8 @@ impl DownloadProvider {
         match self {
             DownloadProvider::Gatari => 0,
             DownloadProvider::BeatConnect => 1,
+            DownloadProvider::OsuDirect => 2,
+            DownloadProvider::Chimu => 3,
         }
     }
 }
@@ -78,6 +95,9 @@ pub struct DownloadJob {
     pub total_bytes_opt: Option<u64>,
     pub error_opt: Option<String>,
     pub out_path_opt: Option<PathBuf>,
+    /// `.part` file of an interrupted download, resumed on the next attempt.
+    #[serde(default)]
+    pub part_path_opt: Option<PathBuf>,
     #[serde(skip)]
     pub cancel_flag: Arc<AtomicBool>,
 }
@@ -94,6 +114,7 @@ impl DownloadJob {
             total_bytes_opt: None,
             error_opt: None,
             out_path_opt: None,
+            part_path_opt: None,
             cancel_flag: Arc::new(AtomicBool::new(false)),
         }
     }
@@ -118,41 +139,46 @@ pub fn resolve_download(provider: DownloadProvider, input: &str) -> Result<Resol
     match provider {
         DownloadProvider::Gatari => parse_gatari_input(input),
         DownloadProvider::BeatConnect => parse_beatconnect_input(input),
+        DownloadProvider::OsuDirect => parse_osu_direct_input(input),
+        DownloadProvider::Chimu => parse_chimu_input(input),
     }
 }
 
 pub fn parse_gatari_input(input: &str) -> Result<ResolvedDownload> {
+    parse_set_id_input(input, |id| format!("https://osu.gatari.pw/d/{id}"))
+}
+
+pub fn parse_osu_direct_input(input: &str) -> Result<ResolvedDownload> {
+    parse_set_id_input(input, |id| format!("https://osu.direct/api/d/{id}"))
+}
+
+pub fn parse_chimu_input(input: &str) -> Result<ResolvedDownload> {
+    parse_set_id_input(input, |id| format!("https://api.chimu.moe/v1/download/{id}"))
+}
+
+/// Finds the BeatmapSetID in a bare number, a download link (`/d/<id>`, `/download/<id>`)
+/// or a beatmapset page, and builds the mirror's download URL for it.
+fn parse_set_id_input(input: &str, download_url: impl Fn(&str) -> String) -> Result<ResolvedDownload> {
+    let resolved = |id: &str| ResolvedDownload {
+        url: download_url(id),
+        set_id: Some(id.to_string()),
+    };
     let trimmed = input.trim();
-    if trimmed.chars().all(|c| c.is_ascii_digit()) {
-        return Ok(ResolvedDownload {
-            url: format!("https://osu.gatari.pw/d/{trimmed}"),
-            set_id: Some(trimmed.to_string()),
-        });
+    if !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(resolved(trimmed));
     }
     let url_like = prepend_scheme_if_missing(trimmed);
-    let re_direct = Regex::new(r"/d/(\d+)")?;
+    let re_direct = Regex::new(r"/(?:d|download)/(\d+)")?;
     if let Some(cap) = re_direct.captures(&url_like) {
-        let id = cap.get(1).unwrap().as_str();
-        return Ok(ResolvedDownload {
-            url: format!("https://osu.gatari.pw/d/{id}"),
-            set_id: Some(id.to_string()),
-        });
+        return Ok(resolved(cap.get(1).unwrap().as_str()));
     }
     let re_generic = Regex::new(r"/(?:s|beatmapsets)/(\d+)")?;
     if let Some(cap) = re_generic.captures(&url_like) {
-        let id = cap.get(1).unwrap().as_str();
-        return Ok(ResolvedDownload {
-            url: format!("https://osu.gatari.pw/d/{id}"),
-            set_id: Some(id.to_string()),
-        });
+        return Ok(resolved(cap.get(1).unwrap().as_str()));
     }
     let re_digits = Regex::new(r"(\d{2,})")?;
     if let Some(cap) = re_digits.captures(&url_like) {
-        let id = cap.get(1).unwrap().as_str();
-        return Ok(ResolvedDownload {
-            url: format!("https://osu.gatari.pw/d/{id}"),
-            set_id: Some(id.to_string()),
-        });
+        return Ok(resolved(cap.get(1).unwrap().as_str()));
     }
     Err(anyhow!(
         "Nao foi possivel extrair o BeatmapSetID do link informado"
@@ -219,12 +245,11 @@ fn unix_ts() -> u64 {
 
 fn sanitize_osz_name(name: &str) -> String {
     let cleaned = sanitize_path_component(name);
-    let with_ext = if cleaned.to_lowercase().ends_with(".osz") {
+    if cleaned.to_lowercase().ends_with(".osz") {
         cleaned
     } else {
         format!("{cleaned}.osz")
-    };
-    with_ext
+    }
 }
 
 fn extract_filename(header: &str) -> Option<String> {
@@ -236,7 +261,7 @@ fn extract_filename(header: &str) -> Option<String> {
             header
                 .split(';')
                 .find_map(|part| part.trim().strip_prefix("filename*="))
-                .map(|v| v.split('\'').last().unwrap_or(v).to_string())
+                .map(|v| v.rsplit('\'').next().unwrap_or(v).to_string())
         })
 }
 
@@ -269,8 +294,10 @@ pub fn download_to_path(
     job: &mut DownloadJob,
     downloads_dir: &Path,
     client: &Client,
+    max_kbps: Option<u32>,
     notify: &mut dyn FnMut(&DownloadJob),
 ) -> Result<()> {
+    let mut throttle = crate::throttle::Throttle::new(max_kbps);
     job.status = DownloadStatus::Resolving;
     notify(job);
     let resolved = resolve_download(job.provider, &job.input)?;
@@ -282,40 +309,77 @@ pub fn download_to_path(
         job.final_url.as_deref().unwrap_or_default()
     );
 
-    let mut response = client
-        .get(&resolved.url)
-        .header(USER_AGENT, "mcosu-importer/1.0")
+    // A `.part` left by an earlier attempt is resumed with a Range request.
+    let previous_part = job
+        .part_path_opt
+        .clone()
+        .zip(job.out_path_opt.clone())
+        .and_then(|(part, target)| {
+            let len = fs::metadata(&part).ok()?.len();
+            (len > 0).then_some((part, target, len))
+        });
+
+    let existing = previous_part.as_ref().map_or(0, |(_, _, len)| *len);
+    let mut response = download_request(client, &resolved.url, existing)
         .send()
         .with_context(|| format!("Requisicao falhou para {}", resolved.url))?;
     let status = response.status();
     if !status.is_success() {
         bail!("HTTP {} ao baixar {}", status.as_u16(), resolved.url);
     }
+    let resume_from = match previous_part.as_ref() {
+        Some((_, _, len)) => resume_offset(
+            status,
+            response.headers().get(CONTENT_RANGE).and_then(|v| v.to_str().ok()),
+            *len,
+        )?,
+        None => 0,
+    };
     let total = response
         .headers()
         .get(CONTENT_LENGTH)
         .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok());
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(|len| len + resume_from);
     job.total_bytes_opt = total;
     let content_type = response
         .headers()
         .get(CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-    let disp = response
-        .headers()
-        .get(CONTENT_DISPOSITION)
-        .and_then(|v| v.to_str().ok());
-    let file_name = choose_file_name(job.provider, resolved.set_id.as_deref(), disp);
-    let target = unique_path(downloads_dir, &file_name);
-    let part_path = target.with_extension("osz.part");
+        .unwrap_or("")
+        .to_string();
+    let (target, part_path, mut file) = match previous_part {
+        Some((part, target, _)) if resume_from > 0 => {
+            info!("Retomando download {} a partir de {} bytes", job.id, resume_from);
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&part)
+                .with_context(|| format!("Abrindo arquivo {}", part.display()))?;
+            (target, part, file)
+        }
+        previous => {
+            if let Some((part, _, _)) = previous {
+                info!("Servidor nao aceitou retomar o download {}; reiniciando", job.id);
+                let _ = fs::remove_file(&part);
+            }
+            let disp = response
+                .headers()
+                .get(CONTENT_DISPOSITION)
+                .and_then(|v| v.to_str().ok());
+            let file_name = choose_file_name(job.provider, resolved.set_id.as_deref(), disp);
+            let target = unique_path(downloads_dir, &file_name);
+            let part_path = target.with_extension("osz.part");
+            let file = File::create(&part_path)
+                .with_context(|| format!("Criando arquivo {}", part_path.display()))?;
+            (target, part_path, file)
+        }
+    };
     job.status = DownloadStatus::Downloading;
     job.out_path_opt = Some(target.clone());
+    job.part_path_opt = Some(part_path.clone());
     notify(job);
 
-    let mut file =
-        File::create(&part_path).with_context(|| format!("Criando arquivo {}", part_path.display()))?;
-    let mut downloaded: u64 = 0;
+    let mut downloaded: u64 = resume_from;
     let mut buffer = [0u8; 16 * 1024];
     let mut first_chunk = Vec::new();
     loop {
@@ -323,18 +387,22 @@ pub fn download_to_path(
             warn!("Download {} cancelado pelo usuario", job.id);
             job.status = DownloadStatus::Cancelled;
             job.error_opt = None;
+            job.part_path_opt = None;
             notify(job);
             let _ = fs::remove_file(&part_path);
             return Ok(());
         }
+        // A read error keeps the `.part` (and `part_path_opt`) so the next attempt resumes.
         let read = response.read(&mut buffer)?;
         if read == 0 {
             break;
         }
-        if downloaded == 0 {
+        // Checked on every response, so an error page sent with a `206` isn't appended to the `.part`.
+        if first_chunk.is_empty() {
             first_chunk.extend_from_slice(&buffer[..read.min(512)]);
-            if looks_like_html(&first_chunk, content_type) {
+            if looks_like_html(&first_chunk, &content_type) {
                 let _ = fs::remove_file(&part_path);
+                job.part_path_opt = None;
                 bail!(beatconnect_html_error(job.provider));
             }
         }
@@ -342,9 +410,13 @@ pub fn download_to_path(
         downloaded += read as u64;
         job.progress_bytes = downloaded;
         notify(job);
+        if let Some(throttle) = throttle.as_mut() {
+            throttle.consume(read);
+        }
     }
     file.flush()?;
     fs::rename(&part_path, &target)?;
+    job.part_path_opt = None;
     job.status = DownloadStatus::Completed;
     job.progress_bytes = downloaded;
     job.out_path_opt = Some(target.clone());
@@ -358,7 +430,41 @@ pub fn download_to_path(
     Ok(())
 }
 
-fn looks_like_html(snippet: &[u8], content_type: &str) -> bool {
+/// GET for `url`, asking for the bytes after `existing` when a `.part` is being resumed.
+/// The User-Agent comes from the client (`http::client`), so it follows `AppConfig.user_agent`.
+pub(crate) fn download_request(client: &Client, url: &str, existing: u64) -> RequestBuilder {
+    let request = client.get(url);
+    if existing > 0 {
+        request.header(RANGE, format!("bytes={existing}-"))
+    } else {
+        request
+    }
+}
+
+/// Bytes already on disk that the response continues from: `existing` for a `206` whose
+/// `Content-Range` starts there, 0 when the server sent the whole file again.
+pub(crate) fn resume_offset(
+    status: StatusCode,
+    content_range: Option<&str>,
+    existing: u64,
+) -> Result<u64> {
+    if status != StatusCode::PARTIAL_CONTENT {
+        return Ok(0);
+    }
+    let start = content_range
+        .and_then(|v| v.trim().strip_prefix("bytes "))
+        .and_then(|v| v.split('-').next())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    match start {
+        Some(start) if start == existing => Ok(existing),
+        _ => bail!(
+            "Servidor retomou o download de um ponto inesperado ({})",
+            content_range.unwrap_or("sem Content-Range")
+        ),
+    }
+}
+
+pub(crate) fn looks_like_html(snippet: &[u8], content_type: &str) -> bool {
     if content_type.to_lowercase().contains("text/html") {
         return true;
     }
@@ -402,6 +508,42 @@ mod tests {
         assert!(dlink.url.contains("/d/555"));
     }
 
+    #[test]
+    fn parse_osu_direct_variants() {
+        let direct = parse_osu_direct_input("12345").unwrap();
+        assert_eq!(direct.url, "https://osu.direct/api/d/12345");
+        assert_eq!(direct.set_id.as_deref(), Some("12345"));
+        let page = parse_osu_direct_input("https://osu.direct/beatmapsets/987").unwrap();
+        assert_eq!(page.url, "https://osu.direct/api/d/987");
+        let dlink = parse_osu_direct_input("osu.direct/api/d/555").unwrap();
+        assert_eq!(dlink.set_id.as_deref(), Some("555"));
+        let official = parse_osu_direct_input("https://osu.ppy.sh/beatmapsets/321#osu/1").unwrap();
+        assert_eq!(official.set_id.as_deref(), Some("321"));
+    }
+
+    #[test]
+    fn parse_chimu_variants() {
+        let direct = parse_chimu_input("12345").unwrap();
+        assert_eq!(direct.url, "https://api.chimu.moe/v1/download/12345");
+        let page = parse_chimu_input("https://chimu.moe/en/s/987").unwrap();
+        assert_eq!(page.url, "https://api.chimu.moe/v1/download/987");
+        let dlink = parse_chimu_input("https://api.chimu.moe/v1/download/555?n=1").unwrap();
+        assert_eq!(dlink.set_id.as_deref(), Some("555"));
+        assert!(parse_chimu_input("sem id").is_err());
+    }
+
+    #[test]
+    fn provider_index_round_trips() {
+        for provider in [
+            DownloadProvider::Gatari,
+            DownloadProvider::BeatConnect,
+            DownloadProvider::OsuDirect,
+            DownloadProvider::Chimu,
+        ] {
+            assert_eq!(DownloadProvider::from_index(provider.to_index()), provider);
+        }
+    }
+
     #[test]
     fn parse_beatconnect_variants() {
         let num = parse_beatconnect_input("54321").unwrap();
@@ -424,6 +566,17 @@ mod tests {
         assert!(!name.contains('?'));
     }
 
+    #[test]
+    fn resume_offset_follows_the_server_answer() {
+        assert_eq!(resume_offset(StatusCode::OK, None, 500).unwrap(), 0);
+        assert_eq!(
+            resume_offset(StatusCode::PARTIAL_CONTENT, Some("bytes 500-999/1000"), 500).unwrap(),
+            500
+        );
+        assert!(resume_offset(StatusCode::PARTIAL_CONTENT, Some("bytes 0-999/1000"), 500).is_err());
+        assert!(resume_offset(StatusCode::PARTIAL_CONTENT, None, 500).is_err());
+    }
+
     #[test]
     fn unique_path_adds_suffix() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+
+/// Sent when `AppConfig.user_agent` is empty.
+pub const DEFAULT_USER_AGENT: &str = concat!("McOsuImporter/", env!("CARGO_PKG_VERSION"));
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Configured User-Agent, or the default when none is set.
+pub fn user_agent(configured: &str) -> &str {
+    let trimmed = configured.trim();
+    if trimmed.is_empty() {
+        DEFAULT_USER_AGENT
+    } else {
+        trimmed
+    }
+}
+
+/// Blocking client shared by searches and downloads. Only the connection gets a timeout;
+/// callers add a per-request one where a whole response should be bounded (searches),
+/// since a download can legitimately take minutes.
+pub fn client(configured_user_agent: &str) -> reqwest::Result<Client> {
+    Client::builder()
+        .user_agent(user_agent(configured_user_agent))
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_user_agent_falls_back_to_default() {
+        assert_eq!(user_agent(""), DEFAULT_USER_AGENT);
+        assert_eq!(user_agent("   "), DEFAULT_USER_AGENT);
+        assert_eq!(user_agent(" Custom/2.0 "), "Custom/2.0");
+        assert!(DEFAULT_USER_AGENT.starts_with("McOsuImporter/"));
+    }
+}
@@ -1,66 +1,463 @@
+use std::collections::HashSet;
 use std::fs;
 use std::io;
 use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use anyhow::{Context, Result, anyhow};
+use thiserror::Error;
 use zip::ZipArchive;
+use zip::result::ZipError;
 
-use crate::app_state::{BeatmapEntry, BeatmapMetadata, sanitize_path_component};
+use crate::app_state::{BeatmapEntry, BeatmapMetadata, FailureKind, sanitize_path_component};
+use crate::osz_reader::is_video_file;
 
 #[derive(Debug)]
 pub struct ImportResult {
     pub destination: PathBuf,
     pub duplicated: bool,
+    /// Uncompressed size of the background videos left out by `skip_video`.
+    pub skipped_video_bytes: u64,
 }
 
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("lendo arquivo .osz")]
+    ArchiveRead(#[from] ZipError),
+    #[error("criando pasta {}", path.display())]
+    CreateDir {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("gravando arquivo {}", path.display())]
+    WriteFile {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Nao foi possivel ler os metadados do arquivo .osz")]
+    Metadata,
+    #[error("entrada {entry:?} sairia da pasta destino (Zip Slip)")]
+    ZipSlip { entry: String },
+    #[error("importacao cancelada pelo usuario")]
+    Cancelled,
+    #[error("Espaco insuficiente: precisa {} MB, tem {} MB", needed.div_ceil(MIB), available / MIB)]
+    InsufficientSpace { needed: u64, available: u64 },
+}
+
+const MIB: u64 = 1_048_576;
+
+impl ImportError {
+    /// The OS refused access to the destination (e.g. Songs under Program Files).
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            ImportError::CreateDir { source, .. } | ImportError::WriteFile { source, .. } => {
+                source.kind() == io::ErrorKind::PermissionDenied
+            }
+            _ => false,
+        }
+    }
+
+    /// Short text shown on the queue card.
+    pub fn short_message(&self) -> &'static str {
+        if self.is_permission_denied() {
+            return "Sem permissao para gravar na pasta Songs";
+        }
+        match self {
+            ImportError::ArchiveRead(_) => "Falha ao extrair o .osz",
+            ImportError::CreateDir { .. } | ImportError::WriteFile { .. } => {
+                "Falha ao criar/gravar na pasta destino"
+            }
+            ImportError::Metadata => "Falha ao ler metadados",
+            ImportError::ZipSlip { .. } => "Arquivo .osz com caminhos invalidos",
+            ImportError::Cancelled => "Importacao cancelada",
+            ImportError::InsufficientSpace { .. } => "Espaco insuficiente na pasta Songs",
+        }
+    }
+
+    pub fn failure_kind(&self) -> FailureKind {
+        if self.is_permission_denied() {
+            return FailureKind::Permission;
+        }
+        match self {
+            ImportError::ArchiveRead(_) => FailureKind::Extraction,
+            ImportError::CreateDir { .. } | ImportError::WriteFile { .. } => FailureKind::Write,
+            ImportError::Metadata => FailureKind::Metadata,
+            ImportError::ZipSlip { .. } => FailureKind::InvalidArchive,
+            ImportError::Cancelled => FailureKind::Other,
+            ImportError::InsufficientSpace { .. } => FailureKind::Write,
+        }
+    }
+
+    /// Whether trying again later can plausibly succeed (e.g. a file was locked).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ImportError::ArchiveRead(ZipError::Io(_)) => true,
+            ImportError::ArchiveRead(_) => false,
+            ImportError::CreateDir { .. } | ImportError::WriteFile { .. } => {
+                !self.is_permission_denied()
+            }
+            // Worth another try once space is freed.
+            ImportError::InsufficientSpace { .. } => true,
+            ImportError::Metadata | ImportError::ZipSlip { .. } | ImportError::Cancelled => false,
+        }
+    }
+}
+
+/// `on_progress` receives (files written, files to write) after each archive entry; raising
+/// `cancel` stops before the next entry with `ImportError::Cancelled`. With `skip_video`,
+/// background videos are not extracted; the map still plays (McOsu and osu! ignore a `Video`
+/// event whose file is missing).
 pub fn import_osz(
     entry: &BeatmapEntry,
     meta: &BeatmapMetadata,
     songs_dir: &Path,
     force: bool,
-) -> Result<ImportResult> {
-    let target_name = build_folder_name(meta, &entry.osz_path);
-    let dest = songs_dir.join(target_name);
+    skip_video: bool,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<ImportResult, ImportError> {
+    let dest = resolve_destination(entry, meta, songs_dir)?;
 
     if dest.exists() && !force {
         return Ok(ImportResult {
             destination: dest,
             duplicated: true,
+            skipped_video_bytes: 0,
         });
     }
-    if dest.exists() && force {
-        fs::remove_dir_all(&dest).ok();
+
+    // A full disk would otherwise surface as a generic write error halfway through.
+    let needed = extracted_size(entry, meta, skip_video)?;
+    ensure_fits(needed, crate::disk::free_space(songs_dir))?;
+
+    // Extract next to the destination and move it into place once complete, so McOsu never
+    // sees a half-written map. A forced reimport keeps the old folder as a backup until then.
+    let staging = sibling_path(&dest, "importing", entry.id);
+    let skipped_video_bytes =
+        match extract_archive(entry, meta, &staging, skip_video, cancel, &mut on_progress) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                fs::remove_dir_all(&staging).ok();
+                return Err(err);
+            }
+        };
+    let backup = force.then(|| sibling_path(&dest, "backup", entry.id));
+    replace_with_staging(&staging, &dest, backup.as_deref(), move_staging)?;
+
+    Ok(ImportResult {
+        destination: dest,
+        duplicated: false,
+        skipped_video_bytes,
+    })
+}
+
+static SIBLING_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Sibling of `dest` used while importing (`kind` "importing" for the extraction, "backup"
+/// for the folder a forced reimport replaces). Process id, entry id and a counter keep
+/// parallel imports to the same destination, and other running instances, apart.
+fn sibling_path(dest: &Path, kind: &str, entry_id: u64) -> PathBuf {
+    let seq = SIBLING_SEQ.fetch_add(1, Ordering::Relaxed);
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{kind}-{}-{entry_id}-{seq}", std::process::id()));
+    dest.with_file_name(name)
+}
+
+/// Puts `staging` at `dest` with `place`, which must clean up whatever it created when it
+/// fails. With `backup` (forced reimport) an existing `dest` is moved there first and only
+/// deleted once `place` succeeded; on any error it is restored.
+fn replace_with_staging(
+    staging: &Path,
+    dest: &Path,
+    backup: Option<&Path>,
+    place: impl FnOnce(&Path, &Path) -> Result<(), ImportError>,
+) -> Result<(), ImportError> {
+    let backup = backup.filter(|_| dest.exists());
+    if let Some(backup) = backup {
+        if let Err(source) = fs::rename(dest, backup) {
+            fs::remove_dir_all(staging).ok();
+            return Err(ImportError::WriteFile {
+                path: dest.to_path_buf(),
+                source,
+            });
+        }
+    }
+    match place(staging, dest) {
+        Ok(()) => {
+            if let Some(backup) = backup {
+                fs::remove_dir_all(backup).ok();
+            }
+            Ok(())
+        }
+        Err(err) => {
+            fs::remove_dir_all(staging).ok();
+            // A `dest` that exists now belongs to someone else; the backup then stays put.
+            if let Some(backup) = backup.filter(|_| !dest.exists()) {
+                fs::rename(backup, dest).ok();
+            }
+            Err(err)
+        }
     }
+}
 
-    fs::create_dir_all(&dest).context("criando pasta de destino")?;
-    let file = fs::File::open(&entry.osz_path).context("abrindo arquivo .osz")?;
-    let mut archive = ZipArchive::new(file).context("lendo arquivo zip")?;
+fn move_staging(staging: &Path, dest: &Path) -> Result<(), ImportError> {
+    if fs::rename(staging, dest).is_ok() {
+        return Ok(());
+    }
+    // Another import got there first; copying would merge into its folder.
+    if dest.exists() {
+        fs::remove_dir_all(staging).ok();
+        return Err(ImportError::WriteFile {
+            path: dest.to_path_buf(),
+            source: io::ErrorKind::AlreadyExists.into(),
+        });
+    }
+    // Rename can be refused (e.g. Songs behind a junction on another volume); copy instead.
+    let copied = copy_dir_recursive(staging, dest);
+    fs::remove_dir_all(staging).ok();
+    if copied.is_err() {
+        fs::remove_dir_all(dest).ok();
+    }
+    copied
+}
+
+fn copy_dir_recursive(from: &Path, to: &Path) -> Result<(), ImportError> {
+    create_dir(to)?;
+    let read_err = |source| ImportError::CreateDir {
+        path: from.to_path_buf(),
+        source,
+    };
+    for item in fs::read_dir(from).map_err(read_err)? {
+        let item = item.map_err(read_err)?;
+        let target = to.join(item.file_name());
+        if item.path().is_dir() {
+            copy_dir_recursive(&item.path(), &target)?;
+        } else {
+            fs::copy(item.path(), &target).map_err(|source| ImportError::WriteFile {
+                path: target.clone(),
+                source,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Uncompressed size of the entries `extract_archive` will write.
+fn extracted_size(entry: &BeatmapEntry, meta: &BeatmapMetadata, skip_video: bool) -> Result<u64, ImportError> {
+    let file = fs::File::open(&entry.osz_path).map_err(ZipError::from)?;
+    let mut archive = ZipArchive::new(file)?;
+    let skipped = unselected_difficulty_files(entry, meta);
+    let mut total = 0u64;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        let excluded = skipped.iter().any(|name| name == file.name())
+            || (skip_video && is_video_file(file.name()));
+        if !excluded {
+            total += file.size();
+        }
+    }
+    Ok(total)
+}
+
+/// Fails when free space is known and smaller than `needed`.
+fn ensure_fits(needed: u64, available: Option<u64>) -> Result<(), ImportError> {
+    match available {
+        Some(available) if available < needed => {
+            Err(ImportError::InsufficientSpace { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the uncompressed bytes of the videos left out.
+fn extract_archive(
+    entry: &BeatmapEntry,
+    meta: &BeatmapMetadata,
+    target: &Path,
+    skip_video: bool,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(u64, u64),
+) -> Result<u64, ImportError> {
+    create_dir(target)?;
+    let file = fs::File::open(&entry.osz_path).map_err(ZipError::from)?;
+    let mut archive = ZipArchive::new(file)?;
+    let skipped = unselected_difficulty_files(entry, meta);
+    let videos = if skip_video {
+        archive.file_names().filter(|name| is_video_file(name)).count()
+    } else {
+        0
+    };
+    let total = archive.len().saturating_sub(skipped.len() + videos) as u64;
+    let mut done = 0u64;
+    let mut video_bytes = 0u64;
     for i in 0..archive.len() {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(ImportError::Cancelled);
+        }
         let mut file = archive.by_index(i)?;
-        let outpath = build_safe_path(&dest, file.name())?;
+        if skipped.iter().any(|name| name == file.name()) {
+            continue;
+        }
+        if skip_video && is_video_file(file.name()) {
+            video_bytes += file.size();
+            continue;
+        }
+        done += 1;
+        let outpath = build_safe_path(target, file.name())?;
 
         if file.is_dir() {
-            fs::create_dir_all(&outpath)
-                .with_context(|| format!("criando pasta {}", outpath.display()))?;
+            create_dir(&outpath)?;
         } else {
             if let Some(parent) = outpath.parent() {
-                fs::create_dir_all(parent)
-                    .with_context(|| format!("criando pasta {}", parent.display()))?;
+                create_dir(parent)?;
             }
-            let mut outfile = fs::File::create(&outpath)
-                .with_context(|| format!("criando arquivo {}", outpath.display()))?;
-            io::copy(&mut file, &mut outfile).context("gravando arquivo extraido")?;
+            let write_err = |source| ImportError::WriteFile {
+                path: outpath.clone(),
+                source,
+            };
+            let mut outfile = fs::File::create(&outpath).map_err(write_err)?;
+            io::copy(&mut file, &mut outfile).map_err(write_err)?;
         }
+        on_progress(done, total.max(done));
     }
+    Ok(video_bytes)
+}
 
-    Ok(ImportResult {
-        destination: dest,
-        duplicated: false,
-    })
+pub fn destination_path(meta: &BeatmapMetadata, osz_path: &Path, songs_dir: &Path) -> PathBuf {
+    songs_dir.join(build_folder_name(meta, osz_path))
+}
+
+/// Destination folder for this archive. Maps without a BeatmapSetID can share a folder name
+/// with a different map; when the existing folder holds another map, a short hash of the
+/// `.osz` is appended instead of treating the new one as a duplicate.
+pub fn resolve_destination(
+    entry: &BeatmapEntry,
+    meta: &BeatmapMetadata,
+    songs_dir: &Path,
+) -> Result<PathBuf, ImportError> {
+    let dest = destination_path(meta, &entry.osz_path, songs_dir);
+    if meta.beatmap_set_id.is_some() || !dest.exists() || holds_same_map(&entry.osz_path, &dest)? {
+        return Ok(dest);
+    }
+    let name = format!(
+        "{} [{}]",
+        build_folder_name(meta, &entry.osz_path),
+        short_osz_hash(entry)?
+    );
+    Ok(songs_dir.join(sanitize_path_component(&name)))
+}
+
+/// A folder in `songs_dir` named like this map's destination (ignoring case), e.g. the same map
+/// downloaded from another mirror with different archive bytes. Without a BeatmapSetID the name
+/// is weaker, so one of the map's `.osu` files must also be there.
+pub fn find_folder_by_name(meta: &BeatmapMetadata, osz_path: &Path, songs_dir: &Path) -> Option<PathBuf> {
+    let expected = build_folder_name(meta, osz_path).to_lowercase();
+    let found = fs::read_dir(songs_dir)
+        .ok()?
+        .flatten()
+        .map(|item| item.path())
+        .find(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().to_lowercase() == expected)
+        })?;
+    let confirmed = meta.beatmap_set_id.is_some()
+        || meta
+            .difficulty_info
+            .iter()
+            .any(|d| clean_entry_path(&d.file_name).is_ok_and(|rel| found.join(rel).is_file()));
+    confirmed.then_some(found)
+}
+
+/// Whether `dest` already holds this map: at least one of the archive's `.osu` files is
+/// present there with identical content (partial imports may lack some difficulties).
+fn holds_same_map(osz_path: &Path, dest: &Path) -> Result<bool, ImportError> {
+    let file = fs::File::open(osz_path).map_err(ZipError::from)?;
+    let mut archive = ZipArchive::new(file)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if !file.name().to_lowercase().ends_with(".osu") {
+            continue;
+        }
+        let Ok(rel) = clean_entry_path(file.name()) else {
+            continue;
+        };
+        let Ok(existing) = fs::read(dest.join(rel)) else {
+            continue;
+        };
+        let mut archived = Vec::new();
+        io::Read::read_to_end(&mut file, &mut archived).map_err(ZipError::from)?;
+        if archived == existing {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn short_osz_hash(entry: &BeatmapEntry) -> Result<String, ImportError> {
+    let hash = match entry.osz_hash.as_ref() {
+        Some(hash) => hash.clone(),
+        None => {
+            let bytes = fs::read(&entry.osz_path).map_err(ZipError::from)?;
+            blake3::hash(&bytes).to_hex().to_string()
+        }
+    };
+    Ok(hash.chars().take(8).collect())
+}
+
+/// Lists files under `dest` (relative to it) that extracting the archive would not recreate.
+pub fn files_missing_from_archive(osz_path: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+    let file = fs::File::open(osz_path).context("abrindo arquivo .osz")?;
+    let mut archive = ZipArchive::new(file).context("lendo arquivo zip")?;
+    let mut archived = HashSet::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if let Ok(rel) = clean_entry_path(file.name()) {
+            archived.insert(rel);
+        }
+    }
+    let mut extras = Vec::new();
+    let mut dirs = vec![dest.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for item in fs::read_dir(&dir)? {
+            let path = item?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let rel = path.strip_prefix(dest).unwrap_or(&path).to_path_buf();
+            if !archived.contains(&rel) {
+                extras.push(rel);
+            }
+        }
+    }
+    extras.sort();
+    Ok(extras)
+}
+
+/// `.osu` files whose difficulty was deselected; audio, backgrounds and other assets are always kept.
+fn unselected_difficulty_files(entry: &BeatmapEntry, meta: &BeatmapMetadata) -> Vec<String> {
+    if entry.selected_difficulties.is_none() {
+        return Vec::new();
+    }
+    meta.difficulty_info
+        .iter()
+        .filter(|d| !entry.is_difficulty_selected(&d.version))
+        .map(|d| d.file_name.clone())
+        .collect()
 }
 
 fn build_folder_name(meta: &BeatmapMetadata, osz_path: &Path) -> String {
-    let mut base = format!("{} - {} ({})", meta.artist, meta.title, meta.creator);
+    // Always the romanized title/artist: original scripts can trip up filesystems and McOsu.
+    // Archives accepted by relaxed validation only carry a title (the file name).
+    let mut base = if meta.artist.is_empty() && meta.creator.is_empty() {
+        meta.title.clone()
+    } else {
+        format!("{} - {} ({})", meta.artist, meta.title, meta.creator)
+    };
     if let Some(set_id) = meta.beatmap_set_id {
         base.push_str(&format!(" [{}]", set_id));
     }
@@ -76,7 +473,7 @@ fn build_folder_name(meta: &BeatmapMetadata, osz_path: &Path) -> String {
     sanitized
 }
 
-fn build_safe_path(base: &Path, inside_zip: &str) -> Result<PathBuf> {
+fn clean_entry_path(inside_zip: &str) -> Result<PathBuf> {
     let mut clean = PathBuf::new();
     for comp in Path::new(inside_zip).components() {
         match comp {
@@ -88,12 +485,30 @@ fn build_safe_path(base: &Path, inside_zip: &str) -> Result<PathBuf> {
     if clean.as_os_str().is_empty() {
         return Err(anyhow!("Entrada de ZIP vazia"));
     }
+    Ok(clean)
+}
+
+fn create_dir(path: &Path) -> Result<(), ImportError> {
+    fs::create_dir_all(path).map_err(|source| ImportError::CreateDir {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn build_safe_path(base: &Path, inside_zip: &str) -> Result<PathBuf, ImportError> {
+    let zip_slip = || ImportError::ZipSlip {
+        entry: inside_zip.to_string(),
+    };
+    let clean = clean_entry_path(inside_zip).map_err(|_| zip_slip())?;
     let candidate = base.join(clean);
-    let canon_base = fs::canonicalize(base)?;
+    let canon_base = fs::canonicalize(base).map_err(|source| ImportError::CreateDir {
+        path: base.to_path_buf(),
+        source,
+    })?;
     let canon_candidate =
         fs::canonicalize(candidate.parent().unwrap_or(base)).unwrap_or(base.to_path_buf());
     if !canon_candidate.starts_with(&canon_base) {
-        return Err(anyhow!("Tentativa de Zip Slip detectada"));
+        return Err(zip_slip());
     }
     Ok(base.join(candidate.strip_prefix(base).unwrap_or(&candidate)))
 }
@@ -101,7 +516,7 @@ fn build_safe_path(base: &Path, inside_zip: &str) -> Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::app_state::BeatmapMetadata;
+    use crate::app_state::{AudioPreview, BeatmapMetadata, DifficultyInfo};
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -116,12 +531,53 @@ mod tests {
             beatmap_ids: vec![],
             background_file: None,
             audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
         };
         let name = build_folder_name(&meta, Path::new("file.osz"));
         assert!(!name.contains('*'));
         assert!(name.contains("Art"));
     }
 
+    #[test]
+    fn folder_name_uses_romanized_metadata() {
+        let meta = BeatmapMetadata {
+            title: "Senbonzakura".into(),
+            artist: "Kurousa-P".into(),
+            creator: "Mapper".into(),
+            difficulties: vec![],
+            beatmap_set_id: Some(7),
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: Some("\u{5343}\u{672c}\u{685c}".into()),
+            artist_unicode: Some("\u{9ed2}\u{3046}\u{3055}P".into()),
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+        assert_eq!(
+            build_folder_name(&meta, Path::new("file.osz")),
+            "Kurousa-P - Senbonzakura (Mapper) [7]"
+        );
+        assert_eq!(meta.display_title(false), "Kurousa-P - Senbonzakura");
+        assert_eq!(
+            meta.display_title(true),
+            "\u{9ed2}\u{3046}\u{3055}P - \u{5343}\u{672c}\u{685c}"
+        );
+    }
+
     #[test]
     fn import_creates_files() {
         let dir = tempdir().unwrap();
@@ -145,6 +601,15 @@ mod tests {
             beatmap_ids: vec![],
             background_file: None,
             audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
         };
         let entry = BeatmapEntry {
             id: 1,
@@ -159,12 +624,277 @@ mod tests {
             destination: None,
             osz_hash: None,
             audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
         };
         let songs_dir = dir.path().join("songs");
         fs::create_dir_all(&songs_dir).unwrap();
-        let res = import_osz(&entry, &meta, &songs_dir, false).unwrap();
+        let mut reports = Vec::new();
+        let res = import_osz(&entry, &meta, &songs_dir, false, false, &AtomicBool::new(false), |done, total| {
+            reports.push((done, total))
+        })
+        .unwrap();
         assert!(res.destination.exists());
         assert!(res.destination.join("song.txt").exists());
+        assert_eq!(reports, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn import_skips_unselected_difficulties() {
+        let dir = tempdir().unwrap();
+        let osz_path = dir.path().join("set.osz");
+        {
+            let file = fs::File::create(&osz_path).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::FileOptions::default();
+            for name in ["easy.osu", "hard.osu", "audio.mp3"] {
+                zip.start_file(name, options).unwrap();
+                write!(zip, "{name}").unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        let difficulty = |version: &str, file_name: &str| DifficultyInfo {
+            version: version.into(),
+            file_name: file_name.into(),
+            beatmap_id: None,
+            format_version: None,
+            mode: None,
+            settings: Default::default(),
+        };
+        let meta = BeatmapMetadata {
+            difficulties: vec!["Easy".into(), "Hard".into()],
+            beatmap_set_id: Some(7),
+            audio_file: Some("audio.mp3".into()),
+            difficulty_info: vec![difficulty("Easy", "easy.osu"), difficulty("Hard", "hard.osu")],
+            ..crate::app_state::test_metadata()
+        };
+        let entry = BeatmapEntry {
+            selected_difficulties: Some(vec!["Hard".into()]),
+            ..crate::app_state::test_entry(osz_path.clone())
+        };
+        let songs_dir = dir.path().join("songs");
+        fs::create_dir_all(&songs_dir).unwrap();
+        let res = import_osz(&entry, &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert!(!res.destination.join("easy.osu").exists());
+        assert!(res.destination.join("hard.osu").exists());
+        assert!(res.destination.join("audio.mp3").exists());
+    }
+
+    #[test]
+    fn space_check_reports_sizes_in_mb() {
+        assert!(ensure_fits(10, None).is_ok());
+        assert!(ensure_fits(10, Some(10)).is_ok());
+        let err = ensure_fits(5 * 1_048_576, Some(1_048_576 + 1)).unwrap_err();
+        assert_eq!(err.to_string(), "Espaco insuficiente: precisa 5 MB, tem 1 MB");
+        assert!(err.is_retryable());
+        assert_eq!(err.failure_kind(), FailureKind::Write);
+    }
+
+    #[test]
+    fn existing_folder_is_found_by_name() {
+        let dir = tempdir().unwrap();
+        let songs = dir.path().join("Songs");
+        let mut meta = BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Mapper".into(),
+            difficulties: vec!["Hard".into()],
+            beatmap_set_id: Some(42),
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![DifficultyInfo {
+                version: "Hard".into(),
+                file_name: "Artist - Title (Mapper) [Hard].osu".into(),
+                beatmap_id: None,
+                format_version: None,
+                mode: None,
+                settings: Default::default(),
+            }],
+        };
+        let osz = Path::new("mirror.osz");
+        assert_eq!(find_folder_by_name(&meta, osz, &songs), None);
+
+        let existing = songs.join("artist - title (mapper) [42]");
+        fs::create_dir_all(&existing).unwrap();
+        assert_eq!(find_folder_by_name(&meta, osz, &songs), Some(existing));
+
+        // Without a set id the folder must also hold one of the map's difficulties.
+        meta.beatmap_set_id = None;
+        let unnamed = songs.join("Artist - Title (Mapper)");
+        fs::create_dir_all(&unnamed).unwrap();
+        assert_eq!(find_folder_by_name(&meta, osz, &songs), None);
+        fs::write(unnamed.join("Artist - Title (Mapper) [Hard].osu"), "osu").unwrap();
+        assert_eq!(find_folder_by_name(&meta, osz, &songs), Some(unnamed));
+    }
+
+    #[test]
+    fn import_can_skip_background_video() {
+        let dir = tempdir().unwrap();
+        let osz_path = dir.path().join("set.osz");
+        {
+            let file = fs::File::create(&osz_path).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::FileOptions::default();
+            zip.start_file("map.osu", options).unwrap();
+            write!(zip, "osu").unwrap();
+            zip.start_file("audio.mp3", options).unwrap();
+            write!(zip, "mp3").unwrap();
+            zip.start_file("bg.MP4", options).unwrap();
+            zip.write_all(&[0u8; 2048]).unwrap();
+            zip.finish().unwrap();
+        }
+        let meta = BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Creator".into(),
+            difficulties: vec![],
+            beatmap_set_id: Some(8),
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: Some("audio.mp3".into()),
+            has_video: true,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+        let entry = BeatmapEntry {
+            id: 1,
+            osz_path: osz_path.clone(),
+            status: crate::app_state::ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: std::time::SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        let songs_dir = dir.path().join("songs");
+        fs::create_dir_all(&songs_dir).unwrap();
+        let mut reports = Vec::new();
+        let res = import_osz(&entry, &meta, &songs_dir, false, true, &AtomicBool::new(false), |done, total| {
+            reports.push((done, total))
+        })
+        .unwrap();
+        assert_eq!(res.skipped_video_bytes, 2048);
+        assert!(!res.destination.join("bg.MP4").exists());
+        assert!(res.destination.join("map.osu").exists());
+        assert!(res.destination.join("audio.mp3").exists());
+        assert_eq!(reports.last(), Some(&(2, 2)));
+
+        let res = import_osz(&entry, &meta, &songs_dir, true, false, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert_eq!(res.skipped_video_bytes, 0);
+        assert!(res.destination.join("bg.MP4").exists());
+    }
+
+    #[test]
+    fn lists_files_not_in_archive() {
+        let dir = tempdir().unwrap();
+        let osz_path = dir.path().join("set.osz");
+        {
+            let file = fs::File::create(&osz_path).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            let options = zip::write::FileOptions::default();
+            zip.start_file("map.osu", options).unwrap();
+            write!(zip, "osu").unwrap();
+            zip.finish().unwrap();
+        }
+        let dest = dir.path().join("dest");
+        fs::create_dir_all(dest.join("sb")).unwrap();
+        fs::write(dest.join("map.osu"), "old").unwrap();
+        fs::write(dest.join("sb").join("custom.png"), "edit").unwrap();
+        let extras = files_missing_from_archive(&osz_path, &dest).unwrap();
+        assert_eq!(extras, vec![PathBuf::from("sb").join("custom.png")]);
+    }
+
+    #[test]
+    fn name_collision_with_different_map_gets_disambiguator() {
+        let dir = tempdir().unwrap();
+        let write_osz = |name: &str, osu: &str| {
+            let path = dir.path().join(name);
+            let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+            zip.start_file("map.osu", zip::write::FileOptions::default()).unwrap();
+            write!(zip, "{osu}").unwrap();
+            zip.finish().unwrap();
+            path
+        };
+        let first = write_osz("first.osz", "first map");
+        let second = write_osz("second.osz", "second map");
+        let meta = BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Creator".into(),
+            difficulties: vec![],
+            beatmap_set_id: None,
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+        let entry_for = |osz_path: PathBuf| BeatmapEntry {
+            id: 1,
+            osz_path,
+            status: crate::app_state::ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: std::time::SystemTime::now(),
+            destination: None,
+            osz_hash: Some("0123456789abcdef".into()),
+            audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        let songs_dir = dir.path().join("songs");
+        fs::create_dir_all(&songs_dir).unwrap();
+
+        let a = import_osz(&entry_for(first.clone()), &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert!(!a.duplicated);
+        let b = import_osz(&entry_for(second), &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert!(!b.duplicated);
+        assert_ne!(a.destination, b.destination);
+        assert!(b.destination.to_string_lossy().ends_with("[01234567]"));
+
+        let again = import_osz(&entry_for(first), &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {}).unwrap();
+        assert!(again.duplicated);
+        assert_eq!(again.destination, a.destination);
     }
 
     #[test]
@@ -188,6 +918,15 @@ mod tests {
             beatmap_ids: vec![],
             background_file: None,
             audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
         };
         let entry = BeatmapEntry {
             id: 1,
@@ -202,10 +941,223 @@ mod tests {
             destination: None,
             osz_hash: None,
             audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
         };
         let songs_dir = dir.path().join("songs");
         fs::create_dir_all(&songs_dir).unwrap();
-        let res = import_osz(&entry, &meta, &songs_dir, false);
+        let res = import_osz(&entry, &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {});
         assert!(res.is_err());
+        assert!(matches!(res, Err(ImportError::ZipSlip { .. })));
+    }
+
+    #[test]
+    fn failed_import_leaves_no_partial_folder() {
+        let dir = tempdir().unwrap();
+        let osz_path = dir.path().join("broken.osz");
+        {
+            let mut zip = zip::ZipWriter::new(fs::File::create(&osz_path).unwrap());
+            let options = zip::write::FileOptions::default();
+            zip.start_file("map.osu", options).unwrap();
+            write!(zip, "new map").unwrap();
+            zip.start_file("../bad.txt", options).unwrap();
+            write!(zip, "bad").unwrap();
+            zip.finish().unwrap();
+        }
+        let meta = BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Creator".into(),
+            difficulties: vec![],
+            beatmap_set_id: Some(7),
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+        let entry = BeatmapEntry {
+            id: 1,
+            osz_path,
+            status: crate::app_state::ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: std::time::SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        let songs_dir = dir.path().join("songs");
+        fs::create_dir_all(&songs_dir).unwrap();
+
+        assert!(import_osz(&entry, &meta, &songs_dir, false, false, &AtomicBool::new(false), |_, _| {}).is_err());
+        assert_eq!(fs::read_dir(&songs_dir).unwrap().count(), 0);
+
+        // A failed forced reimport keeps the previously imported folder untouched.
+        let dest = destination_path(&meta, &entry.osz_path, &songs_dir);
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("map.osu"), "old map").unwrap();
+        assert!(import_osz(&entry, &meta, &songs_dir, true, false, &AtomicBool::new(false), |_, _| {}).is_err());
+        assert_eq!(fs::read_to_string(dest.join("map.osu")).unwrap(), "old map");
+        assert_eq!(fs::read_dir(&songs_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn forced_reimport_restores_the_old_folder_when_the_copy_fails() {
+        let dir = tempdir().unwrap();
+        let dest = dir.path().join("1 Artist - Title");
+        let staging = sibling_path(&dest, "importing", 1);
+        let backup = sibling_path(&dest, "backup", 1);
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("map.osu"), "old map").unwrap();
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("map.osu"), "new map").unwrap();
+
+        let failed = replace_with_staging(&staging, &dest, Some(&backup), |_, to| {
+            Err(ImportError::WriteFile {
+                path: to.join("map.osu"),
+                source: io::Error::other("disco cheio"),
+            })
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(dest.join("map.osu")).unwrap(), "old map");
+        assert!(!staging.exists());
+        assert!(!backup.exists());
+
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("map.osu"), "new map").unwrap();
+        replace_with_staging(&staging, &dest, Some(&backup), move_staging).unwrap();
+        assert_eq!(fs::read_to_string(dest.join("map.osu")).unwrap(), "new map");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn staging_never_merges_into_a_destination_that_reappeared() {
+        let dir = tempdir().unwrap();
+        let dest = dir.path().join("1 Artist - Title");
+        let staging = sibling_path(&dest, "importing", 1);
+        assert_ne!(staging, sibling_path(&dest, "importing", 1));
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("map.osu"), "new map").unwrap();
+        // Another import finished first.
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("other.osu"), "other import").unwrap();
+
+        assert!(replace_with_staging(&staging, &dest, None, move_staging).is_err());
+        assert_eq!(fs::read_to_string(dest.join("other.osu")).unwrap(), "other import");
+        assert!(!dest.join("map.osu").exists());
+        assert!(!staging.exists());
+    }
+
+    #[test]
+    fn cancelled_import_leaves_nothing_behind() {
+        let dir = tempdir().unwrap();
+        let osz_path = dir.path().join("big.osz");
+        {
+            let mut zip = zip::ZipWriter::new(fs::File::create(&osz_path).unwrap());
+            let options = zip::write::FileOptions::default();
+            for i in 0..5 {
+                zip.start_file(format!("hitsound{i}.wav"), options).unwrap();
+                write!(zip, "wav").unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        let meta = BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Creator".into(),
+            difficulties: vec![],
+            beatmap_set_id: Some(8),
+            beatmap_ids: vec![],
+            background_file: None,
+            audio_file: None,
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+        let entry = BeatmapEntry {
+            id: 1,
+            osz_path,
+            status: crate::app_state::ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: std::time::SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        let songs_dir = dir.path().join("songs");
+        fs::create_dir_all(&songs_dir).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let res = import_osz(&entry, &meta, &songs_dir, false, false, &cancel, |done, _| {
+            if done == 2 {
+                cancel.store(true, Ordering::SeqCst);
+            }
+        });
+        assert!(matches!(res, Err(ImportError::Cancelled)));
+        assert_eq!(fs::read_dir(&songs_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn copy_fallback_reproduces_the_tree() {
+        let dir = tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("sb")).unwrap();
+        fs::write(from.join("map.osu"), "map").unwrap();
+        fs::write(from.join("sb").join("bg.png"), "png").unwrap();
+        let to = dir.path().join("to");
+        copy_dir_recursive(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(to.join("map.osu")).unwrap(), "map");
+        assert_eq!(fs::read_to_string(to.join("sb").join("bg.png")).unwrap(), "png");
+    }
+
+    #[test]
+    fn permission_errors_are_not_retryable() {
+        let denied = ImportError::WriteFile {
+            path: PathBuf::from("x"),
+            source: io::Error::from(io::ErrorKind::PermissionDenied),
+        };
+        assert!(!denied.is_retryable());
+        assert!(denied.is_permission_denied());
+        assert_eq!(denied.short_message(), "Sem permissao para gravar na pasta Songs");
+        let locked = ImportError::CreateDir {
+            path: PathBuf::from("x"),
+            source: io::Error::other("busy"),
+        };
+        assert!(locked.is_retryable());
+        assert_eq!(locked.short_message(), "Falha ao criar/gravar na pasta destino");
     }
 }
@@ -1,36 +1,47 @@
 mod app_state;
 mod cache;
 mod concurrency;
+mod disk;
 mod audio;
+// Only the mirror helpers are used so far; the download queue built on the rest isn't wired up.
+#[allow(dead_code)]
+mod downloader;
 mod preview;
+mod http;
 mod importer;
+mod notifications;
 mod osu_parser;
 mod osz_reader;
 mod path_utils;
+mod rate_limit;
+mod steam;
+mod throttle;
+mod tray;
 mod watcher;
 
 use arboard::Clipboard;
-use audio::AudioPlayer;
+use audio::{AudioPlayer, AudioSource};
 use anyhow::Context;
-use std::collections::HashMap;
-use std::fs::{self, create_dir_all, OpenOptions};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
 use std::env;
 use std::io::{Read, Write};
 use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use urlencoding::encode;
 use serde::{de, Deserialize, Deserializer};
 
-use app_state::{AppConfig, AudioPreviewStatus, BeatmapEntry, ImportStatus};
+use app_state::{AppConfig, AudioPreviewStatus, BeatmapEntry, FailureKind, ImportStatus, ViewMode};
 use cache::{CacheStore, load_config, save_config};
-use concurrency::ImportGuards;
+use concurrency::{ImportGuards, ImportPriority};
 use path_utils::{
     can_delete_source, downloads_songs_conflict, is_within_dir, validate_songs_choice,
 };
-use slint::{Color, SharedString};
+use slint::{Color, Model, SharedString};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 slint::include_modules!();
@@ -40,23 +51,48 @@ enum CommandMsg {
     AddFile(PathBuf),
     ManualImport(u64, bool),
     ImportAll,
+    ImportNew,
+    ImportSelected(Vec<u64>),
+    ReimportAllForced,
+    RetryFailed,
+    AnalyzeQueue,
+    CancelBulk,
+    ExpandPack(u64),
     ClearCompleted,
+    PrecacheAudio,
+    AutoClearCompleted(std::time::Duration),
     UpdateConfig(AppConfig),
     AddFileDialog,
     OpenSource(u64),
     OpenDestination(u64),
     OpenBrowser(u64),
-    SearchBeatmaps(String),
-    DownloadBeatmap(u64),
+    CopyBeatmapLink(u64),
+    OpenDifficultyPage(i32),
+    SearchBeatmaps(String, SearchFilter),
+    LoadMoreBeatmaps,
+    DownloadBeatmap(BeatmapSearchResult),
     CopyLogs,
+    OpenDataFolder(PathBuf),
+    ClearCache,
+    CopyPreviewUrl(String),
+    RestartPreviewServer,
     DeleteSource(u64),
     Ignore(u64),
+    CancelImport(u64),
     ToggleAutoDelete(bool),
     ConfirmAutoDelete(bool),
     CancelAutoDeletePrompt,
     ShowErrorDetail(u64),
+    ShowSearchErrorDetail,
     PreviewAudio(u64),
+    StopAudio(u64),
+    StopAllAudio,
     PreviewMap(u64),
+    ClearPreview(u64),
+    SetDifficultySelected(u64, String, bool),
+    SetSongsTarget(u64, PathBuf),
+    UndoImport(u64),
+    PauseAutoImport(bool),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,12 +108,17 @@ enum UiMsg {
     Log(LogLevel, String),
     ConfigChanged(AppConfig, Option<String>),
     ReplaceAll(Vec<BeatmapEntry>),
-    BeatmapSearchState { loading: bool, message: Option<String> },
+    BeatmapSearchState { loading: bool, message: Option<String>, has_detail: bool },
     BeatmapResults(Vec<BeatmapSearchResult>),
+    BeatmapMoreAvailable(bool),
+    /// Cover of this beatmapset was fetched into the thumbnail cache.
+    SearchCover(u64, PathBuf),
     BeatmapDownloadStatus { active: bool, text: Option<String> },
     ShowAutoDeletePrompt,
     HideAutoDeletePrompt,
     BulkRunning(bool),
+    BulkProgress { done: usize, total: usize },
+    PreviewUrl(String),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -89,20 +130,134 @@ enum BeatmapSource {
 #[derive(Clone, Debug)]
 struct BeatmapSearchResult {
     id: u64,
+    set_id: u64,
     title: String,
     artist: String,
     creator: String,
     source: BeatmapSource,
     download_url: String,
+    stats: SearchStats,
+    /// `.osz` size reported by the source mirror's API; only compared (and logged), since
+    /// the metadata may describe another variant of the file.
+    expected_size: Option<u64>,
+    /// Cached cover thumbnail; `None` until it has been fetched.
+    cover_path: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug)]
 struct BeatmapFound {
+    set_id: u64,
     title: String,
     artist: String,
     creator: String,
     source: BeatmapSource,
     download_url: String,
+    stats: SearchStats,
+    expected_size: Option<u64>,
+}
+
+/// Set details a mirror may report; `None` when the response doesn't include them.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SearchStats {
+    difficulty_count: Option<usize>,
+    length_secs: Option<u64>,
+    bpm: Option<f64>,
+    /// Game mode of the set (0 osu!, 1 taiko, 2 catch, 3 mania).
+    mode: Option<u8>,
+}
+
+impl SearchStats {
+    /// "4 dif. | 2:15 | 180 BPM", leaving out whatever is unknown.
+    fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(count) = self.difficulty_count {
+            parts.push(format!("{count} dif."));
+        }
+        if let Some(secs) = self.length_secs {
+            parts.push(format!("{}:{:02}", secs / 60, secs % 60));
+        }
+        if let Some(bpm) = self.bpm {
+            parts.push(format!("{:.0} BPM", bpm));
+        }
+        parts.join(" | ")
+    }
+}
+
+/// Client-side filters for search results. Results missing a value are kept, since
+/// only some mirrors report difficulty count, length or BPM.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SearchFilter {
+    min_difficulties: Option<usize>,
+    min_length_secs: Option<u64>,
+    max_length_secs: Option<u64>,
+    min_bpm: Option<f64>,
+    max_bpm: Option<f64>,
+    /// Only sets of this mode; `None` (the default) shows every mode.
+    mode: Option<u8>,
+}
+
+impl SearchFilter {
+    fn from_inputs(min_diffs: &str, min_len: &str, max_len: &str, min_bpm: &str, max_bpm: &str) -> Self {
+        let number = |text: &str| text.trim().replace(',', ".").parse::<f64>().ok().filter(|v| *v > 0.0);
+        Self {
+            min_difficulties: min_diffs.trim().parse::<usize>().ok().filter(|v| *v > 0),
+            min_length_secs: parse_length_input(min_len),
+            max_length_secs: parse_length_input(max_len),
+            min_bpm: number(min_bpm),
+            max_bpm: number(max_bpm),
+            mode: None,
+        }
+    }
+
+    /// Mode picked in the search box: 0 is "Todos", then osu!, Taiko, Catch and Mania.
+    fn with_mode_choice(self, index: i32) -> Self {
+        Self {
+            mode: (1..=4).contains(&index).then(|| (index - 1) as u8),
+            ..self
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn matches(&self, stats: &SearchStats) -> bool {
+        let at_least = |value: Option<f64>, min: Option<f64>| match (value, min) {
+            (Some(v), Some(min)) => v >= min,
+            _ => true,
+        };
+        let at_most = |value: Option<f64>, max: Option<f64>| match (value, max) {
+            (Some(v), Some(max)) => v <= max,
+            _ => true,
+        };
+        let count = stats.difficulty_count.map(|c| c as f64);
+        let length = stats.length_secs.map(|l| l as f64);
+        at_least(count, self.min_difficulties.map(|c| c as f64))
+            && at_least(length, self.min_length_secs.map(|l| l as f64))
+            && at_most(length, self.max_length_secs.map(|l| l as f64))
+            && at_least(stats.bpm, self.min_bpm)
+            && at_most(stats.bpm, self.max_bpm)
+            && (self.mode.is_none() || stats.mode.is_none() || stats.mode == self.mode)
+    }
+}
+
+/// Stores `query` as the search to prefill next launch; false when it was already the last one.
+fn remember_query(cfg: &mut AppConfig, query: &str) -> bool {
+    if cfg.last_query.as_deref() == Some(query) {
+        return false;
+    }
+    cfg.last_query = Some(query.to_string());
+    true
+}
+
+/// Accepts plain seconds ("90") or minutes and seconds ("1:30"); empty or zero means unset.
+fn parse_length_input(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let secs = match text.split_once(':') {
+        Some((min, sec)) => min.trim().parse::<u64>().ok()? * 60 + sec.trim().parse::<u64>().ok()?,
+        None => text.parse::<u64>().ok()?,
+    };
+    (secs > 0).then_some(secs)
 }
 
 #[derive(Deserialize, Debug)]
@@ -121,6 +276,8 @@ struct CatboyBeatmap {
     artist: String,
     #[serde(rename = "Creator")]
     creator: String,
+    #[serde(rename = "FileSize", default)]
+    file_size: Option<u64>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -135,6 +292,33 @@ struct NerinyanBeatmap {
     creator: String,
     #[serde(rename = "mode")]
     mode: Option<u8>,
+    #[serde(default)]
+    bpm: Option<f64>,
+    #[serde(default)]
+    beatmaps: Vec<NerinyanDifficulty>,
+    #[serde(default)]
+    file_size: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct NerinyanDifficulty {
+    #[serde(default)]
+    total_length: Option<u64>,
+    #[serde(default)]
+    bpm: Option<f64>,
+}
+
+impl NerinyanBeatmap {
+    fn stats(&self) -> SearchStats {
+        SearchStats {
+            difficulty_count: (!self.beatmaps.is_empty()).then_some(self.beatmaps.len()),
+            length_secs: self.beatmaps.iter().filter_map(|d| d.total_length).max(),
+            bpm: self
+                .bpm
+                .or_else(|| self.beatmaps.iter().filter_map(|d| d.bpm).reduce(f64::max)),
+            mode: self.mode,
+        }
+    }
 }
 
 fn deserialize_flexible_id<'de, D>(deserializer: D) -> Result<u64, D::Error>
@@ -175,8 +359,94 @@ fn load_startup_config() -> AppConfig {
     cfg
 }
 
+/// First launch: offers the McOsu `Songs` folders found in the Steam libraries, since the
+/// `Downloads/McOsuSongs` default is rarely right. Returns the folder the user accepted.
+fn suggest_songs_folder(cfg: &mut AppConfig, candidates: &[PathBuf]) -> Option<PathBuf> {
+    for candidate in candidates {
+        if validate_songs_choice(&cfg.downloads_dir, candidate).is_err() {
+            continue;
+        }
+        let answer = rfd::MessageDialog::new()
+            .set_title("Pasta Songs do McOsu encontrada")
+            .set_description(&format!(
+                "Encontramos a pasta Songs do McOsu em:\n{}\n\nUsar esta pasta para importar os beatmaps?",
+                candidate.display()
+            ))
+            .set_buttons(rfd::MessageButtons::OkCancel)
+            .show();
+        if answer == rfd::MessageDialogResult::Ok {
+            cfg.set_default_songs_dir(candidate.clone());
+            return Some(candidate.clone());
+        }
+    }
+    None
+}
+
+/// Validates a new Songs folder, shows any warnings and makes it the default destination.
+fn choose_songs_dir(path: PathBuf, tx: &mpsc::Sender<CommandMsg>) {
+    let mut cfg = load_config();
+    if !check_songs_choice(&cfg, &path) {
+        return;
+    }
+    cfg.set_default_songs_dir(path);
+    let _ = save_config(&cfg);
+    let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+}
+
+/// Validates another Songs folder and adds it to the destinations a card can pick.
+fn add_songs_dir(path: PathBuf, tx: &mpsc::Sender<CommandMsg>) {
+    let mut cfg = load_config();
+    if !check_songs_choice(&cfg, &path) {
+        return;
+    }
+    cfg.songs_dirs.push(path);
+    cfg.songs_dirs = cfg.songs_destinations();
+    let _ = save_config(&cfg);
+    let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+}
+
+/// Refuses a Songs folder that overlaps Downloads and warns about protected or synced ones.
+fn check_songs_choice(cfg: &AppConfig, path: &Path) -> bool {
+    if let Err(msg) = validate_songs_choice(&cfg.downloads_dir, path) {
+        rfd::MessageDialog::new()
+            .set_title("Caminho inseguro")
+            .set_description(&msg)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+        return false;
+    }
+    if let Some(msg) = path_utils::protected_songs_warning(path) {
+        rfd::MessageDialog::new()
+            .set_title("Pasta protegida")
+            .set_description(&msg)
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+    }
+    if let Some(msg) = path_utils::cloud_sync_warning(path, &cfg.cloud_sync_markers) {
+        rfd::MessageDialog::new()
+            .set_title("Pasta sincronizada")
+            .set_description(&msg)
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+    }
+    true
+}
+
+/// Paths in an imported profile may come from another machine: both folders must exist here
+/// and pass the usual Songs checks.
+fn validate_imported_paths(cfg: &AppConfig) -> Result<(), String> {
+    for (label, dir) in [("Downloads", &cfg.downloads_dir), ("Songs", &cfg.songs_dir)] {
+        if !dir.is_dir() {
+            return Err(format!("Pasta {label} do perfil nao existe: {}.", dir.display()));
+        }
+    }
+    validate_songs_choice(&cfg.downloads_dir, &cfg.songs_dir)
+}
+
 fn enforce_path_safety(cfg: &mut AppConfig) -> Option<String> {
-    let warning = downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir);
+    let warning = songs_destinations_conflict(cfg);
     if warning.is_some() {
         cfg.auto_import = false;
         cfg.auto_delete_source = false;
@@ -192,11 +462,6 @@ fn main() -> anyhow::Result<()> {
     app_state::ensure_dir(&cache::audio_cache_dir())?;
     app_state::ensure_dir(&cache::preview_dir())?;
 
-    let log_dir = Path::new("logs");
-    if !log_dir.exists() {
-        create_dir_all(log_dir)?;
-    }
-
     let file_appender = tracing_appender::rolling::never(cache::logs_dir(), "app.log");
     let (nb_writer, _log_guard) = tracing_appender::non_blocking(file_appender);
     let console_layer = tracing_subscriber::fmt::layer().with_writer(std::io::stdout);
@@ -207,11 +472,27 @@ fn main() -> anyhow::Result<()> {
         .with(console_layer)
         .with(file_layer)
         .init();
+    let base_dir_warning = cache::base_dir_warning();
+    if let Some(warning) = base_dir_warning.as_deref() {
+        tracing::warn!("{warning}");
+    }
 
     let app = AppWindow::new()?;
+    let first_run = !cache::config_exists();
     let mut config = load_startup_config();
+    let songs_candidates = steam::find_mcosu_songs();
+    let detected_songs = if first_run {
+        suggest_songs_folder(&mut config, &songs_candidates)
+    } else {
+        None
+    };
     let cache_store = Arc::new(CacheStore::load());
+    cache_store.set_media_limit_mb(config.media_cache_max_mb);
+    if !cache_store.enforce_limits().is_empty() {
+        let _ = cache_store.save();
+    }
     let guards = Arc::new(ImportGuards::default());
+    guards.set_import_limit(config.max_parallel_imports);
     let initial_warning = enforce_path_safety(&mut config);
     let _ = save_config(&config);
     let shared_config: Arc<Mutex<AppConfig>> = Arc::new(Mutex::new(config.clone()));
@@ -219,42 +500,80 @@ fn main() -> anyhow::Result<()> {
     let beatmap_entries: Arc<Mutex<HashMap<u64, BeatmapEntry>>> =
         Arc::new(Mutex::new(HashMap::new()));
     let ui_state_entries = Arc::new(Mutex::new(Vec::<BeatmapEntry>::new()));
+    // Queue entries ticked in the UI; only touched on the UI thread.
+    let queue_selection = Arc::new(Mutex::new(HashSet::<u64>::new()));
     let log_state = Arc::new(Mutex::new(Vec::<(LogLevel, String)>::new()));
+    // Search results as currently listed, keyed by row id; only touched on the UI thread so a
+    // click resolves against what the user sees even while a new search is running.
     let search_results_state: Arc<Mutex<HashMap<u64, BeatmapSearchResult>>> =
         Arc::new(Mutex::new(HashMap::new()));
 
     let (cmd_tx, cmd_rx) = mpsc::channel::<CommandMsg>();
     let (ui_tx, ui_rx) = mpsc::channel::<UiMsg>();
 
-    seed_existing_osz(&config.downloads_dir, &cmd_tx)?;
+    let mut already_processed = 0;
+    for dir in config.watch_dirs() {
+        already_processed +=
+            seed_existing_osz(&dir, &config.accepted_extensions, &cmd_tx, &cache_store)?;
+    }
+    if already_processed > 0 {
+        let _ = ui_tx.send(UiMsg::Log(
+            LogLevel::Info,
+            format!("{already_processed} arquivo(s) ja processados e sem alteracoes ignorados"),
+        ));
+    }
+    if let Some(warning) = base_dir_warning {
+        let _ = ui_tx.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!("{warning}. Configuracoes, cache e logs serao gravados nessa pasta."),
+        ));
+    }
 
-    // Start watcher
-    {
+    // Start watcher; the worker thread owns it and re-targets it when the folders change.
+    let dir_watcher = {
         let tx = cmd_tx.clone();
-        let dir = config.downloads_dir.clone();
-        watcher::start_watcher(dir, move |path| {
+        watcher::start_watcher(&config.watch_dirs(), &config.accepted_extensions, move |path| {
             let _ = tx.send(CommandMsg::AddFile(path));
-        })?;
-    }
+        })?
+    };
 
     // UI wiring
     app.set_download_path(SharedString::from(
         config.downloads_dir.display().to_string(),
     ));
     app.set_songs_path(SharedString::from(config.songs_dir.display().to_string()));
+    app.set_songs_choices(songs_choices_model(&config));
+    app.set_songs_suggestions(slint::ModelRc::new(Rc::new(slint::VecModel::from(
+        songs_candidates
+            .iter()
+            .map(|p| SharedString::from(p.display().to_string()))
+            .collect::<Vec<_>>(),
+    ))));
     app.set_auto_import(config.auto_import);
     app.set_auto_delete_after_import(config.auto_delete_source);
+    app.set_desktop_notifications(config.desktop_notifications);
+    app.set_minimize_to_tray(config.minimize_to_tray);
+    app.set_grid_view(config.view_mode == ViewMode::Grid);
+    app.set_unicode_metadata(config.show_unicode_metadata);
+    app.set_download_limit(download_limit_text(config.max_download_kbps));
     app.set_show_completed(true);
     app.set_paths_blocked(initial_warning.is_some());
     app.set_bulk_import_running(false);
     app.set_auto_delete_prompt_visible(false);
     app.set_auto_delete_prompt_skip(false);
     app.set_active_tab(0);
-    app.set_beatmap_query(SharedString::default());
+    app.set_beatmap_query(SharedString::from(config.last_query.clone().unwrap_or_default()));
     app.set_beatmap_loading(false);
     app.set_beatmap_downloading(false);
     app.set_beatmap_status(SharedString::default());
     app.set_beatmap_message(SharedString::default());
+    app.set_preview_url(SharedString::default());
+    app.set_data_dirs_text(SharedString::from(format!(
+        "Dados: {} | Cache: {} | Logs: {}",
+        cache::base_dir().display(),
+        cache::cache_dir().display(),
+        cache::logs_dir().display()
+    )));
     app.set_beatmap_results(slint::ModelRc::new(Rc::new(slint::VecModel::default())));
     app.set_path_warning(SharedString::from(
         initial_warning.clone().unwrap_or_default(),
@@ -277,6 +596,20 @@ fn main() -> anyhow::Result<()> {
                 "Auto-import inicia desligado; clique em Importar ja ou ligue o toggle".into(),
             ));
         }
+        if let Some(songs) = detected_songs {
+            let _ = ui_tx.send(UiMsg::Log(
+                LogLevel::Info,
+                format!("Pasta Songs detectada na Steam: {}", songs.display()),
+            ));
+        }
+        if let Some(warn) = path_utils::protected_songs_warning(&config.songs_dir) {
+            let _ = ui_tx.send(UiMsg::Log(LogLevel::Warn, warn));
+        }
+        if let Some(warn) =
+            path_utils::cloud_sync_warning(&config.songs_dir, &config.cloud_sync_markers)
+        {
+            let _ = ui_tx.send(UiMsg::Log(LogLevel::Warn, warn));
+        }
     }
 
     app.on_pick_download({
@@ -302,19 +635,102 @@ fn main() -> anyhow::Result<()> {
         let tx = cmd_tx.clone();
         move || {
             if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                let mut cfg = load_config();
-                if let Err(msg) = validate_songs_choice(&cfg.downloads_dir, &path) {
-                    rfd::MessageDialog::new()
-                        .set_title("Caminho inseguro")
-                        .set_description(&msg)
-                        .set_buttons(rfd::MessageButtons::Ok)
-                        .show();
+                choose_songs_dir(path, &tx);
+            }
+        }
+    });
+    app.on_add_songs_dir({
+        let tx = cmd_tx.clone();
+        move || {
+            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                add_songs_dir(path, &tx);
+            }
+        }
+    });
+    app.on_set_songs_target({
+        let tx = cmd_tx.clone();
+        move |id, dir| {
+            let _ = tx.send(CommandMsg::SetSongsTarget(id as u64, PathBuf::from(dir.as_str())));
+        }
+    });
+    app.on_export_settings({
+        let ui_tx = ui_tx.clone();
+        move || {
+            let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON", &["json"])
+                .set_file_name("mcosu-importer-config.json")
+                .save_file()
+            else {
+                return;
+            };
+            let include_paths = rfd::MessageDialog::new()
+                .set_title("Exportar config")
+                .set_description("Incluir as pastas Downloads e Songs? Escolha Nao para compartilhar com outra maquina.")
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                == rfd::MessageDialogResult::Yes;
+            let result = cache::export_config_profile(&load_config(), include_paths)
+                .and_then(|json| fs::write(&path, json).map_err(Into::into));
+            let msg = match result {
+                Ok(()) => (LogLevel::Info, format!("Config exportada para {}", path.display())),
+                Err(err) => (LogLevel::Error, format!("Falha ao exportar config: {err:#}")),
+            };
+            let _ = ui_tx.send(UiMsg::Log(msg.0, msg.1));
+        }
+    });
+    app.on_import_settings({
+        let tx = cmd_tx.clone();
+        let ui_tx = ui_tx.clone();
+        move || {
+            let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+                return;
+            };
+            let current = load_config();
+            let imported = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| cache::merge_config_profile(&current, &text));
+            let mut cfg = match imported {
+                Ok(cfg) => cfg,
+                Err(err) => {
+                    let _ = ui_tx.send(UiMsg::Log(
+                        LogLevel::Error,
+                        format!("Falha ao importar config: {err:#}"),
+                    ));
                     return;
                 }
-                cfg.songs_dir = path.clone();
-                let _ = save_config(&cfg);
-                let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+            };
+            let paths_changed = cfg.downloads_dir != current.downloads_dir
+                || cfg.songs_dir != current.songs_dir
+                || cfg.songs_dirs != current.songs_dirs;
+            let path_check = if paths_changed {
+                validate_imported_paths(&cfg)
+            } else {
+                Ok(())
+            };
+            if let Err(msg) = path_check {
+                rfd::MessageDialog::new()
+                    .set_title("Importar config")
+                    .set_description(&format!("{msg} As pastas atuais foram mantidas."))
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .show();
+                cfg.downloads_dir = current.downloads_dir;
+                cfg.songs_dir = current.songs_dir;
+                cfg.songs_dirs = current.songs_dirs;
             }
+            cfg.songs_dirs = cfg.songs_destinations();
+            let _ = save_config(&cfg);
+            let _ = ui_tx.send(UiMsg::Log(
+                LogLevel::Info,
+                format!("Config importada de {}", path.display()),
+            ));
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
+    app.on_use_songs_suggestion({
+        let tx = cmd_tx.clone();
+        move |path| {
+            choose_songs_dir(PathBuf::from(path.as_str()), &tx);
         }
     });
     app.on_toggle_auto({
@@ -326,12 +742,75 @@ fn main() -> anyhow::Result<()> {
             let _ = tx.send(CommandMsg::UpdateConfig(cfg));
         }
     });
+    app.on_pause_auto_import({
+        let tx = cmd_tx.clone();
+        move |paused| {
+            let _ = tx.send(CommandMsg::PauseAutoImport(paused));
+        }
+    });
+    app.on_toggle_notifications({
+        let tx = cmd_tx.clone();
+        move |state| {
+            let mut cfg = load_config();
+            cfg.desktop_notifications = state;
+            let _ = save_config(&cfg);
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
+    app.on_toggle_minimize_to_tray({
+        let tx = cmd_tx.clone();
+        move |state| {
+            let mut cfg = load_config();
+            cfg.minimize_to_tray = state;
+            let _ = save_config(&cfg);
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
+    app.on_toggle_unicode_metadata({
+        let tx = cmd_tx.clone();
+        move |state| {
+            let mut cfg = load_config();
+            cfg.show_unicode_metadata = state;
+            let _ = save_config(&cfg);
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
+    app.on_toggle_grid_view({
+        let tx = cmd_tx.clone();
+        move |state| {
+            let mut cfg = load_config();
+            cfg.view_mode = if state { ViewMode::Grid } else { ViewMode::List };
+            let _ = save_config(&cfg);
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
+    app.on_set_download_limit({
+        let tx = cmd_tx.clone();
+        move |text| {
+            let mut cfg = load_config();
+            cfg.max_download_kbps = parse_download_limit(&text);
+            let _ = save_config(&cfg);
+            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+        }
+    });
     app.on_import_all({
         let tx = cmd_tx.clone();
         move || {
             let _ = tx.send(CommandMsg::ImportAll);
         }
     });
+    app.on_import_new({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::ImportNew);
+        }
+    });
+    app.on_precache_audio({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::PrecacheAudio);
+        }
+    });
     app.on_clear_completed({
         let tx = cmd_tx.clone();
         move || {
@@ -356,6 +835,27 @@ fn main() -> anyhow::Result<()> {
             let _ = tx.send(CommandMsg::Ignore(id as u64));
         }
     });
+    app.on_undo_import({
+        let tx = cmd_tx.clone();
+        move |id| {
+            let confirm = rfd::MessageDialog::new()
+                .set_title("Desfazer importacao")
+                .set_description(
+                    "Mover a pasta importada para a lixeira? O arquivo de origem continua na fila para importar de novo.",
+                )
+                .set_buttons(rfd::MessageButtons::OkCancel)
+                .show();
+            if confirm == rfd::MessageDialogResult::Ok {
+                let _ = tx.send(CommandMsg::UndoImport(id as u64));
+            }
+        }
+    });
+    app.on_cancel_import({
+        let tx = cmd_tx.clone();
+        move |id| {
+            let _ = tx.send(CommandMsg::CancelImport(id as u64));
+        }
+    });
     app.on_delete_source({
         let tx = cmd_tx.clone();
         move |id| {
@@ -389,16 +889,60 @@ fn main() -> anyhow::Result<()> {
             let _ = tx.send(CommandMsg::OpenBrowser(id as u64));
         }
     });
+    app.on_copy_beatmap_link({
+        let tx = cmd_tx.clone();
+        move |id| {
+            let _ = tx.send(CommandMsg::CopyBeatmapLink(id as u64));
+        }
+    });
+    app.on_open_difficulty_page({
+        let tx = cmd_tx.clone();
+        move |beatmap_id| {
+            let _ = tx.send(CommandMsg::OpenDifficultyPage(beatmap_id));
+        }
+    });
     app.on_search_beatmaps({
         let tx = cmd_tx.clone();
+        let app_weak = app.as_weak();
         move |query| {
-            let _ = tx.send(CommandMsg::SearchBeatmaps(query.to_string()));
+            let filter = app_weak
+                .upgrade()
+                .map(|app| {
+                    SearchFilter::from_inputs(
+                        &app.get_filter_min_difficulties(),
+                        &app.get_filter_min_length(),
+                        &app.get_filter_max_length(),
+                        &app.get_filter_min_bpm(),
+                        &app.get_filter_max_bpm(),
+                    )
+                    .with_mode_choice(app.get_filter_mode_index())
+                })
+                .unwrap_or_default();
+            let _ = tx.send(CommandMsg::SearchBeatmaps(query.to_string(), filter));
+        }
+    });
+    app.on_load_more_beatmaps({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::LoadMoreBeatmaps);
         }
     });
     app.on_download_beatmap({
         let tx = cmd_tx.clone();
+        let shown = search_results_state.clone();
+        let app_weak = app.as_weak();
         move |id| {
-            let _ = tx.send(CommandMsg::DownloadBeatmap(id as u64));
+            let result = shown.lock().ok().and_then(|m| m.get(&(id as u64)).cloned());
+            match result {
+                Some(result) => {
+                    let _ = tx.send(CommandMsg::DownloadBeatmap(result));
+                }
+                None => {
+                    if let Some(app) = app_weak.upgrade() {
+                        app.set_beatmap_status("Beatmap nao encontrado nos resultados.".into());
+                    }
+                }
+            }
         }
     });
     app.on_add_file({
@@ -409,10 +953,12 @@ fn main() -> anyhow::Result<()> {
     });
     app.on_toggle_show_completed({
         let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
         let cfg_state = shared_config.clone();
         let app_ref = app.as_weak();
         move |_state| {
             let entries_state = entries_state.clone();
+            let selection = selection.clone();
             let cfg_state = cfg_state.clone();
             let app_ref = app_ref.clone();
             slint::invoke_from_event_loop(move || {
@@ -422,35 +968,210 @@ fn main() -> anyhow::Result<()> {
                         .ok()
                         .map(|g| g.clone())
                         .unwrap_or_default();
-                    refresh_entries_model(&app, &entries_state, &cfg);
+                    refresh_entries_model(&app, &entries_state, &selection, &cfg);
                 }
             })
             .ok();
         }
     });
-    app.on_toggle_auto_delete({
-        let tx = cmd_tx.clone();
-        move |state| {
-            let _ = tx.send(CommandMsg::ToggleAutoDelete(state));
+    app.on_toggle_selected({
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
+        move |id, checked| {
+            if let Ok(mut set) = selection.lock() {
+                if checked {
+                    set.insert(id as u64);
+                } else {
+                    set.remove(&(id as u64));
+                }
+            }
+            if let Some(app) = app_ref.upgrade() {
+                let cfg = cfg_state.lock().ok().map(|g| g.clone()).unwrap_or_default();
+                refresh_entries_model(&app, &entries_state, &selection, &cfg);
+            }
         }
     });
-    app.on_confirm_auto_delete({
-        let tx = cmd_tx.clone();
-        move |skip| {
-            let _ = tx.send(CommandMsg::ConfirmAutoDelete(skip));
+    app.on_queue_filter_changed({
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
+        move |_text| {
+            let Some(app) = app_ref.upgrade() else {
+                return;
+            };
+            let cfg = cfg_state.lock().ok().map(|g| g.clone()).unwrap_or_default();
+            refresh_entries_model(&app, &entries_state, &selection, &cfg);
         }
     });
-    app.on_cancel_auto_delete_prompt({
-        let tx = cmd_tx.clone();
+    app.on_select_all_visible({
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
         move || {
-            let _ = tx.send(CommandMsg::CancelAutoDeletePrompt);
+            let Some(app) = app_ref.upgrade() else {
+                return;
+            };
+            let visible = app.get_beatmaps();
+            if let Ok(mut set) = selection.lock() {
+                set.extend(
+                    visible
+                        .iter()
+                        .filter(|item| item.can_import)
+                        .map(|item| item.id as u64),
+                );
+            }
+            let cfg = cfg_state.lock().ok().map(|g| g.clone()).unwrap_or_default();
+            refresh_entries_model(&app, &entries_state, &selection, &cfg);
         }
     });
-    app.on_copy_logs({
-        let tx = cmd_tx.clone();
+    app.on_clear_selection({
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
         move || {
-            let _ = tx.send(CommandMsg::CopyLogs);
-        }
+            if let Ok(mut set) = selection.lock() {
+                set.clear();
+            }
+            if let Some(app) = app_ref.upgrade() {
+                let cfg = cfg_state.lock().ok().map(|g| g.clone()).unwrap_or_default();
+                refresh_entries_model(&app, &entries_state, &selection, &cfg);
+            }
+        }
+    });
+    app.on_reimport_all_forced({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::ReimportAllForced);
+        }
+    });
+    app.on_retry_failed({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::RetryFailed);
+        }
+    });
+    app.on_analyze_queue({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::AnalyzeQueue);
+        }
+    });
+    app.on_cancel_bulk_import({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::CancelBulk);
+        }
+    });
+    app.on_import_selected({
+        let tx = cmd_tx.clone();
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
+        move || {
+            let ids = selection
+                .lock()
+                .map(|mut set| set.drain().collect::<Vec<_>>())
+                .unwrap_or_default();
+            if ids.is_empty() {
+                return;
+            }
+            let _ = tx.send(CommandMsg::ImportSelected(ids));
+            if let Some(app) = app_ref.upgrade() {
+                let cfg = cfg_state.lock().ok().map(|g| g.clone()).unwrap_or_default();
+                refresh_entries_model(&app, &entries_state, &selection, &cfg);
+            }
+        }
+    });
+    app.on_toggle_sort_recent({
+        let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
+        let cfg_state = shared_config.clone();
+        let app_ref = app.as_weak();
+        move |_state| {
+            let entries_state = entries_state.clone();
+            let selection = selection.clone();
+            let cfg_state = cfg_state.clone();
+            let app_ref = app_ref.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(app) = app_ref.upgrade() {
+                    let cfg = cfg_state
+                        .lock()
+                        .ok()
+                        .map(|g| g.clone())
+                        .unwrap_or_default();
+                    refresh_entries_model(&app, &entries_state, &selection, &cfg);
+                }
+            })
+            .ok();
+        }
+    });
+    app.on_toggle_auto_delete({
+        let tx = cmd_tx.clone();
+        move |state| {
+            let _ = tx.send(CommandMsg::ToggleAutoDelete(state));
+        }
+    });
+    app.on_confirm_auto_delete({
+        let tx = cmd_tx.clone();
+        move |skip| {
+            let _ = tx.send(CommandMsg::ConfirmAutoDelete(skip));
+        }
+    });
+    app.on_cancel_auto_delete_prompt({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::CancelAutoDeletePrompt);
+        }
+    });
+    app.on_copy_logs({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::CopyLogs);
+        }
+    });
+    app.on_open_data_folder({
+        let tx = cmd_tx.clone();
+        move |which| {
+            let dir = match which.as_str() {
+                "cache" => cache::cache_dir(),
+                "logs" => cache::logs_dir(),
+                _ => cache::base_dir(),
+            };
+            let _ = tx.send(CommandMsg::OpenDataFolder(dir));
+        }
+    });
+    app.on_clear_cache({
+        let tx = cmd_tx.clone();
+        move || {
+            let confirm = rfd::MessageDialog::new()
+                .set_title("Limpar cache")
+                .set_description(
+                    "Apagar miniaturas, audios e previews em cache? Eles sao gerados de novo quando necessario. O indice de duplicados e mantido.",
+                )
+                .set_buttons(rfd::MessageButtons::OkCancel)
+                .show();
+            if confirm == rfd::MessageDialogResult::Ok {
+                let _ = tx.send(CommandMsg::ClearCache);
+            }
+        }
+    });
+    app.on_copy_preview_url({
+        let tx = cmd_tx.clone();
+        move |url| {
+            let _ = tx.send(CommandMsg::CopyPreviewUrl(url.to_string()));
+        }
+    });
+    app.on_restart_preview({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::RestartPreviewServer);
+        }
     });
     app.on_show_error_detail({
         let tx = cmd_tx.clone();
@@ -458,18 +1179,130 @@ fn main() -> anyhow::Result<()> {
             let _ = tx.send(CommandMsg::ShowErrorDetail(id as u64));
         }
     });
+    app.on_show_search_error_detail({
+        let tx = cmd_tx.clone();
+        move || {
+            let _ = tx.send(CommandMsg::ShowSearchErrorDetail);
+        }
+    });
     app.on_preview_audio({
         let tx = cmd_tx.clone();
         move |id| {
             let _ = tx.send(CommandMsg::PreviewAudio(id as u64));
         }
     });
+    app.on_stop_audio({
+        let tx = cmd_tx.clone();
+        move |id| {
+            let _ = tx.send(CommandMsg::StopAudio(id as u64));
+        }
+    });
     app.on_preview_map({
         let tx = cmd_tx.clone();
         move |id| {
             let _ = tx.send(CommandMsg::PreviewMap(id as u64));
         }
     });
+    app.on_clear_preview({
+        let tx = cmd_tx.clone();
+        move |id| {
+            let _ = tx.send(CommandMsg::ClearPreview(id as u64));
+        }
+    });
+    app.on_toggle_difficulty({
+        let tx = cmd_tx.clone();
+        move |id, version, selected| {
+            let _ = tx.send(CommandMsg::SetDifficultySelected(
+                id as u64,
+                version.to_string(),
+                selected,
+            ));
+        }
+    });
+
+    // Tray icon (polled on the UI thread)
+    let tray = match tray::Tray::new(config.auto_import) {
+        Ok(t) => Some(t),
+        Err(err) => {
+            let _ = ui_tx.send(UiMsg::Log(
+                LogLevel::Warn,
+                format!("Icone na bandeja indisponivel: {err:#}"),
+            ));
+            None
+        }
+    };
+    let tray_available = tray.is_some();
+    app.set_tray_available(tray_available);
+    app.window().on_close_requested({
+        let cfg_state = shared_config.clone();
+        let tx = cmd_tx.clone();
+        move || {
+            // Hidden to the tray or quitting, a preview shouldn't keep playing.
+            let _ = tx.send(CommandMsg::StopAllAudio);
+            let minimize = cfg_state
+                .lock()
+                .map(|c| c.minimize_to_tray)
+                .unwrap_or(false);
+            if !(tray_available && minimize) {
+                let _ = slint::quit_event_loop();
+            }
+            slint::CloseRequestResponse::HideWindow
+        }
+    });
+    let tray_timer = slint::Timer::default();
+    if let Some(tray) = tray {
+        let app_ref = app.as_weak();
+        let tx = cmd_tx.clone();
+        let cfg_state = shared_config.clone();
+        tray_timer.start(
+            slint::TimerMode::Repeated,
+            std::time::Duration::from_millis(250),
+            move || {
+                for action in tray.poll() {
+                    match action {
+                        tray::TrayAction::SetAutoImport(state) => {
+                            let mut cfg = load_config();
+                            cfg.auto_import = state;
+                            let _ = save_config(&cfg);
+                            let _ = tx.send(CommandMsg::UpdateConfig(cfg));
+                        }
+                        tray::TrayAction::ShowWindow => {
+                            if let Some(app) = app_ref.upgrade() {
+                                let _ = app.show();
+                            }
+                        }
+                        tray::TrayAction::Quit => {
+                            let _ = slint::quit_event_loop();
+                        }
+                    }
+                }
+                if let Ok(cfg) = cfg_state.lock() {
+                    tray.sync_auto_import(cfg.auto_import);
+                }
+            },
+        );
+    }
+
+    let auto_clear_timer = slint::Timer::default();
+    {
+        let tx = cmd_tx.clone();
+        let cfg_state = shared_config.clone();
+        auto_clear_timer.start(
+            slint::TimerMode::Repeated,
+            AUTO_CLEAR_INTERVAL,
+            move || {
+                let threshold = cfg_state
+                    .lock()
+                    .ok()
+                    .and_then(|c| c.auto_clear_completed_after_secs);
+                if let Some(secs) = threshold {
+                    let _ = tx.send(CommandMsg::AutoClearCompleted(
+                        std::time::Duration::from_secs(secs),
+                    ));
+                }
+            },
+        );
+    }
 
     // Worker thread
     {
@@ -480,16 +1313,51 @@ fn main() -> anyhow::Result<()> {
         let shared_cfg_thread = shared_config.clone();
         let cfg_start = config.clone();
         let guards_thread = guards.clone();
-        let search_map = search_results_state.clone();
         thread::spawn(move || {
             let mut next_id: u64 = 1;
+            // Results of the current search across its pages; ids restart with each new search.
+            let mut search_results: HashMap<u64, BeatmapSearchResult> = HashMap::new();
             let mut next_search_id: u64 = 1;
+            // Bumped by each search so cover fetches for older results stop.
+            let cover_generation = Arc::new(AtomicU64::new(0));
+            let mut last_search_error: Option<String> = None;
+            // Query, filter and page of the last search that reached a mirror.
+            let mut last_search: Option<(String, SearchFilter, u32)> = None;
             let mut cfg = cfg_start;
             let audio_player = AudioPlayer::new();
+            // Preview being watched for its natural end; while set, the loop wakes up
+            // periodically so its "Tocando" badge is cleared without user input.
+            let mut watched_preview: Option<(u64, u64)> = None;
             loop {
-                if let Ok(msg) = cmd_rx.recv() {
+                let received = if watched_preview.is_some() {
+                    cmd_rx.recv_timeout(PREVIEW_POLL_INTERVAL).ok()
+                } else {
+                    cmd_rx.recv().ok()
+                };
+                if let Some((id, generation)) = watched_preview {
+                    match audio_player.poll_playback(id, generation) {
+                        audio::PlaybackPoll::Active => {}
+                        audio::PlaybackPoll::Finished => {
+                            reset_stopped_preview(id, &entries, &ui_sender);
+                            watched_preview = None;
+                        }
+                        audio::PlaybackPoll::Replaced => watched_preview = None,
+                    }
+                }
+                if let Some(msg) = received {
                     match msg {
                         CommandMsg::AddFile(path) => {
+                            // The watcher and the startup scan can both report the same file.
+                            if let Some(existing) = entries
+                                .lock()
+                                .ok()
+                                .and_then(|m| entry_for_source(&m, &path))
+                            {
+                                if existing.status != ImportStatus::Completed {
+                                    let _ = ui_sender.send(UiMsg::Upsert(existing));
+                                }
+                                continue;
+                            }
                             let id = next_id;
                             next_id += 1;
                             let entry = BeatmapEntry {
@@ -505,6 +1373,11 @@ fn main() -> anyhow::Result<()> {
                                 destination: None,
                                 osz_hash: None,
                                 audio: app_state::AudioPreview::default(),
+                                selected_difficulties: None,
+                                completed_at: None,
+                                failure_kind: None,
+                                import_progress: None,
+                                songs_target: None,
                             };
                             if let Ok(mut guard) = entries.lock() {
                                 guard.insert(id, entry.clone());
@@ -517,12 +1390,32 @@ fn main() -> anyhow::Result<()> {
                                 cache_store.clone(),
                                 cfg.clone(),
                                 guards_thread.clone(),
+                                cmd_tx.clone(),
                             );
                         }
+                        CommandMsg::ExpandPack(id) => {
+                            if let Some(entry) =
+                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
+                            {
+                                let entries_clone = entries.clone();
+                                let ui_clone = ui_sender.clone();
+                                let cmd_clone = cmd_tx.clone();
+                                let downloads = cfg.downloads_dir.clone();
+                                thread::spawn(move || {
+                                    expand_pack(entry, &entries_clone, &ui_clone, &cmd_clone, &downloads);
+                                });
+                            }
+                        }
                         CommandMsg::ManualImport(id, force) => {
                             if let Some(entry) =
                                 entries.lock().ok().and_then(|m| m.get(&id).cloned())
                             {
+                                if force
+                                    && cfg.confirm_overwrite_extras
+                                    && !confirm_overwrite_extras(&entry, &cfg, &ui_sender)
+                                {
+                                    continue;
+                                }
                                 spawn_import_only(
                                     entry,
                                     entries.clone(),
@@ -534,33 +1427,133 @@ fn main() -> anyhow::Result<()> {
                                 );
                             }
                         }
-                        CommandMsg::ImportAll => {
+                        CommandMsg::ImportAll | CommandMsg::ImportNew | CommandMsg::ImportSelected(_) => {
+                            let scope = match msg {
+                                CommandMsg::ImportNew => BulkScope::New,
+                                CommandMsg::ImportSelected(ids) => BulkScope::Selected(ids),
+                                _ => BulkScope::Ready,
+                            };
                             spawn_bulk_import(
                                 entries.clone(),
                                 ui_sender.clone(),
                                 cfg.clone(),
                                 cache_store.clone(),
                                 guards_thread.clone(),
+                                scope,
                             );
                         }
-                        CommandMsg::ClearCompleted => {
-                            let mut removed = 0usize;
-                            let mut remaining = Vec::new();
-                            if let Ok(mut guard) = entries.lock() {
-                                guard.retain(|_, e| {
-                                    let done = matches!(
-                                        e.status,
-                                        ImportStatus::Completed | ImportStatus::DuplicateSkipped
-                                    );
-                                    if done {
-                                        removed += 1;
-                                    }
-                                    !done
-                                });
-                                remaining = guard.values().cloned().collect();
-                                remaining.sort_by_key(|e| e.id);
+                        CommandMsg::ReimportAllForced => {
+                            let count = entries
+                                .lock()
+                                .map(|m| m.values().filter(|e| BulkScope::Forced.includes(e)).count())
+                                .unwrap_or(0);
+                            if count == 0 {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Info,
+                                    "Nenhum item concluido, duplicado ou com falha para reimportar.".into(),
+                                ));
+                                continue;
+                            }
+                            let confirm = rfd::MessageDialog::new()
+                                .set_title("Reimportar tudo (forcado)")
+                                .set_level(rfd::MessageLevel::Warning)
+                                .set_description(&format!(
+                                    "{count} item(ns) serao reimportados a forca. As pastas de destino em Songs serao apagadas e extraidas novamente, incluindo arquivos que nao estao no .osz.\n\nContinuar?"
+                                ))
+                                .set_buttons(rfd::MessageButtons::OkCancel)
+                                .show();
+                            if confirm != rfd::MessageDialogResult::Ok {
+                                continue;
+                            }
+                            spawn_bulk_import(
+                                entries.clone(),
+                                ui_sender.clone(),
+                                cfg.clone(),
+                                cache_store.clone(),
+                                guards_thread.clone(),
+                                BulkScope::Forced,
+                            );
+                        }
+                        CommandMsg::RetryFailed => {
+                            let failed = entries
+                                .lock()
+                                .map(|m| {
+                                    m.values()
+                                        .filter(|e| e.status == ImportStatus::Failed)
+                                        .cloned()
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default();
+                            if failed.is_empty() {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Info,
+                                    "Nenhum item com falha para tentar novamente.".into(),
+                                ));
+                                continue;
+                            }
+                            let _ = ui_sender.send(UiMsg::Log(
+                                LogLevel::Info,
+                                format!(
+                                    "Tentando novamente {} falha(s): {}",
+                                    failed.len(),
+                                    failure_breakdown(failed.iter())
+                                ),
+                            ));
+                            let any_with_metadata = failed.iter().any(|e| e.metadata.is_some());
+                            // Entries that never got metadata go through the whole pipeline again.
+                            for entry in failed.into_iter().filter(|e| e.metadata.is_none()) {
+                                spawn_processing(
+                                    entry,
+                                    entries.clone(),
+                                    ui_sender.clone(),
+                                    cache_store.clone(),
+                                    cfg.clone(),
+                                    guards_thread.clone(),
+                                    cmd_tx.clone(),
+                                );
+                            }
+                            if any_with_metadata {
+                                spawn_bulk_import(
+                                    entries.clone(),
+                                    ui_sender.clone(),
+                                    cfg.clone(),
+                                    cache_store.clone(),
+                                    guards_thread.clone(),
+                                    BulkScope::Failed,
+                                );
                             }
-                            let _ = ui_sender.send(UiMsg::ReplaceAll(remaining));
+                        }
+                        CommandMsg::AnalyzeQueue => {
+                            let analysis = entries
+                                .lock()
+                                .map(|m| {
+                                    analyze_queue(
+                                        m.values(),
+                                        |set_id| cache_store.find_set(set_id).is_some(),
+                                        |hash| cache_store.find_hash(hash).is_some(),
+                                    )
+                                })
+                                .unwrap_or_default();
+                            let summary = analysis.summary();
+                            let _ = ui_sender.send(UiMsg::Log(
+                                LogLevel::Info,
+                                format!("Analise da fila: {summary}"),
+                            ));
+                            rfd::MessageDialog::new()
+                                .set_title("Analise da fila")
+                                .set_description(&summary)
+                                .set_buttons(rfd::MessageButtons::Ok)
+                                .show();
+                        }
+                        CommandMsg::CancelBulk => {
+                            guards_thread.cancel_bulk();
+                            let _ = ui_sender.send(UiMsg::Log(
+                                LogLevel::Info,
+                                "Cancelando importacao em lote apos o item atual...".into(),
+                            ));
+                        }
+                        CommandMsg::ClearCompleted => {
+                            let removed = clear_completed(&entries, &ui_sender, None);
                             let _ = ui_sender.send(UiMsg::Log(
                                 LogLevel::Info,
                                 format!(
@@ -569,10 +1562,53 @@ fn main() -> anyhow::Result<()> {
                                 ),
                             ));
                         }
+                        CommandMsg::PrecacheAudio => {
+                            spawn_audio_precache(
+                                entries.clone(),
+                                ui_sender.clone(),
+                                cache_store.clone(),
+                                cfg.clone(),
+                            );
+                        }
+                        CommandMsg::AutoClearCompleted(max_age) => {
+                            let removed = clear_completed(&entries, &ui_sender, Some(max_age));
+                            if removed > 0 {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Info,
+                                    format!(
+                                        "{} item(ns) concluidos removidos automaticamente da fila",
+                                        removed
+                                    ),
+                                ));
+                            }
+                        }
                         CommandMsg::UpdateConfig(new_cfg) => {
                             let requested_auto_import = new_cfg.auto_import;
                             let requested_auto_delete = new_cfg.auto_delete_source;
                             cfg = new_cfg;
+                            for dir in cfg.songs_destinations().iter().skip(1) {
+                                if let Some(warn) = downloads_songs_conflict(&cfg.downloads_dir, dir) {
+                                    let _ = ui_sender.send(UiMsg::Log(
+                                        LogLevel::Warn,
+                                        format!("Destino {} ignorado: {warn}", dir.display()),
+                                    ));
+                                }
+                            }
+                            dir_watcher.set_extensions(&cfg.accepted_extensions);
+                            for dir in dir_watcher.set_dirs(&cfg.watch_dirs()) {
+                                let _ = seed_existing_osz(
+                                    &dir,
+                                    &cfg.accepted_extensions,
+                                    &cmd_tx,
+                                    &cache_store,
+                                );
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Info,
+                                    format!("Observando {}", dir.display()),
+                                ));
+                            }
+                            guards_thread.set_import_limit(cfg.max_parallel_imports);
+                            cache_store.set_media_limit_mb(cfg.media_cache_max_mb);
                             let warning = enforce_path_safety(&mut cfg);
                             let _ = save_config(&cfg);
                             if let Ok(mut guard) = shared_cfg_thread.lock() {
@@ -616,7 +1652,7 @@ fn main() -> anyhow::Result<()> {
                         }
                         CommandMsg::ToggleAutoDelete(state) => {
                             let warning =
-                                downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir);
+                                songs_destinations_conflict(&cfg);
                             if state && warning.is_some() {
                                 let warn_text =
                                     warning.clone().unwrap_or_else(|| "Caminho inseguro".into());
@@ -677,7 +1713,7 @@ fn main() -> anyhow::Result<()> {
                         }
                         CommandMsg::CancelAutoDeletePrompt => {
                             let warning =
-                                downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir);
+                                songs_destinations_conflict(&cfg);
                             let _ = ui_sender.send(UiMsg::HideAutoDeletePrompt);
                             let _ = ui_sender.send(UiMsg::ConfigChanged(
                                 cfg.clone(),
@@ -685,8 +1721,14 @@ fn main() -> anyhow::Result<()> {
                             ));
                         }
                         CommandMsg::AddFileDialog => {
+                            let beatmap_exts: Vec<&str> = cfg
+                                .accepted_extensions
+                                .iter()
+                                .map(|e| e.trim_start_matches('.'))
+                                .collect();
                             if let Some(file) = rfd::FileDialog::new()
-                                .add_filter("Beatmap", &["osz"])
+                                .add_filter("Beatmap", &beatmap_exts)
+                                .add_filter("Pacote de mapas", &["zip"])
                                 .pick_file()
                             {
                                 let _ = ui_sender.send(UiMsg::Log(
@@ -703,13 +1745,59 @@ fn main() -> anyhow::Result<()> {
                                 open_in_explorer(&entry.osz_path);
                             }
                         }
-                        CommandMsg::OpenDestination(id) => {
-                            if let Some(entry) =
-                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
-                            {
-                                if let Some(dest) = entry.destination.clone() {
-                                    open_in_explorer(&dest);
-                                }
+                        CommandMsg::OpenDataFolder(dir) => {
+                            if let Err(err) = app_state::ensure_dir(&dir) {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Error,
+                                    format!("Nao foi possivel abrir {}: {err}", dir.display()),
+                                ));
+                                continue;
+                            }
+                            open_in_explorer(&dir);
+                        }
+                        CommandMsg::ClearCache => {
+                            // A playing preview keeps its cached file open.
+                            if let Some(id) = audio_player.stop_all() {
+                                reset_stopped_preview(id, &entries, &ui_sender);
+                            }
+                            let freed = cache_store.clear_media();
+                            let _ = cache_store.save();
+                            let audio_dir = cache::audio_cache_dir();
+                            let cleared: Vec<BeatmapEntry> = entries
+                                .lock()
+                                .map(|mut m| {
+                                    m.values_mut()
+                                        .filter(|e| {
+                                            e.audio
+                                                .cached_path
+                                                .as_ref()
+                                                .is_some_and(|p| is_within_dir(&audio_dir, p))
+                                        })
+                                        .map(|e| {
+                                            e.audio.cached_path = None;
+                                            e.clone()
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            for entry in cleared {
+                                let _ = ui_sender.send(UiMsg::Upsert(entry));
+                            }
+                            let _ = ui_sender.send(UiMsg::Log(
+                                LogLevel::Info,
+                                format!(
+                                    "Cache limpo: {:.1} MB liberados",
+                                    freed as f64 / 1_048_576.0
+                                ),
+                            ));
+                        }
+                        CommandMsg::OpenDestination(id) => {
+                            if let Some(entry) =
+                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
+                            {
+                                if let Some(dest) = entry.destination.clone() {
+                                    open_in_explorer(&dest);
+                                }
                             }
                         }
                         CommandMsg::OpenBrowser(id) => {
@@ -723,113 +1811,201 @@ fn main() -> anyhow::Result<()> {
                                 }
                             }
                         }
-                        CommandMsg::SearchBeatmaps(query) => {
-                            // --- CONFIGURAÇÃO DO LOG EM ARQUIVO ---
-                            let log_path = "logs/search_log.txt";
-                            let mut log_file = OpenOptions::new()
-                                .create(true) // Cria o arquivo se não existir
-                                .append(true) // Adiciona ao final do arquivo em vez de apagar
-                                .open(log_path)
-                                .expect("Não foi possível abrir o arquivo de log.");
-                            // --- FIM DA CONFIGURAÇÃO ---
-
-                            // Agora, todas as chamadas `println!` e `eprintln!` são substituídas por `writeln!`
-                            writeln!(log_file, "\n--- INICIANDO NOVO CICLO DE BUSCA ---").unwrap();
+                        CommandMsg::CopyBeatmapLink(id) => {
+                            if let Some(set_id) = entries
+                                .lock()
+                                .ok()
+                                .and_then(|m| m.get(&id).and_then(|e| e.metadata.as_ref()?.beatmap_set_id))
+                            {
+                                if let Ok(mut cb) = Clipboard::new() {
+                                    let _ = cb.set_text(beatmap_set_url(set_id));
+                                    let _ = ui_sender
+                                        .send(UiMsg::Log(LogLevel::Info, "Link do beatmap copiado".into()));
+                                }
+                            }
+                        }
+                        CommandMsg::OpenDifficultyPage(beatmap_id) => {
+                            if beatmap_id > 0 {
+                                let _ = open_difficulty_in_browser(beatmap_id);
+                            }
+                        }
+                        cmd @ (CommandMsg::SearchBeatmaps(..) | CommandMsg::LoadMoreBeatmaps) => {
+                            // "Carregar mais" repeats the last search with the next page.
+                            let (query, filter, page) = match cmd {
+                                CommandMsg::SearchBeatmaps(query, filter) => (query, filter, 0),
+                                _ => match last_search.clone() {
+                                    Some((query, filter, page)) => (query, filter, page + 1),
+                                    None => continue,
+                                },
+                            };
+                            let append = page > 0;
+                            let mut log_file = open_search_log();
+
+                            writeln!(log_file, "\n--- INICIANDO NOVO CICLO DE BUSCA ---").ok();
                             
                             let trimmed = query.trim().to_string();
                             if trimmed.is_empty() {
                                 let _ = ui_sender.send(UiMsg::BeatmapSearchState {
                                     loading: false,
                                     message: Some("Digite um termo para buscar beatmaps.".into()),
+                                    has_detail: false,
                                 });
                                 continue;
                             }
                             let _ = ui_sender.send(UiMsg::BeatmapSearchState {
                                 loading: true,
                                 message: None,
+                                has_detail: false,
                             });
+                            last_search_error = None;
 
-                            writeln!(log_file, "[DIAGNÓSTICO] Buscando pelo termo: '{}'", trimmed).unwrap();
-
-                            let mut fetch_error = false;
-                            let found: Vec<BeatmapFound> = match fetch_nerinyan(&trimmed) {
-                                Ok(list) => {
-                                    writeln!(log_file, "[DIAGNÓSTICO] fetch_nerinyan retornou Ok. Número de beatmaps encontrados: {}", list.len()).unwrap();
-                                    list
-                                },
-                                Err(err) => {
-                                    // --- MUDANÇA CRÍTICA ---
-                                    // Agora, em vez de uma mensagem genérica, vamos imprimir a causa raiz detalhada do erro.
-                                    writeln!(log_file, "--- ERRO FATAL NA FUNÇÃO fetch_nerinyan ---").unwrap();
-                                    writeln!(log_file, "A causa raiz do erro foi:").unwrap();
-                                    writeln!(log_file, "{:#?}", err).unwrap(); // Imprime o erro detalhado com formatação.
-                                    // --- FIM DA MUDANÇA ---
-                                    
-                                    fetch_error = true;
-                                    let _ = ui_sender.send(UiMsg::Log(
-                                        LogLevel::Warn,
-                                        format!("Falha na busca Nerinyan: {:?}", err),
-                                    ));
-                                    Vec::new()
+                            writeln!(log_file, "[DIAGNÓSTICO] Buscando pelo termo: '{}' (pagina {})", trimmed, page + 1).ok();
+                            if remember_query(&mut cfg, &trimmed) {
+                                let _ = save_config(&cfg);
+                                if let Ok(mut guard) = shared_cfg_thread.lock() {
+                                    *guard = cfg.clone();
                                 }
-                            };
+                            }
+
+                            // Both mirrors are queried at once; a set both return keeps the
+                            // copy from whichever answered first.
+                            let (result_tx, result_rx) = mpsc::channel();
+                            for source in [BeatmapSource::Nerinyan, BeatmapSource::Catboy] {
+                                let result_tx = result_tx.clone();
+                                let ui_sender = ui_sender.clone();
+                                let query = trimmed.clone();
+                                let user_agent = cfg.user_agent.clone();
+                                thread::spawn(move || {
+                                    let on_rate_limit = |wait| {
+                                        let _ = ui_sender.send(UiMsg::BeatmapSearchState {
+                                            loading: true,
+                                            message: Some(rate_limit::wait_message(wait)),
+                                            has_detail: false,
+                                        });
+                                    };
+                                    let res = match source {
+                                        BeatmapSource::Nerinyan => fetch_nerinyan(&query, page, &user_agent, on_rate_limit),
+                                        BeatmapSource::Catboy => fetch_catboy(&query, page, &user_agent, on_rate_limit),
+                                    };
+                                    let _ = result_tx.send((source, res));
+                                });
+                            }
+                            drop(result_tx);
 
-                            writeln!(log_file, "[DIAGNÓSTICO] Vetor 'found' tem {} itens antes do processamento do Mutex.", found.len()).unwrap();
-
-                            let mut items: Vec<BeatmapSearchResult> = Vec::new();
-                            match search_map.lock() {
-                                Ok(mut map) => {
-                                    writeln!(log_file, "[DIAGNÓSTICO] Mutex lock adquirido com sucesso.").unwrap();
-                                    map.clear();
-                                    for entry in found {
-                                        let id = next_search_id;
-                                        next_search_id += 1;
-                                        let result = BeatmapSearchResult { id, title: entry.title, artist: entry.artist, creator: entry.creator, source: entry.source, download_url: entry.download_url };
-                                        map.insert(id, result.clone());
-                                        items.push(result);
+                            let mut found: Vec<BeatmapFound> = Vec::new();
+                            let mut failures: Vec<(BeatmapSource, String)> = Vec::new();
+                            let mut error_details: Vec<String> = Vec::new();
+                            let mut more_available = false;
+                            for (source, res) in result_rx {
+                                let label = beatmap_source_label(&source);
+                                match res {
+                                    Ok(list) => {
+                                        writeln!(log_file, "[DIAGNÓSTICO] {} retornou Ok. Número de beatmaps encontrados: {}", label, list.len()).ok();
+                                        more_available |= list.len() >= SEARCH_PAGE_SIZE as usize;
+                                        found = merge_search_results(found, list);
                                     }
-                                },
-                                Err(poisoned) => {
-                                    writeln!(log_file, "[DIAGNÓSTICO] Mutex estava envenenado! Tentando recuperar.").unwrap();
-                                    let mut map = poisoned.into_inner();
-                                    map.clear();
-                                    for entry in found {
-                                        let id = next_search_id;
-                                        next_search_id += 1;
-                                        let result = BeatmapSearchResult { id, title: entry.title, artist: entry.artist, creator: entry.creator, source: entry.source, download_url: entry.download_url };
-                                        map.insert(id, result.clone())
-;                    items.push(result);
+                                    Err(err) => {
+                                        writeln!(log_file, "--- ERRO NA BUSCA {} ---", label).ok();
+                                        writeln!(log_file, "A causa raiz do erro foi:").ok();
+                                        writeln!(log_file, "{:#?}", err).ok();
+                                        let _ = ui_sender.send(UiMsg::Log(
+                                            LogLevel::Warn,
+                                            format!("Falha na busca {}: {:?}", label, err),
+                                        ));
+                                        error_details.push(format!("{}: {:#}", label, err));
+                                        failures.push((source, search_error_reason(&err)));
                                     }
                                 }
                             }
+                            if !error_details.is_empty() {
+                                last_search_error = Some(error_details.join("\n\n"));
+                            }
+                            let fetch_error = (!failures.is_empty()).then(|| search_failure_summary(&failures));
+                            let all_failed = failures.len() == 2;
+                            if !all_failed {
+                                last_search = Some((trimmed.clone(), filter.clone(), page));
+                            }
+                            // A failed page can be asked for again.
+                            let more_available = more_available || (append && all_failed);
+
+                            let total_found = found.len();
+                            let found: Vec<BeatmapFound> = found
+                                .into_iter()
+                                .filter(|b| filter.matches(&b.stats))
+                                .collect();
+                            let hidden = total_found - found.len();
+                            if !filter.is_empty() {
+                                writeln!(log_file, "[DIAGNÓSTICO] Filtros {:?} ocultaram {} de {} itens.", filter, hidden, total_found).ok();
+                            }
+
+                            writeln!(log_file, "[DIAGNÓSTICO] Vetor 'found' tem {} itens antes do processamento do Mutex.", found.len()).ok();
+
+                            // Later pages add to the list; a new search starts over.
+                            if !append {
+                                search_results.clear();
+                                next_search_id = 1;
+                            }
+                            let (added, capped) =
+                                store_search_results(&mut search_results, &mut next_search_id, found);
+                            if capped {
+                                writeln!(log_file, "[DIAGNÓSTICO] Limite de {} resultados atingido.", MAX_SEARCH_RESULTS).ok();
+                            }
+                            for result in search_results.values_mut().filter(|r| r.cover_path.is_none()) {
+                                result.cover_path = cache_store.get_thumbnail(&cover_key(result.set_id));
+                            }
+                            let mut items: Vec<BeatmapSearchResult> = search_results.values().cloned().collect();
+                            items.sort_by_key(|r| r.id);
+                            let missing_covers: Vec<u64> = items
+                                .iter()
+                                .filter(|r| r.cover_path.is_none() && r.set_id != 0)
+                                .map(|r| r.set_id)
+                                .collect();
+                            spawn_cover_fetch(
+                                missing_covers,
+                                cfg.user_agent.clone(),
+                                cache_store.clone(),
+                                ui_sender.clone(),
+                                cover_generation.clone(),
+                            );
+                            let _ = ui_sender.send(UiMsg::BeatmapMoreAvailable(more_available && !capped));
 
-                            writeln!(log_file, "[DIAGNÓSTICO] Vetor 'items' tem {} itens após o processamento do Mutex.", items.len()).unwrap();
+                            writeln!(log_file, "[DIAGNÓSTICO] Vetor 'items' tem {} itens após o processamento do Mutex.", items.len()).ok();
 
                             if items.is_empty() {
-                                writeln!(log_file, "[DIAGNÓSTICO] 'items' está vazio. Preparando mensagem de 'sem resultados' ou 'falha'.").unwrap();
-                                let message = if fetch_error { "Falha ao buscar beatmaps na Nerinyan.".into() } else { "Nenhum beatmap encontrado.".into() };
-                                writeln!(log_file, "[DIAGNÓSTICO] Enviando para UI a mensagem: '{}'", message).unwrap();
+                                writeln!(log_file, "[DIAGNÓSTICO] 'items' está vazio. Preparando mensagem de 'sem resultados' ou 'falha'.").ok();
+                                let message = match &fetch_error {
+                                    Some(reason) if all_failed => format!("Falha ao buscar beatmaps ({}).", reason),
+                                    Some(reason) => format!("Nenhum beatmap encontrado ({} indisponivel).", reason),
+                                    None if hidden > 0 => format!("Nenhum dos {} beatmaps passou pelos filtros.", hidden),
+                                    None => "Nenhum beatmap encontrado.".into(),
+                                };
+                                writeln!(log_file, "[DIAGNÓSTICO] Enviando para UI a mensagem: '{}'", message).ok();
                                 let _ = ui_sender.send(UiMsg::BeatmapResults(Vec::new()));
-                                let _ = ui_sender.send(UiMsg::BeatmapSearchState { loading: false, message: Some(message) });
+                                let _ = ui_sender.send(UiMsg::BeatmapSearchState { loading: false, message: Some(message), has_detail: fetch_error.is_some() });
                             } else {
-                                writeln!(log_file, "[DIAGNÓSTICO] 'items' tem resultados. Enviando {} itens para a UI.", items.len()).unwrap();
+                                writeln!(log_file, "[DIAGNÓSTICO] 'items' tem resultados. Enviando {} itens para a UI.", items.len()).ok();
                                 let _ = ui_sender.send(UiMsg::BeatmapResults(items));
-                                let _ = ui_sender.send(UiMsg::BeatmapSearchState { loading: false, message: None });
+                                let mut notes = Vec::new();
+                                if let Some(reason) = &fetch_error {
+                                    notes.push(format!("{} indisponivel; mostrando os demais resultados.", reason));
+                                }
+                                if hidden > 0 {
+                                    notes.push(format!("{} resultado(s) ocultos pelos filtros.", hidden));
+                                }
+                                if capped {
+                                    notes.push(format!("Limite de {} resultados atingido; refine a busca.", MAX_SEARCH_RESULTS));
+                                } else if append && added == 0 {
+                                    notes.push("Nenhum resultado novo nesta pagina.".into());
+                                }
+                                let _ = ui_sender.send(UiMsg::BeatmapSearchState {
+                                    loading: false,
+                                    message: (!notes.is_empty()).then(|| notes.join(" ")),
+                                    has_detail: fetch_error.is_some(),
+                                });
                             }
-                            writeln!(log_file, "--- FIM DO CICLO DE BUSCA ---\n").unwrap();
+                            writeln!(log_file, "--- FIM DO CICLO DE BUSCA ---\n").ok();
                         }
-                        CommandMsg::DownloadBeatmap(search_id) => {
-                            let result_opt = search_map
-                                .lock()
-                                .ok()
-                                .and_then(|m| m.get(&search_id).cloned());
-                            let Some(result) = result_opt else {
-                                let _ = ui_sender.send(UiMsg::BeatmapDownloadStatus {
-                                    active: false,
-                                    text: Some("Beatmap nao encontrado nos resultados.".into()),
-                                });
-                                continue;
-                            };
+                        CommandMsg::DownloadBeatmap(result) => {
                             let status_label = format!(
                                 "Baixando {} - {} ({}) via {}...",
                                 result.artist,
@@ -843,16 +2019,15 @@ fn main() -> anyhow::Result<()> {
                             });
 
                             let downloads_dir = cfg.downloads_dir.clone();
+                            let max_kbps = cfg.max_download_kbps;
+                            let user_agent = cfg.user_agent.clone();
                             let ui_sender_clone = ui_sender.clone();
                             let cmd_tx_clone = cmd_tx.clone();
                             thread::spawn(move || {
                                 let download_name = build_osz_name(&result);
                                 let target = ensure_unique_path(&downloads_dir, &download_name);
                                 let part_path = target.with_extension("osz.part");
-                                let client = reqwest::blocking::Client::builder()
-                                    .user_agent("McOsuImporter/beatmap-search")
-                                    .build();
-                                let client = match client {
+                                let client = match http::client(&user_agent) {
                                     Ok(c) => c,
                                     Err(err) => {
                                         let _ = ui_sender_clone.send(UiMsg::BeatmapDownloadStatus {
@@ -864,79 +2039,121 @@ fn main() -> anyhow::Result<()> {
                                         return;
                                     }
                                 };
-                                let res = download_with_progress(
-                                    &client,
-                                    &result.download_url,
-                                    &part_path,
-                                    &target,
-                                    |done, total| {
-                                        if let Some(total) = total {
-                                            let pct = ((done as f64 / total as f64) * 100.0)
-                                                .clamp(0.0, 100.0);
-                                            let _ = ui_sender_clone.send(
-                                                UiMsg::BeatmapDownloadStatus {
-                                                    active: true,
-                                                    text: Some(format!(
-                                                        "Baixando... {:.0}% ({:.1} / {:.1} MB)",
-                                                        pct,
-                                                        done as f64 / 1_048_576.0,
-                                                        total as f64 / 1_048_576.0
-                                                    )),
-                                                },
-                                            );
-                                        } else {
-                                            let _ = ui_sender_clone.send(
-                                                UiMsg::BeatmapDownloadStatus {
-                                                    active: true,
-                                                    text: Some(format!(
-                                                        "Baixando... {:.1} MB",
-                                                        done as f64 / 1_048_576.0
-                                                    )),
-                                                },
-                                            );
-                                        }
-                                    },
-                                );
-                                match res {
-                                    Ok(_) => {
+                                let on_rate_limit = |wait: std::time::Duration| {
+                                    let _ = ui_sender_clone.send(UiMsg::BeatmapDownloadStatus {
+                                        active: true,
+                                        text: Some(rate_limit::wait_message(wait)),
+                                    });
+                                };
+                                let on_progress = |progress: &DownloadProgress| {
+                                    let speed = format!(
+                                        "{:.1} MB/s",
+                                        progress.bytes_per_sec / 1_048_576.0
+                                    );
+                                    if let Some(total) = progress.total {
+                                        let pct = ((progress.done as f64 / total as f64) * 100.0)
+                                            .clamp(0.0, 100.0);
+                                        let eta = progress
+                                            .eta
+                                            .map(|eta| format!(", ~{} restantes", format_eta(eta)))
+                                            .unwrap_or_default();
                                         let _ = ui_sender_clone.send(
                                             UiMsg::BeatmapDownloadStatus {
-                                                active: false,
-                                                text: Some("Download concluido!".into()),
+                                                active: true,
+                                                text: Some(format!(
+                                                    "Baixando... {:.0}% ({:.1} / {:.1} MB) - {speed}{eta}",
+                                                    pct,
+                                                    progress.done as f64 / 1_048_576.0,
+                                                    total as f64 / 1_048_576.0
+                                                )),
                                             },
                                         );
-                                        let _ = ui_sender_clone.send(UiMsg::Log(
-                                            LogLevel::Info,
-                                            format!(
-                                                "Download concluido: {}",
-                                                target
-                                                    .file_name()
-                                                    .and_then(|s| s.to_str())
-                                                    .unwrap_or_default()
-                                            ),
-                                        ));
-                                        let _ = cmd_tx_clone.send(CommandMsg::AddFile(target));
-                                    }
-                                    Err(err) => {
-                                        eprintln!("Falha no download: {:?}", err);
+                                    } else {
                                         let _ = ui_sender_clone.send(
                                             UiMsg::BeatmapDownloadStatus {
-                                                active: false,
+                                                active: true,
                                                 text: Some(format!(
-                                                    "Falha no download: {:?}",
-                                                    err
+                                                    "Baixando... {:.1} MB - {speed}",
+                                                    progress.done as f64 / 1_048_576.0
                                                 )),
                                             },
                                         );
-                                        let _ = ui_sender_clone.send(UiMsg::Log(
-                                            LogLevel::Error,
-                                            format!(
-                                                "Erro ao baixar {}: {:?}",
-                                                result.download_url,
-                                                err
-                                            ),
-                                        ));
                                     }
+                                };
+                                let mirrors = download_mirrors(&result);
+                                let mut last_err = None;
+                                for (attempt, (label, url)) in mirrors.iter().enumerate() {
+                                    let _ = ui_sender_clone.send(UiMsg::Log(
+                                        LogLevel::Info,
+                                        format!("Tentando download via {label}: {url}"),
+                                    ));
+                                    if attempt > 0 {
+                                        // Another mirror may serve different bytes, so it starts over.
+                                        let _ = fs::remove_file(&part_path);
+                                        let _ = ui_sender_clone.send(UiMsg::BeatmapDownloadStatus {
+                                            active: true,
+                                            text: Some(format!("Tentando outro espelho ({label})...")),
+                                        });
+                                    }
+                                    match download_with_progress(
+                                        &client,
+                                        url,
+                                        &part_path,
+                                        &target,
+                                        max_kbps,
+                                        &on_rate_limit,
+                                        &on_progress,
+                                    ) {
+                                        Ok(check) => {
+                                            let _ = ui_sender_clone.send(
+                                                UiMsg::BeatmapDownloadStatus {
+                                                    active: false,
+                                                    text: Some(format!("Download concluido via {label}!")),
+                                                },
+                                            );
+                                            let (verified, api_mismatch) =
+                                                size_check_note(&check, mirror_expected_size(&result, attempt));
+                                            let _ = ui_sender_clone.send(UiMsg::Log(
+                                                if api_mismatch { LogLevel::Warn } else { LogLevel::Info },
+                                                format!(
+                                                    "Download concluido via {label}: {} ({} bytes, zip valido, {verified})",
+                                                    target
+                                                        .file_name()
+                                                        .and_then(|s| s.to_str())
+                                                        .unwrap_or_default(),
+                                                    check.bytes
+                                                ),
+                                            ));
+                                            let _ = cmd_tx_clone.send(CommandMsg::AddFile(target));
+                                            return;
+                                        }
+                                        Err(err) => {
+                                            let _ = ui_sender_clone.send(UiMsg::Log(
+                                                LogLevel::Warn,
+                                                format!("Falha no download via {label} ({url}): {err:#}"),
+                                            ));
+                                            last_err = Some(err);
+                                        }
+                                    }
+                                }
+                                if let Some(err) = last_err {
+                                    let _ = ui_sender_clone.send(
+                                        UiMsg::BeatmapDownloadStatus {
+                                            active: false,
+                                            text: Some(format!(
+                                                "Falha no download em todos os espelhos: {err:#}"
+                                            )),
+                                        },
+                                    );
+                                    let _ = ui_sender_clone.send(UiMsg::Log(
+                                        LogLevel::Error,
+                                        format!(
+                                            "Erro ao baixar {} ({} espelho(s) tentados): {:#}",
+                                            result.download_url,
+                                            mirrors.len(),
+                                            err
+                                        ),
+                                    ));
                                 }
                             });
                         }
@@ -961,6 +2178,80 @@ fn main() -> anyhow::Result<()> {
                                 );
                             }
                         }
+                        CommandMsg::PauseAutoImport(paused) => {
+                            guards_thread.set_auto_import_paused(paused);
+                            let line = if paused {
+                                "Auto-import pausado: novos mapas ficam na fila"
+                            } else {
+                                "Auto-import retomado"
+                            };
+                            let _ = ui_sender.send(UiMsg::Log(LogLevel::Info, line.into()));
+                        }
+                        CommandMsg::UndoImport(id) => {
+                            let Some(mut entry) =
+                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
+                            else {
+                                continue;
+                            };
+                            let Some(dest) = undo_target(&entry, &cfg) else {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Warn,
+                                    format!(
+                                        "{}: Destino fora das pastas Songs configuradas; nada removido",
+                                        entry.source_file_name()
+                                    ),
+                                ));
+                                continue;
+                            };
+                            // A preview playing from the folder keeps its audio file open.
+                            if entry
+                                .audio
+                                .cached_path
+                                .as_ref()
+                                .is_some_and(|p| is_within_dir(&dest, p))
+                            {
+                                if let Some(stopped) = audio_player.stop_all() {
+                                    reset_stopped_preview(stopped, &entries, &ui_sender);
+                                }
+                                entry.audio.cached_path = None;
+                            }
+                            let name = entry.source_file_name();
+                            if let Err(err) = retry_delete(&name, &ui_sender, || trash::delete(&dest)) {
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    LogLevel::Error,
+                                    format!(
+                                        "{name}: Falha ao mover {} para a lixeira: {err}",
+                                        dest.display()
+                                    ),
+                                ));
+                                continue;
+                            }
+                            cache_store.forget_destination(&dest);
+                            cache_store.unmark_processed(&entry.osz_path);
+                            let _ = cache_store.save();
+                            entry.destination = None;
+                            update_entry(
+                                &mut entry,
+                                &entries,
+                                &ui_sender,
+                                ImportStatus::Detected,
+                                Some("Importacao desfeita: pasta movida para a lixeira".into()),
+                                None,
+                            );
+                        }
+                        CommandMsg::CancelImport(id) => {
+                            let name = entries
+                                .lock()
+                                .ok()
+                                .and_then(|m| m.get(&id).map(|e| e.source_file_name()))
+                                .unwrap_or_default();
+                            let line = if guards_thread.cancel_import(id) {
+                                (LogLevel::Info, format!("{name}: Cancelando importacao..."))
+                            } else {
+                                (LogLevel::Warn, format!("{name}: Nenhuma importacao em andamento"))
+                            };
+                            let _ = ui_sender.send(UiMsg::Log(line.0, line.1));
+                        }
                         CommandMsg::CopyLogs => {
                             if let Ok(logs) = logs_arc.lock() {
                                 let text = logs
@@ -975,6 +2266,13 @@ fn main() -> anyhow::Result<()> {
                                 }
                             }
                         }
+                        CommandMsg::CopyPreviewUrl(url) => {
+                            if let Ok(mut cb) = Clipboard::new() {
+                                let _ = cb.set_text(url);
+                                let _ = ui_sender
+                                    .send(UiMsg::Log(LogLevel::Info, "URL do preview copiada".into()));
+                            }
+                        }
                         CommandMsg::ShowErrorDetail(id) => {
                             if let Some(entry) =
                                 entries.lock().ok().and_then(|m| m.get(&id).cloned())
@@ -991,6 +2289,16 @@ fn main() -> anyhow::Result<()> {
                                     .show();
                             }
                         }
+                        CommandMsg::ShowSearchErrorDetail => {
+                            let detail = last_search_error
+                                .clone()
+                                .unwrap_or_else(|| "Sem detalhes adicionais".into());
+                            rfd::MessageDialog::new()
+                                .set_title("Detalhes do erro de busca")
+                                .set_description(&detail)
+                                .set_buttons(rfd::MessageButtons::Ok)
+                                .show();
+                        }
                         CommandMsg::PreviewAudio(id) => {
                             if let Some(entry) =
                                 entries.lock().ok().and_then(|m| m.get(&id).cloned())
@@ -1004,7 +2312,27 @@ fn main() -> anyhow::Result<()> {
                                     ui_clone,
                                     cache_clone,
                                     audio_player.clone(),
+                                    cfg.normalize_preview_volume,
                                 );
+                                watched_preview = audio_player.current_playback();
+                            }
+                        }
+                        CommandMsg::StopAudio(id) => {
+                            let status = audio_player.stop(id);
+                            if let Some(mut entry) =
+                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
+                            {
+                                if matches!(
+                                    entry.audio.status,
+                                    AudioPreviewStatus::Playing | AudioPreviewStatus::Paused
+                                ) {
+                                    update_audio_state(&mut entry, &entries, &ui_sender, status, None, None);
+                                }
+                            }
+                        }
+                        CommandMsg::StopAllAudio => {
+                            if let Some(id) = audio_player.stop_all() {
+                                reset_stopped_preview(id, &entries, &ui_sender);
                             }
                         }
                         CommandMsg::PreviewMap(id) => {
@@ -1026,6 +2354,74 @@ fn main() -> anyhow::Result<()> {
                                 });
                             }
                         }
+                        CommandMsg::ClearPreview(id) => {
+                            if let Some(entry) =
+                                entries.lock().ok().and_then(|m| m.get(&id).cloned())
+                            {
+                                let outcome = match entry.osz_hash.as_deref() {
+                                    Some(hash) => cache::remove_preview(hash),
+                                    None => Ok(false),
+                                };
+                                let (level, text) = match outcome {
+                                    Ok(true) => (LogLevel::Info, "preview removido do cache".to_string()),
+                                    Ok(false) => (LogLevel::Info, "nenhum preview em cache".to_string()),
+                                    Err(err) => (LogLevel::Warn, format!("falha ao limpar preview ({err:#})")),
+                                };
+                                let _ = ui_sender.send(UiMsg::Log(
+                                    level,
+                                    format!("{}: {text}", entry.source_file_name()),
+                                ));
+                            }
+                        }
+                        CommandMsg::RestartPreviewServer => {
+                            let ui_clone = ui_sender.clone();
+                            let cfg_clone = cfg.clone();
+                            thread::spawn(move || restart_preview_server(&ui_clone, &cfg_clone));
+                        }
+                        CommandMsg::SetSongsTarget(id, dir) => {
+                            let default = path_utils::same_file(&dir, &cfg.songs_dir);
+                            let updated = entries.lock().ok().and_then(|mut m| {
+                                let entry = m.get_mut(&id)?;
+                                entry.songs_target = (!default).then_some(dir);
+                                Some(entry.clone())
+                            });
+                            if let Some(entry) = updated {
+                                let _ = ui_sender.send(UiMsg::Upsert(entry));
+                            }
+                        }
+                        CommandMsg::SetDifficultySelected(id, version, selected) => {
+                            let mut outcome = None;
+                            if let Ok(mut guard) = entries.lock() {
+                                if let Some(entry) = guard.get_mut(&id) {
+                                    let all = entry
+                                        .metadata
+                                        .as_ref()
+                                        .map(|m| m.difficulties.clone())
+                                        .unwrap_or_default();
+                                    match apply_difficulty_selection(
+                                        entry.selected_difficulties.as_deref(),
+                                        &all,
+                                        &version,
+                                        selected,
+                                    ) {
+                                        Ok(selection) => {
+                                            entry.selected_difficulties = selection;
+                                            outcome = Some((entry.clone(), None));
+                                        }
+                                        Err(msg) => outcome = Some((entry.clone(), Some(msg))),
+                                    }
+                                }
+                            }
+                            if let Some((entry, warning)) = outcome {
+                                if let Some(msg) = warning {
+                                    let _ = ui_sender.send(UiMsg::Log(
+                                        LogLevel::Warn,
+                                        format!("{}: {msg}", entry.source_file_name()),
+                                    ));
+                                }
+                                let _ = ui_sender.send(UiMsg::Upsert(entry));
+                            }
+                        }
                     }
                 }
             }
@@ -1035,7 +2431,9 @@ fn main() -> anyhow::Result<()> {
     // UI update thread (receives UiMsg and applies in UI thread)
     {
         let entries_state = ui_state_entries.clone();
+        let selection = queue_selection.clone();
         let logs_state = log_state.clone();
+        let search_shown = search_results_state.clone();
         let app_weak = app.as_weak();
         let config_state = shared_config.clone();
         thread::spawn(move || {
@@ -1043,6 +2441,7 @@ fn main() -> anyhow::Result<()> {
                 match msg {
                     UiMsg::Upsert(entry) => {
                         let entries_state = entries_state.clone();
+                        let selection = selection.clone();
                         let app_ref = app_weak.clone();
                         let cfg_state = config_state.clone();
                         slint::invoke_from_event_loop(move || {
@@ -1061,13 +2460,14 @@ fn main() -> anyhow::Result<()> {
                                     .ok()
                                     .map(|g| g.clone())
                                     .unwrap_or_default();
-                                refresh_entries_model(&app, &entries_state, &cfg);
+                                refresh_entries_model(&app, &entries_state, &selection, &cfg);
                             }
                         })
                         .ok();
                     }
                     UiMsg::ReplaceAll(list) => {
                         let entries_state = entries_state.clone();
+                        let selection = selection.clone();
                         let app_ref = app_weak.clone();
                         let cfg_state = config_state.clone();
                         slint::invoke_from_event_loop(move || {
@@ -1082,7 +2482,7 @@ fn main() -> anyhow::Result<()> {
                                     .ok()
                                     .map(|g| g.clone())
                                     .unwrap_or_default();
-                                refresh_entries_model(&app, &entries_state, &cfg);
+                                refresh_entries_model(&app, &entries_state, &selection, &cfg);
                             }
                         })
                         .ok();
@@ -1109,11 +2509,12 @@ fn main() -> anyhow::Result<()> {
                         })
                         .ok();
                     }
-                    UiMsg::BeatmapSearchState { loading, message } => {
+                    UiMsg::BeatmapSearchState { loading, message, has_detail } => {
                         let app_ref = app_weak.clone();
                         slint::invoke_from_event_loop(move || {
                             if let Some(app) = app_ref.upgrade() {
                                 app.set_beatmap_loading(loading);
+                                app.set_beatmap_error_detail(has_detail);
                                 app.set_beatmap_message(SharedString::from(
                                     message.unwrap_or_default(),
                                 ));
@@ -1123,7 +2524,11 @@ fn main() -> anyhow::Result<()> {
                     }
                     UiMsg::BeatmapResults(list) => {
                         let app_ref = app_weak.clone();
+                        let shown = search_shown.clone();
                         slint::invoke_from_event_loop(move || {
+                            if let Ok(mut map) = shown.lock() {
+                                *map = list.iter().map(|r| (r.id, r.clone())).collect();
+                            }
                             if let Some(app) = app_ref.upgrade() {
                                 let items = list
                                     .iter()
@@ -1131,6 +2536,53 @@ fn main() -> anyhow::Result<()> {
                                     .collect::<Vec<_>>();
                                 let model = Rc::new(slint::VecModel::from(items));
                                 app.set_beatmap_results(model.into());
+                                app.set_beatmap_source_counts(SharedString::from(
+                                    search_source_counts(&list),
+                                ));
+                            }
+                        })
+                        .ok();
+                    }
+                    UiMsg::BeatmapMoreAvailable(more) => {
+                        let app_ref = app_weak.clone();
+                        slint::invoke_from_event_loop(move || {
+                            if let Some(app) = app_ref.upgrade() {
+                                app.set_beatmap_has_more(more);
+                            }
+                        })
+                        .ok();
+                    }
+                    UiMsg::SearchCover(set_id, path) => {
+                        let app_ref = app_weak.clone();
+                        let shown = search_shown.clone();
+                        slint::invoke_from_event_loop(move || {
+                            let ids: HashSet<u64> = shown
+                                .lock()
+                                .map(|mut map| {
+                                    map.values_mut()
+                                        .filter(|r| r.set_id == set_id)
+                                        .map(|r| {
+                                            r.cover_path = Some(path.clone());
+                                            r.id
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            let Some(app) = app_ref.upgrade() else {
+                                return;
+                            };
+                            let Ok(cover) = slint::Image::load_from_path(&path) else {
+                                return;
+                            };
+                            let model = app.get_beatmap_results();
+                            for row in 0..model.row_count() {
+                                if let Some(mut item) = model.row_data(row) {
+                                    if ids.contains(&(item.id as u64)) {
+                                        item.cover = cover.clone();
+                                        item.has_cover = true;
+                                        model.set_row_data(row, item);
+                                    }
+                                }
                             }
                         })
                         .ok();
@@ -1149,6 +2601,7 @@ fn main() -> anyhow::Result<()> {
                     }
                     UiMsg::ConfigChanged(cfg, warning) => {
                         let entries_state = entries_state.clone();
+                        let selection = selection.clone();
                         let app_ref = app_weak.clone();
                         let cfg_state = config_state.clone();
                         slint::invoke_from_event_loop(move || {
@@ -1162,8 +2615,14 @@ fn main() -> anyhow::Result<()> {
                                 app.set_songs_path(SharedString::from(
                                     cfg.songs_dir.display().to_string(),
                                 ));
+                                app.set_songs_choices(songs_choices_model(&cfg));
                                 app.set_auto_import(cfg.auto_import);
                                 app.set_auto_delete_after_import(cfg.auto_delete_source);
+                                app.set_desktop_notifications(cfg.desktop_notifications);
+                                app.set_minimize_to_tray(cfg.minimize_to_tray);
+                                app.set_grid_view(cfg.view_mode == ViewMode::Grid);
+                                app.set_unicode_metadata(cfg.show_unicode_metadata);
+                                app.set_download_limit(download_limit_text(cfg.max_download_kbps));
                                 app.set_paths_blocked(warning.is_some());
                                 app.set_path_warning(SharedString::from(
                                     warning.clone().unwrap_or_default(),
@@ -1171,7 +2630,7 @@ fn main() -> anyhow::Result<()> {
                                 if let Ok(vec) = entries_state.lock() {
                                     drop(vec);
                                 }
-                                refresh_entries_model(&app, &entries_state, &cfg);
+                                refresh_entries_model(&app, &entries_state, &selection, &cfg);
                             }
                         })
                         .ok();
@@ -1204,12 +2663,33 @@ fn main() -> anyhow::Result<()> {
                         })
                         .ok();
                     }
+                    UiMsg::BulkProgress { done, total } => {
+                        let app_ref = app_weak.clone();
+                        slint::invoke_from_event_loop(move || {
+                            if let Some(app) = app_ref.upgrade() {
+                                app.set_bulk_done(done as i32);
+                                app.set_bulk_total(total as i32);
+                            }
+                        })
+                        .ok();
+                    }
+                    UiMsg::PreviewUrl(url) => {
+                        let app_ref = app_weak.clone();
+                        slint::invoke_from_event_loop(move || {
+                            if let Some(app) = app_ref.upgrade() {
+                                app.set_preview_url(SharedString::from(url));
+                            }
+                        })
+                        .ok();
+                    }
                 }
             }
         });
     }
 
-    app.run()?;
+    app.show()?;
+    slint::run_event_loop_until_quit()?;
+    preview::shutdown_server();
     Ok(())
 }
 
@@ -1220,27 +2700,71 @@ fn spawn_processing(
     cache_store: Arc<CacheStore>,
     cfg: AppConfig,
     guards: Arc<ImportGuards>,
+    cmd_sender: mpsc::Sender<CommandMsg>,
 ) {
     thread::spawn(move || {
-        update_entry(
-            &mut entry,
-            &entries,
-            &ui_sender,
-            ImportStatus::WaitingStable,
-            None,
-            None,
-        );
-        if !watcher::is_file_stable(&entry.osz_path, &cfg.stability) {
+        let file_name = entry.source_file_name();
+        if let Some(pattern) = app_state::find_ignore_match(&cfg.ignore_patterns, &[file_name.as_str()]) {
             update_entry(
                 &mut entry,
                 &entries,
                 &ui_sender,
-                ImportStatus::Failed,
-                Some("Arquivo nao estabilizou".into()),
-                Some("O arquivo nao ficou estavel dentro do tempo limite".into()),
+                ImportStatus::DuplicateSkipped,
+                Some(format!("Ignorado (regra: {pattern})")),
+                None,
             );
             return;
         }
+        // Files that have not been touched for a while (e.g. seeded at startup) skip the stability wait.
+        let settled_after = std::time::Duration::from_secs(cfg.stability.settled_after_secs);
+        if !watcher::is_settled(&entry.osz_path, settled_after) {
+            update_entry(
+                &mut entry,
+                &entries,
+                &ui_sender,
+                ImportStatus::WaitingStable,
+                None,
+                None,
+            );
+            if !watcher::is_file_stable(&entry.osz_path, &cfg.stability) {
+                update_entry(
+                    &mut entry,
+                    &entries,
+                    &ui_sender,
+                    ImportStatus::Failed,
+                    Some("Arquivo nao estabilizou".into()),
+                    Some("O arquivo nao ficou estavel dentro do tempo limite".into()),
+                );
+                return;
+            }
+        }
+        // Extra guard for cloud-sync/browser writers that pause long enough to look stable.
+        let min_age = std::time::Duration::from_secs(cfg.min_file_age_secs);
+        if !min_age.is_zero() {
+            update_entry(
+                &mut entry,
+                &entries,
+                &ui_sender,
+                ImportStatus::WaitingStable,
+                Some(format!("Aguardando idade minima ({}s)", cfg.min_file_age_secs)),
+                None,
+            );
+            let timeout = std::time::Duration::from_secs(cfg.stability.timeout_secs).max(min_age * 2);
+            if !watcher::wait_for_min_age(&entry.osz_path, min_age, timeout) {
+                update_entry(
+                    &mut entry,
+                    &entries,
+                    &ui_sender,
+                    ImportStatus::Failed,
+                    Some("Arquivo ainda em gravacao".into()),
+                    Some(format!(
+                        "O arquivo continuou sendo modificado e nao atingiu {}s sem alteracoes",
+                        cfg.min_file_age_secs
+                    )),
+                );
+                return;
+            }
+        }
         update_entry(
             &mut entry,
             &entries,
@@ -1250,28 +2774,98 @@ fn spawn_processing(
             None,
         );
 
-        match osz_reader::read_osz_metadata(&entry.osz_path, &cache_store) {
+        let metadata = {
+            let _slot = guards.metadata_slot();
+            osz_reader::read_osz_metadata(
+                &entry.osz_path,
+                &cache_store,
+                cfg.strict_archive_validation,
+            )
+        };
+        match metadata {
             Ok(meta) => {
                 entry.metadata = Some(meta.metadata.clone());
                 entry.thumbnail_path = meta.thumbnail_path.clone();
                 entry.osz_hash = Some(meta.hash.clone());
-                // duplicate detection
-                if let Some(set_id) = meta.metadata.beatmap_set_id {
-                    if let Some(dest) = cache_store.find_set(set_id) {
-                        entry.destination = Some(dest.clone());
-                        update_entry(
-                            &mut entry,
-                            &entries,
-                            &ui_sender,
-                            ImportStatus::DuplicateSkipped,
-                            Some("Duplicado (BeatmapSetID)".into()),
+                let display = meta.metadata.display_title(false);
+                let display_unicode = meta.metadata.display_title(true);
+                if let Some(pattern) = app_state::find_ignore_match(
+                    &cfg.ignore_patterns,
+                    &[
+                        display.as_str(),
+                        display_unicode.as_str(),
+                        meta.metadata.title.as_str(),
+                        meta.metadata.artist.as_str(),
+                        meta.metadata.creator.as_str(),
+                    ],
+                ) {
+                    update_entry(
+                        &mut entry,
+                        &entries,
+                        &ui_sender,
+                        ImportStatus::DuplicateSkipped,
+                        Some(format!("Ignorado (regra: {pattern})")),
+                        None,
+                    );
+                    return;
+                }
+                if meta.metadata.difficulties.is_empty() {
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Warn,
+                        format!(
+                            "{}: nenhum .osu valido; importando pelo nome do arquivo (validacao relaxada)",
+                            entry.source_file_name()
+                        ),
+                    ));
+                }
+                if meta.metadata.audio_missing() {
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Warn,
+                        format!(
+                            "{}: audio referenciado ({}) nao existe no .osz",
+                            entry.source_file_name(),
+                            meta.metadata.audio_file.as_deref().unwrap_or_default()
+                        ),
+                    ));
+                }
+                if let Some(version) = meta
+                    .metadata
+                    .oldest_format_version()
+                    .filter(|v| *v < osu_parser::LEGACY_FORMAT_BELOW)
+                {
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Warn,
+                        format!(
+                            "{}: formato .osu antigo (v{version}); pode ser exibido incorretamente no McOsu",
+                            entry.source_file_name()
+                        ),
+                    ));
+                }
+                // duplicate detection
+                // Only destinations inside a configured Songs folder count.
+                let known = |dest: Option<PathBuf>| {
+                    dest.filter(|d| in_songs_destinations(&cfg, d))
+                };
+                if let Some(set_id) = meta.metadata.beatmap_set_id {
+                    if let Some(dest) = known(cache_store.find_set(set_id)) {
+                        entry.destination = Some(dest.clone());
+                        cache_store.mark_processed(&entry.osz_path);
+                        let _ = cache_store.save();
+                        update_entry(
+                            &mut entry,
+                            &entries,
+                            &ui_sender,
+                            ImportStatus::DuplicateSkipped,
+                            Some("Duplicado (BeatmapSetID)".into()),
                             None,
                         );
                         return;
                     }
                 }
-                if let Some(dest) = cache_store.find_hash(&meta.hash) {
+                if let Some(dest) = known(cache_store.find_hash(&meta.hash)) {
                     entry.destination = Some(dest.clone());
+                    cache_store.mark_processed(&entry.osz_path);
+                    let _ = cache_store.save();
                     update_entry(
                         &mut entry,
                         &entries,
@@ -1282,6 +2876,28 @@ fn spawn_processing(
                     );
                     return;
                 }
+                // Same map from another mirror: different bytes, same folder name.
+                let by_name = cfg.songs_destinations().iter().find_map(|songs| {
+                    importer::find_folder_by_name(&meta.metadata, &entry.osz_path, songs)
+                });
+                if let Some(dest) = by_name {
+                    if let Some(set_id) = meta.metadata.beatmap_set_id {
+                        cache_store.register_beatmap_set(set_id, dest.clone());
+                    }
+                    cache_store.register_hash(meta.hash.clone(), dest.clone());
+                    entry.destination = Some(dest);
+                    cache_store.mark_processed(&entry.osz_path);
+                    let _ = cache_store.save();
+                    update_entry(
+                        &mut entry,
+                        &entries,
+                        &ui_sender,
+                        ImportStatus::DuplicateSkipped,
+                        Some("Duplicado (pasta com mesmo nome)".into()),
+                        None,
+                    );
+                    return;
+                }
                 let hash_short: String = meta.hash.chars().take(8).collect();
                 update_entry(
                     &mut entry,
@@ -1292,7 +2908,20 @@ fn spawn_processing(
                     None,
                 );
             }
+            Err(err) if err.downcast_ref::<osz_reader::BeatmapPack>().is_some() => {
+                update_entry(
+                    &mut entry,
+                    &entries,
+                    &ui_sender,
+                    ImportStatus::ReadingMetadata,
+                    Some(format!("Pacote detectado: {err}")),
+                    None,
+                );
+                let _ = cmd_sender.send(CommandMsg::ExpandPack(entry.id));
+                return;
+            }
             Err(err) => {
+                entry.failure_kind = Some(FailureKind::Metadata);
                 update_entry(
                     &mut entry,
                     &entries,
@@ -1305,8 +2934,19 @@ fn spawn_processing(
             }
         }
 
-        if cfg.auto_import
-            && downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir).is_none()
+        if cfg.auto_import && guards.auto_import_paused() {
+            // Stays ready for a manual import once the user has looked at it.
+            let message = entry.message.take().map(|m| format!("{m} - auto-import pausado"));
+            update_entry(
+                &mut entry,
+                &entries,
+                &ui_sender,
+                ImportStatus::ReadingMetadata,
+                message,
+                None,
+            );
+        } else if cfg.auto_import
+            && songs_destinations_conflict(&cfg).is_none()
         {
             perform_import(
                 &mut entry,
@@ -1316,7 +2956,13 @@ fn spawn_processing(
                 &cache_store,
                 &guards,
                 false,
+                ImportPriority::Background,
             );
+            if cfg.desktop_notifications && entry.status == ImportStatus::Completed {
+                if let Some(meta) = entry.metadata.as_ref() {
+                    notifications::notify_imported(meta.display_title(cfg.show_unicode_metadata));
+                }
+            }
         } else if cfg.auto_import {
             let _ = ui_sender.send(UiMsg::Log(
                 LogLevel::Warn,
@@ -1344,10 +2990,62 @@ fn spawn_import_only(
             &cache_store,
             &guards,
             force,
+            ImportPriority::Interactive,
         );
     });
 }
 
+/// Unpacks a map pack into `Downloads/_packs/<name>/` (not watched) and queues each inner `.osz`.
+fn expand_pack(
+    mut entry: BeatmapEntry,
+    entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: &mpsc::Sender<UiMsg>,
+    cmd_sender: &mpsc::Sender<CommandMsg>,
+    downloads: &Path,
+) {
+    let stem = entry
+        .osz_path
+        .file_stem()
+        .map(|s| app_state::sanitize_path_component(&s.to_string_lossy()))
+        .unwrap_or_else(|| format!("pacote-{}", entry.id));
+    let target = downloads.join("_packs").join(stem);
+    match osz_reader::extract_pack(&entry.osz_path, &target) {
+        Ok(files) => {
+            // Expanding the same pack twice reuses its files; don't queue them again.
+            let new_files: Vec<_> = match entries.lock() {
+                Ok(map) => files
+                    .into_iter()
+                    .filter(|file| entry_for_source(&map, file).is_none())
+                    .collect(),
+                Err(_) => files,
+            };
+            let count = new_files.len();
+            for file in new_files {
+                let _ = cmd_sender.send(CommandMsg::AddFile(file));
+            }
+            update_entry(
+                &mut entry,
+                entries,
+                ui_sender,
+                ImportStatus::Completed,
+                Some(format!("Pacote expandido: {count} mapa(s) adicionados a fila")),
+                None,
+            );
+        }
+        Err(err) => {
+            update_entry(
+                &mut entry,
+                entries,
+                ui_sender,
+                ImportStatus::Failed,
+                Some("Falha ao expandir pacote".into()),
+                Some(format!("{err:#}")),
+            );
+        }
+    }
+}
+
+/// Entries "Importar ja" picks up, including earlier failures that can be retried.
 fn is_ready_for_import(entry: &BeatmapEntry) -> bool {
     if entry.metadata.is_none() {
         return false;
@@ -1357,19 +3055,146 @@ fn is_ready_for_import(entry: &BeatmapEntry) -> bool {
         ImportStatus::Completed
             | ImportStatus::Importing
             | ImportStatus::DuplicateSkipped
-            | ImportStatus::Failed
+            | ImportStatus::Cancelled
     )
 }
 
+/// Never-attempted entries only; failures stay in the queue for inspection.
+fn is_new_for_import(entry: &BeatmapEntry) -> bool {
+    entry.metadata.is_some()
+        && matches!(
+            entry.status,
+            ImportStatus::Detected | ImportStatus::ReadingMetadata
+        )
+}
+
+/// Failed entries grouped by cause, most frequent first: "2 de permissao, 1 de extracao".
+fn failure_breakdown<'a>(entries: impl IntoIterator<Item = &'a BeatmapEntry>) -> String {
+    let mut groups = std::collections::BTreeMap::<FailureKind, usize>::new();
+    for entry in entries {
+        if entry.status == ImportStatus::Failed {
+            *groups
+                .entry(entry.failure_kind.unwrap_or(FailureKind::Other))
+                .or_default() += 1;
+        }
+    }
+    let mut groups = groups.into_iter().collect::<Vec<_>>();
+    groups.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    groups
+        .into_iter()
+        .map(|(kind, count)| format!("{count} {}", kind.label()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct QueueCounts {
+    ready: usize,
+    new: usize,
+    failed: usize,
+}
+
+fn queue_counts<'a>(entries: impl IntoIterator<Item = &'a BeatmapEntry>) -> QueueCounts {
+    let mut counts = QueueCounts::default();
+    for entry in entries {
+        if is_ready_for_import(entry) {
+            counts.ready += 1;
+        }
+        if is_new_for_import(entry) {
+            counts.new += 1;
+        }
+        if entry.status == ImportStatus::Failed {
+            counts.failed += 1;
+        }
+    }
+    counts
+}
+
+/// Preflight of a bulk import: how the ready entries would be classified.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct QueueAnalysis {
+    new: usize,
+    duplicate_set: usize,
+    duplicate_hash: usize,
+}
+
+impl QueueAnalysis {
+    fn summary(&self) -> String {
+        let duplicates = self.duplicate_set + self.duplicate_hash;
+        format!(
+            "{} novos, {} duplicados ({} por BeatmapSetID, {} por hash)",
+            self.new, duplicates, self.duplicate_set, self.duplicate_hash
+        )
+    }
+}
+
+/// Runs the same set ID / hash lookups as processing over the ready entries, without importing.
+fn analyze_queue<'a>(
+    entries: impl Iterator<Item = &'a BeatmapEntry>,
+    known_set: impl Fn(i32) -> bool,
+    known_hash: impl Fn(&str) -> bool,
+) -> QueueAnalysis {
+    let mut analysis = QueueAnalysis::default();
+    for entry in entries.filter(|e| is_ready_for_import(e)) {
+        let set_id = entry.metadata.as_ref().and_then(|m| m.beatmap_set_id);
+        if set_id.is_some_and(&known_set) {
+            analysis.duplicate_set += 1;
+        } else if entry.osz_hash.as_deref().is_some_and(&known_hash) {
+            analysis.duplicate_hash += 1;
+        } else {
+            analysis.new += 1;
+        }
+    }
+    analysis
+}
+
+/// Which queue entries a bulk import picks up.
+#[derive(Debug, Clone, PartialEq)]
+enum BulkScope {
+    /// Everything ready, including failures ("Importar ja").
+    Ready,
+    /// Never-attempted entries only ("Importar novos").
+    New,
+    /// Ticked entries that are ready for import.
+    Selected(Vec<u64>),
+    /// Finished or failed entries, reimported with `force` (destination wiped first).
+    Forced,
+    /// Failed entries whose metadata was read ("Tentar novamente falhas").
+    Failed,
+}
+
+impl BulkScope {
+    fn includes(&self, entry: &BeatmapEntry) -> bool {
+        match self {
+            BulkScope::Ready => is_ready_for_import(entry),
+            BulkScope::New => is_new_for_import(entry),
+            BulkScope::Selected(ids) => {
+                ids.contains(&entry.id)
+                    && (is_ready_for_import(entry)
+                        || (entry.metadata.is_some() && entry.status == ImportStatus::Cancelled))
+            }
+            BulkScope::Failed => entry.metadata.is_some() && entry.status == ImportStatus::Failed,
+            BulkScope::Forced => {
+                entry.metadata.is_some()
+                    && matches!(
+                        entry.status,
+                        ImportStatus::DuplicateSkipped | ImportStatus::Completed | ImportStatus::Failed
+                    )
+            }
+        }
+    }
+}
+
 fn spawn_bulk_import(
     entries: Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
     ui_sender: mpsc::Sender<UiMsg>,
     cfg: AppConfig,
     cache_store: Arc<CacheStore>,
     guards: Arc<ImportGuards>,
+    scope: BulkScope,
 ) {
     thread::spawn(move || {
-        if downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir).is_some() {
+        if songs_destinations_conflict(&cfg).is_some() {
             let _ = ui_sender.send(UiMsg::Log(
                 LogLevel::Warn,
                 "Importar ja bloqueado por configuracao insegura de caminhos.".into(),
@@ -1403,7 +3228,7 @@ fn spawn_bulk_import(
             .lock()
             .map(|m| {
                 m.values()
-                    .filter(|e| is_ready_for_import(e))
+                    .filter(|e| scope.includes(e))
                     .cloned()
                     .collect::<Vec<_>>()
             })
@@ -1419,7 +3244,17 @@ fn spawn_bulk_import(
             LogLevel::Info,
             format!("Importando {} item(ns) da fila", ready.len()),
         ));
-        for mut entry in ready {
+        let force = scope == BulkScope::Forced;
+        let total = ready.len();
+        let _ = ui_sender.send(UiMsg::BulkProgress { done: 0, total });
+        for (done, mut entry) in ready.into_iter().enumerate() {
+            if guards.bulk_cancelled() {
+                let _ = ui_sender.send(UiMsg::Log(
+                    LogLevel::Warn,
+                    format!("Importacao em lote cancelada ({done}/{total} processados)"),
+                ));
+                break;
+            }
             perform_import(
                 &mut entry,
                 &entries,
@@ -1427,8 +3262,13 @@ fn spawn_bulk_import(
                 &cfg,
                 &cache_store,
                 &guards,
-                false,
+                force,
+                ImportPriority::Background,
             );
+            let _ = ui_sender.send(UiMsg::BulkProgress {
+                done: done + 1,
+                total,
+            });
         }
     });
 }
@@ -1441,6 +3281,7 @@ fn perform_import(
     cache_store: &Arc<CacheStore>,
     guards: &Arc<ImportGuards>,
     force: bool,
+    priority: ImportPriority,
 ) {
     if !guards.try_lock_entry(entry.id) {
         let _ = ui_sender.send(UiMsg::Log(
@@ -1465,7 +3306,9 @@ fn perform_import(
         guards,
         id: entry.id,
     };
+    let cancel = guards.import_cancel_flag(entry.id).unwrap_or_default();
 
+    // Marked before waiting for a slot so a queued import can already be cancelled.
     update_entry(
         entry,
         entries,
@@ -1474,8 +3317,38 @@ fn perform_import(
         None,
         None,
     );
+    let _slot = guards.import_slot(priority);
+    let songs_dir = songs_target(entry, cfg);
+    if let Some(warn) = downloads_songs_conflict(&cfg.downloads_dir, &songs_dir) {
+        update_entry(
+            entry,
+            entries,
+            ui_sender,
+            ImportStatus::Failed,
+            Some("Destino inseguro".into()),
+            Some(warn),
+        );
+        return;
+    }
     if let Some(meta) = entry.metadata.clone() {
-        match importer::import_osz(entry, &meta, &cfg.songs_dir, force) {
+        let mut progress_view = entry.clone();
+        let on_progress = |done: u64, total: u64| {
+            let next = next_progress_percent(done, total, progress_view.import_progress);
+            if let Some(percent) = next {
+                progress_view.import_progress = Some(percent);
+                let _ = ui_sender.send(UiMsg::Upsert(progress_view.clone()));
+            }
+        };
+        let imported = importer::import_osz(
+            entry,
+            &meta,
+            &songs_dir,
+            force,
+            cfg.skip_video_on_import,
+            &cancel,
+            on_progress,
+        );
+        match imported {
             Ok(res) => {
                 entry.destination = Some(res.destination.clone());
                 let status = if res.duplicated {
@@ -1486,7 +3359,21 @@ fn perform_import(
                 let msg = if res.duplicated {
                     Some("Duplicado - pasta ja existia".into())
                 } else {
-                    Some("Importado".into())
+                    let mut text = match entry.selected_difficulties.as_ref() {
+                        Some(selected) => format!(
+                            "Importado ({}/{} dificuldades)",
+                            selected.len(),
+                            meta.difficulties.len()
+                        ),
+                        None => "Importado".into(),
+                    };
+                    if res.skipped_video_bytes > 0 {
+                        text.push_str(&format!(
+                            " - video ignorado ({:.1} MB)",
+                            res.skipped_video_bytes as f64 / 1_048_576.0
+                        ));
+                    }
+                    Some(text)
                 };
                 if let Some(set_id) = meta.beatmap_set_id {
                     cache_store.register_beatmap_set(set_id, res.destination.clone());
@@ -1494,17 +3381,34 @@ fn perform_import(
                 if let Some(hash) = entry.osz_hash.clone() {
                     cache_store.register_hash(hash, res.destination.clone());
                 }
+                cache_store.mark_processed(&entry.osz_path);
                 let _ = cache_store.save();
                 update_entry(entry, entries, ui_sender, status, msg, None);
                 if matches!(status, ImportStatus::Completed)
-                    && cfg.auto_delete_source
-                    && downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir).is_none()
+                    && songs_destinations_conflict(cfg).is_none()
                 {
-                    maybe_delete_source_after_import(entry, entries, ui_sender, cfg);
+                    if cfg.archive_source_after_import {
+                        archive_source_after_import(entry, entries, ui_sender, cfg);
+                    } else if cfg.mark_source_imported {
+                        mark_source_imported(entry, entries, ui_sender, cfg);
+                    } else if cfg.auto_delete_source {
+                        maybe_delete_source_after_import(entry, entries, ui_sender, cfg);
+                    }
                 }
             }
+            Err(importer::ImportError::Cancelled) => {
+                update_entry(
+                    entry,
+                    entries,
+                    ui_sender,
+                    ImportStatus::Cancelled,
+                    Some("Importacao cancelada".into()),
+                    None,
+                );
+            }
             Err(err) => {
-                let (short, detail) = classify_import_error(&err);
+                let (short, detail) = describe_import_error(&err);
+                entry.failure_kind = Some(err.failure_kind());
                 update_entry(
                     entry,
                     entries,
@@ -1516,17 +3420,152 @@ fn perform_import(
             }
         }
     } else {
+        let (short, detail) = describe_import_error(&importer::ImportError::Metadata);
+        entry.failure_kind = Some(FailureKind::Metadata);
         update_entry(
             entry,
             entries,
             ui_sender,
             ImportStatus::Failed,
-            Some("Sem metadados".into()),
-            Some("Nao foi possivel ler os metadados do arquivo .osz".into()),
+            Some(short),
+            Some(detail),
         );
     }
 }
 
+/// Percentage to report for `done` of `total` files, or `None` while it moved less than
+/// `PROGRESS_STEP` points since `last`, so sets with hundreds of hitsounds don't flood the UI.
+fn next_progress_percent(done: u64, total: u64, last: Option<u8>) -> Option<u8> {
+    const PROGRESS_STEP: u8 = 5;
+    if total == 0 {
+        return None;
+    }
+    let percent = (done.min(total) * 100 / total) as u8;
+    match last {
+        Some(last) if percent < 100 && percent < last.saturating_add(PROGRESS_STEP) => None,
+        Some(last) if percent <= last => None,
+        _ => Some(percent),
+    }
+}
+
+/// Songs folder this entry imports into: its own pick while that is still configured,
+/// otherwise the default.
+fn songs_target(entry: &BeatmapEntry, cfg: &AppConfig) -> PathBuf {
+    entry
+        .songs_target
+        .as_ref()
+        .filter(|target| {
+            cfg.songs_destinations()
+                .iter()
+                .any(|d| path_utils::same_file(d, target))
+        })
+        .cloned()
+        .unwrap_or_else(|| cfg.songs_dir.clone())
+}
+
+fn in_songs_destinations(cfg: &AppConfig, dest: &Path) -> bool {
+    cfg.songs_destinations()
+        .iter()
+        .any(|songs| is_within_dir(songs, dest))
+}
+
+/// Overlap warning for the first Songs destination that conflicts with Downloads.
+fn songs_destinations_conflict(cfg: &AppConfig) -> Option<String> {
+    cfg.songs_destinations()
+        .iter()
+        .find_map(|songs| downloads_songs_conflict(&cfg.downloads_dir, songs))
+}
+
+/// `can_delete_source` against every Songs destination, not only the default one.
+fn can_delete_source_in_all(cfg: &AppConfig, source: &Path) -> bool {
+    cfg.songs_destinations()
+        .iter()
+        .all(|songs| can_delete_source(&cfg.downloads_dir, songs, source))
+}
+
+/// Folder an undo would move to the trash: only for completed imports whose destination sits
+/// inside (never at the root of) a configured Songs folder.
+fn undo_target(entry: &BeatmapEntry, cfg: &AppConfig) -> Option<PathBuf> {
+    if entry.status != ImportStatus::Completed {
+        return None;
+    }
+    let dest = entry.destination.as_ref()?;
+    cfg.songs_destinations()
+        .iter()
+        .any(|songs| is_within_dir(songs, dest) && !is_within_dir(dest, songs))
+        .then(|| dest.clone())
+}
+
+/// Destinations offered by the per-card picker; folders overlapping Downloads are left out.
+fn songs_choices_model(cfg: &AppConfig) -> slint::ModelRc<SharedString> {
+    let choices = cfg
+        .songs_destinations()
+        .into_iter()
+        .filter(|d| downloads_songs_conflict(&cfg.downloads_dir, d).is_none())
+        .map(|d| SharedString::from(d.display().to_string()))
+        .collect::<Vec<_>>();
+    slint::ModelRc::new(Rc::new(slint::VecModel::from(choices)))
+}
+
+/// Asks before a forced reimport wipes files in the destination that the archive won't restore.
+fn confirm_overwrite_extras(
+    entry: &BeatmapEntry,
+    cfg: &AppConfig,
+    ui_sender: &mpsc::Sender<UiMsg>,
+) -> bool {
+    let Some(meta) = entry.metadata.as_ref() else {
+        return true;
+    };
+    let Ok(dest) = importer::resolve_destination(entry, meta, &songs_target(entry, cfg)) else {
+        return true;
+    };
+    if !dest.is_dir() {
+        return true;
+    }
+    let extras = match importer::files_missing_from_archive(&entry.osz_path, &dest) {
+        Ok(list) => list,
+        Err(err) => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Warn,
+                format!(
+                    "{}: nao foi possivel comparar destino com o .osz ({err:#})",
+                    entry.source_file_name()
+                ),
+            ));
+            return true;
+        }
+    };
+    if extras.is_empty() {
+        return true;
+    }
+    let mut listing = extras
+        .iter()
+        .take(10)
+        .map(|p| format!("- {}", p.display()))
+        .collect::<Vec<_>>();
+    if extras.len() > 10 {
+        listing.push(format!("... e mais {}", extras.len() - 10));
+    }
+    let confirm = rfd::MessageDialog::new()
+        .set_title("Confirmar reimportacao")
+        .set_description(&format!(
+            "A pasta {} contem {} arquivo(s) que nao estao no .osz e serao apagados:\n{}\n\nContinuar?",
+            dest.display(),
+            extras.len(),
+            listing.join("\n")
+        ))
+        .set_buttons(rfd::MessageButtons::OkCancel)
+        .show();
+    if confirm == rfd::MessageDialogResult::Ok {
+        return true;
+    }
+    let _ = ui_sender.send(UiMsg::Log(
+        LogLevel::Info,
+        format!("{}: reimportacao cancelada", entry.source_file_name()),
+    ));
+    false
+}
+
 fn handle_delete_source(
     entry: &mut BeatmapEntry,
     entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
@@ -1561,14 +3600,14 @@ fn attempt_delete_source(
     success_msg: &str,
     failure_context: &str,
 ) {
-    if let Some(warn) = downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir) {
+    if let Some(warn) = songs_destinations_conflict(cfg) {
         let _ = ui_sender.send(UiMsg::Log(
             LogLevel::Warn,
             format!("Protecao ativa: {warn}"),
         ));
         return;
     }
-    if !can_delete_source(&cfg.downloads_dir, &cfg.songs_dir, &entry.osz_path) {
+    if !can_delete_source_in_all(cfg, &entry.osz_path) {
         let _ = ui_sender.send(UiMsg::Log(
             LogLevel::Warn,
             format!(
@@ -1590,16 +3629,19 @@ fn attempt_delete_source(
         return;
     }
 
-    let deletion = trash::delete(&entry.osz_path).or_else(|err| {
-        let _ = ui_sender.send(UiMsg::Log(
-            LogLevel::Warn,
-            format!(
-                "{}: Falha ao mover para lixeira ({err}); tentando apagar definitivamente",
-                entry.source_file_name()
-            ),
-        ));
-        fs::remove_file(&entry.osz_path)
-    });
+    let name = entry.source_file_name();
+    let path = entry.osz_path.clone();
+    let deletion = if cfg.permanent_delete {
+        retry_delete(&name, ui_sender, || fs::remove_file(&path))
+    } else {
+        retry_delete(&name, ui_sender, || trash::delete(&path)).or_else(|err| {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Warn,
+                format!("{name}: Falha ao mover para lixeira ({err}); tentando apagar definitivamente"),
+            ));
+            fs::remove_file(&path)
+        })
+    };
     match deletion {
         Ok(_) => {
             update_entry(
@@ -1617,13 +3659,58 @@ fn attempt_delete_source(
                 entries,
                 ui_sender,
                 entry.status,
-                Some(format!("{failure_context}: fonte nao removida")),
+                Some(match delete_failure_reason(&err) {
+                    Some(reason) => format!("{failure_context}: fonte nao removida ({reason})"),
+                    None => format!("{failure_context}: fonte nao removida"),
+                }),
                 Some(err.to_string()),
             );
         }
     }
 }
 
+const AUTO_CLEAR_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// How often a playing preview is checked for having reached the end of the track.
+const PREVIEW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+const DELETE_ATTEMPTS: u32 = 4;
+const DELETE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Retries a delete a few times; antivirus/indexers often hold a fresh file open briefly.
+fn retry_delete<E: std::fmt::Display>(
+    name: &str,
+    ui_sender: &mpsc::Sender<UiMsg>,
+    mut op: impl FnMut() -> Result<(), E>,
+) -> Result<(), E> {
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < DELETE_ATTEMPTS => {
+                let _ = ui_sender.send(UiMsg::Log(
+                    LogLevel::Info,
+                    format!("{name}: tentativa {attempt}/{DELETE_ATTEMPTS} de remocao falhou ({err})"),
+                ));
+                thread::sleep(DELETE_RETRY_DELAY);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn delete_failure_reason(err: &std::io::Error) -> Option<&'static str> {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION on Windows.
+    if cfg!(windows) && matches!(err.raw_os_error(), Some(32) | Some(33)) {
+        return Some("arquivo em uso");
+    }
+    match err.kind() {
+        std::io::ErrorKind::ResourceBusy => Some("arquivo em uso"),
+        std::io::ErrorKind::PermissionDenied => Some("sem permissao"),
+        std::io::ErrorKind::NotFound => Some("arquivo nao encontrado"),
+        _ => None,
+    }
+}
+
 fn maybe_delete_source_after_import(
     entry: &mut BeatmapEntry,
     entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
@@ -1640,75 +3727,265 @@ fn maybe_delete_source_after_import(
     );
 }
 
-fn classify_import_error(err: &anyhow::Error) -> (String, String) {
-    let detail = format!("{err:#}");
-    let err_txt = err.to_string().to_lowercase();
-    let short = if err_txt.contains("zip")
-        || err_txt.contains("archive")
-        || err_txt.contains("unzip")
-    {
-        "Falha ao extrair o .osz"
-    } else if err_txt.contains("create")
-        || err_txt.contains("permiss")
-        || err_txt.contains("acesso")
-        || err_txt.contains("denied")
-    {
-        "Falha ao criar/gravar na pasta destino"
-    } else if err_txt.contains("metadata") || err_txt.contains("metadado") {
-        "Falha ao ler metadados"
+/// Applies a checkbox change to the difficulty selection; `Ok(None)` means every difficulty is selected.
+fn apply_difficulty_selection(
+    current: Option<&[String]>,
+    all: &[String],
+    version: &str,
+    selected: bool,
+) -> Result<Option<Vec<String>>, &'static str> {
+    let mut chosen = current
+        .map(|c| c.to_vec())
+        .unwrap_or_else(|| all.to_vec());
+    chosen.retain(|v| v != version);
+    if selected {
+        chosen.push(version.to_string());
+    }
+    if chosen.is_empty() {
+        return Err("Selecione ao menos uma dificuldade para importar");
+    }
+    if all.iter().all(|v| chosen.contains(v)) {
+        Ok(None)
     } else {
-        "Erro ao importar .osz"
-    };
-    (short.into(), detail)
+        Ok(Some(chosen))
+    }
 }
 
-fn update_entry(
+fn archive_source_after_import(
     entry: &mut BeatmapEntry,
     entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
     ui_sender: &mpsc::Sender<UiMsg>,
-    status: ImportStatus,
-    message: Option<String>,
-    error_detail: Option<String>,
+    cfg: &AppConfig,
 ) {
-    entry.status = status;
-    entry.message = message.clone();
-    entry.error_detail = error_detail.clone();
-    if status == ImportStatus::Failed || entry.error_detail.is_some() {
-        entry.error_short = message.clone();
-    } else {
-        entry.error_short = None;
-    }
-    if let Ok(mut guard) = entries.lock() {
-        if let Some(stored) = guard.get_mut(&entry.id) {
-            *stored = entry.clone();
-        }
+    if !is_within_dir(&cfg.downloads_dir, &entry.osz_path) || !entry.osz_path.exists() {
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!(
+                "{}: Fonte fora da pasta de Downloads configurada; nada arquivado",
+                entry.source_file_name()
+            ),
+        ));
+        return;
     }
-    let _ = ui_sender.send(UiMsg::Upsert(entry.clone()));
-    let _ = ui_sender.send(UiMsg::Upsert(entry.clone()));
-    if let Some(msg) = message {
-        let level = match status {
-            ImportStatus::Failed => LogLevel::Error,
-            ImportStatus::DuplicateSkipped => LogLevel::Warn,
-            _ if entry.error_detail.is_some() => LogLevel::Warn,
-            _ => LogLevel::Info,
-        };
-        let mut line = format!("{}: {}", entry.source_file_name(), msg);
-        if let Some(detail) = error_detail {
-            line.push_str(&format!(" ({detail})"));
-        }
-        let _ = ui_sender.send(UiMsg::Log(level, line));
+    let archive_dir = path_utils::dated_archive_dir(&cfg.downloads_dir, std::time::SystemTime::now());
+    if in_songs_destinations(cfg, &archive_dir) {
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!(
+                "Pasta de arquivo {} fica dentro de Songs; fonte mantida",
+                archive_dir.display()
+            ),
+        ));
+        return;
+    }
+    let target = ensure_unique_path(&archive_dir, &entry.source_file_name());
+    let moved = app_state::ensure_dir(&archive_dir).and_then(|_| fs::rename(&entry.osz_path, &target));
+    match moved {
+        Ok(_) => {
+            entry.osz_path = target;
+            update_entry(
+                entry,
+                entries,
+                ui_sender,
+                entry.status,
+                Some(format!("Fonte arquivada em {}", archive_dir.display())),
+                None,
+            );
+        }
+        Err(err) => {
+            update_entry(
+                entry,
+                entries,
+                ui_sender,
+                entry.status,
+                Some("Arquivamento: fonte nao movida".into()),
+                Some(err.to_string()),
+            );
+        }
+    }
+}
+
+fn mark_source_imported(
+    entry: &mut BeatmapEntry,
+    entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: &mpsc::Sender<UiMsg>,
+    cfg: &AppConfig,
+) {
+    if !is_within_dir(&cfg.downloads_dir, &entry.osz_path) || !entry.osz_path.exists() {
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!(
+                "{}: Fonte fora da pasta de Downloads configurada; nada marcado",
+                entry.source_file_name()
+            ),
+        ));
+        return;
+    }
+    let marked = path_utils::imported_marker_path(&entry.osz_path);
+    let target = match (marked.parent(), marked.file_name().and_then(|n| n.to_str())) {
+        (Some(dir), Some(name)) => ensure_unique_path(dir, name),
+        _ => marked,
+    };
+    match fs::rename(&entry.osz_path, &target) {
+        Ok(_) => {
+            entry.osz_path = target;
+            update_entry(
+                entry,
+                entries,
+                ui_sender,
+                entry.status,
+                Some("Fonte marcada como importada".into()),
+                None,
+            );
+        }
+        Err(err) => {
+            update_entry(
+                entry,
+                entries,
+                ui_sender,
+                entry.status,
+                Some("Marcacao: fonte nao renomeada".into()),
+                Some(err.to_string()),
+            );
+        }
+    }
+}
+
+fn describe_import_error(err: &importer::ImportError) -> (String, String) {
+    let mut detail = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        detail.push_str(&format!(": {cause}"));
+        source = std::error::Error::source(cause);
+    }
+    if err.is_permission_denied() {
+        detail.push_str(&format!("\n\n{}", path_utils::PERMISSION_GUIDANCE));
+    }
+    let short = if matches!(err, importer::ImportError::InsufficientSpace { .. }) {
+        // The sizes are what the user needs to act on.
+        err.to_string()
+    } else if err.is_retryable() {
+        format!("{} (tente novamente)", err.short_message())
+    } else {
+        err.short_message().to_string()
+    };
+    (short, detail)
+}
+
+fn is_finished(status: ImportStatus) -> bool {
+    matches!(status, ImportStatus::Completed | ImportStatus::DuplicateSkipped)
+}
+
+/// Drops finished entries from the queue, optionally only those finished at least
+/// `older_than` ago, and pushes the remaining list to the UI. Returns how many were removed.
+fn clear_completed(
+    entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: &mpsc::Sender<UiMsg>,
+    older_than: Option<std::time::Duration>,
+) -> usize {
+    let now = std::time::SystemTime::now();
+    let mut removed = 0usize;
+    let mut remaining = Vec::new();
+    if let Ok(mut guard) = entries.lock() {
+        guard.retain(|_, e| {
+            let done = should_clear(e, older_than, now);
+            if done {
+                removed += 1;
+            }
+            !done
+        });
+        remaining = guard.values().cloned().collect();
+        remaining.sort_by_key(|e| e.id);
+    }
+    if removed > 0 || older_than.is_none() {
+        let _ = ui_sender.send(UiMsg::ReplaceAll(remaining));
+    }
+    removed
+}
+
+fn should_clear(
+    entry: &BeatmapEntry,
+    older_than: Option<std::time::Duration>,
+    now: std::time::SystemTime,
+) -> bool {
+    if !is_finished(entry.status) {
+        return false;
+    }
+    match older_than {
+        None => true,
+        Some(max_age) => entry
+            .completed_at
+            .and_then(|at| now.duration_since(at).ok())
+            .is_some_and(|age| age >= max_age),
+    }
+}
+
+fn update_entry(
+    entry: &mut BeatmapEntry,
+    entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: &mpsc::Sender<UiMsg>,
+    status: ImportStatus,
+    message: Option<String>,
+    error_detail: Option<String>,
+) {
+    entry.status = status;
+    entry.message = message.clone();
+    entry.error_detail = error_detail.clone();
+    if status != ImportStatus::Failed {
+        entry.failure_kind = None;
+    }
+    if is_finished(status) {
+        entry
+            .completed_at
+            .get_or_insert_with(std::time::SystemTime::now);
+    } else {
+        entry.completed_at = None;
+    }
+    if status == ImportStatus::Failed || entry.error_detail.is_some() {
+        entry.error_short = message.clone();
+    } else {
+        entry.error_short = None;
+    }
+    if let Ok(mut guard) = entries.lock() {
+        if let Some(stored) = guard.get_mut(&entry.id) {
+            *stored = entry.clone();
+        }
+    }
+    let _ = ui_sender.send(UiMsg::Upsert(entry.clone()));
+    if let Some(msg) = message {
+        let level = match status {
+            ImportStatus::Failed => LogLevel::Error,
+            ImportStatus::DuplicateSkipped | ImportStatus::Cancelled => LogLevel::Warn,
+            _ if entry.error_detail.is_some() => LogLevel::Warn,
+            _ => LogLevel::Info,
+        };
+        let mut line = format!("{}: {}", entry.source_file_name(), msg);
+        if let Some(detail) = error_detail {
+            line.push_str(&format!(" ({detail})"));
+        }
+        let _ = ui_sender.send(UiMsg::Log(level, line));
     }
 }
 
 fn refresh_entries_model(
     app: &AppWindow,
     entries_state: &Arc<Mutex<Vec<BeatmapEntry>>>,
+    selection: &Arc<Mutex<HashSet<u64>>>,
     cfg: &AppConfig,
 ) {
-    let path_warning = downloads_songs_conflict(&cfg.downloads_dir, &cfg.songs_dir);
+    let path_warning = songs_destinations_conflict(cfg);
     if let Ok(vec) = entries_state.lock() {
+        let selected = selection
+            .lock()
+            .map(|mut set| {
+                set.retain(|id| vec.iter().any(|e| e.id == *id));
+                set.clone()
+            })
+            .unwrap_or_default();
+        app.set_selected_count(selected.len() as i32);
         let show_completed = app.get_show_completed();
-        let ui_items = vec
+        let filter = app.get_queue_filter();
+        let mut visible = vec
             .iter()
             .filter(|e| {
                 show_completed
@@ -1717,10 +3994,22 @@ fn refresh_entries_model(
                         ImportStatus::Completed | ImportStatus::DuplicateSkipped
                     )
             })
-            .map(|e| to_ui_item(e, cfg, path_warning.as_deref()))
+            .filter(|e| matches_queue_filter(e, &filter))
+            .collect::<Vec<_>>();
+        if app.get_sort_recent_first() {
+            sort_recent_first(&mut visible);
+        }
+        let ui_items = visible
+            .into_iter()
+            .map(|e| to_ui_item(e, cfg, path_warning.as_deref(), selected.contains(&e.id)))
             .collect::<Vec<_>>();
         let model = Rc::new(slint::VecModel::from(ui_items));
         app.set_beatmaps(model.into());
+        let counts = queue_counts(vec.iter());
+        app.set_ready_count(counts.ready as i32);
+        app.set_new_count(counts.new as i32);
+        app.set_failed_count(counts.failed as i32);
+        app.set_failure_breakdown(SharedString::from(failure_breakdown(vec.iter())));
     }
     app.set_path_warning(SharedString::from(
         path_warning.clone().unwrap_or_default(),
@@ -1728,7 +4017,41 @@ fn refresh_entries_model(
     app.set_paths_blocked(path_warning.is_some());
 }
 
-fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>) -> BeatmapItem {
+/// Queue text filter: metadata (see `BeatmapMetadata::matches_filter`), or the file name while
+/// metadata is not read yet.
+fn matches_queue_filter(entry: &BeatmapEntry, filter: &str) -> bool {
+    match &entry.metadata {
+        Some(meta) => meta.matches_filter(filter),
+        None => {
+            let name = entry.source_file_name().to_lowercase();
+            filter
+                .split_whitespace()
+                .all(|word| name.contains(&word.to_lowercase()))
+        }
+    }
+}
+
+/// Most recently finished entries first; entries still in progress keep their queue order at the end.
+fn sort_recent_first(entries: &mut [&BeatmapEntry]) {
+    entries.sort_by_key(|e| std::cmp::Reverse(e.completed_at));
+}
+
+fn relative_time_label(at: std::time::SystemTime, now: std::time::SystemTime) -> String {
+    let secs = now.duration_since(at).map(|d| d.as_secs()).unwrap_or(0);
+    match secs {
+        0..=59 => "agora".into(),
+        60..=3_599 => format!("ha {} min", secs / 60),
+        3_600..=86_399 => format!("ha {} h", secs / 3_600),
+        _ => format!("ha {} d", secs / 86_400),
+    }
+}
+
+fn to_ui_item(
+    entry: &BeatmapEntry,
+    cfg: &AppConfig,
+    path_warning: Option<&str>,
+    selected: bool,
+) -> BeatmapItem {
     let placeholder = {
         let buffer = slint::SharedPixelBuffer::<slint::Rgb8Pixel>::new(1, 1);
         slint::Image::from_rgb8(buffer)
@@ -1743,7 +4066,7 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
     let in_downloads = is_within_dir(&cfg.downloads_dir, &entry.osz_path);
     let source_exists = entry.osz_path.exists();
     let can_delete = show_delete
-        && can_delete_source(&cfg.downloads_dir, &cfg.songs_dir, &entry.osz_path)
+        && can_delete_source_in_all(cfg, &entry.osz_path)
         && source_exists;
     let can_import = matches!(
         entry.status,
@@ -1751,12 +4074,15 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
             | ImportStatus::WaitingStable
             | ImportStatus::ReadingMetadata
             | ImportStatus::Failed
+            | ImportStatus::Cancelled
     );
+    let can_cancel = matches!(entry.status, ImportStatus::Importing);
     let can_reimport = matches!(
         entry.status,
         ImportStatus::DuplicateSkipped | ImportStatus::Completed | ImportStatus::Failed
     );
     let can_ignore = !matches!(entry.status, ImportStatus::Importing);
+    let can_undo = undo_target(entry, cfg).is_some();
     let mut info_message = entry.message.clone().unwrap_or_default();
     let mut error_short = entry.error_short.clone().unwrap_or_default();
     let mut error_detail = entry.error_detail.clone().unwrap_or_default();
@@ -1793,12 +4119,12 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
     let title = entry
         .metadata
         .as_ref()
-        .map(|m| m.display_title())
+        .map(|m| m.display_title(cfg.show_unicode_metadata))
         .unwrap_or_else(|| "Desconhecido".into());
     let artist = entry
         .metadata
         .as_ref()
-        .map(|m| m.artist.clone())
+        .map(|m| m.artist_for(cfg.show_unicode_metadata).to_string())
         .unwrap_or_default();
     let source_full = entry.source_file_name();
     let destination_full = entry
@@ -1807,10 +4133,54 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|| "-".into());
     let (audio_status, audio_available, audio_playing, audio_enabled) = audio_status_ui(entry);
+    let audio_stoppable = matches!(
+        entry.audio.status,
+        AudioPreviewStatus::Playing | AudioPreviewStatus::Paused
+    );
     let preview_enabled = entry.metadata.is_some()
         && (entry.osz_path.exists() || entry.destination.as_ref().map(|d| d.exists()).unwrap_or(false));
+    let difficulty_count = entry
+        .metadata
+        .as_ref()
+        .map(|m| difficulty_count_label(m.difficulties.len()))
+        .unwrap_or_default();
+    let has_video = entry.metadata.as_ref().map(|m| m.has_video).unwrap_or(false);
+    let mode_label = entry.metadata.as_ref().map(|m| m.mode_label()).unwrap_or_default();
+    let tags = entry.metadata.as_ref().map(|m| m.tags.join(" ")).unwrap_or_default();
+    let map_source = entry
+        .metadata
+        .as_ref()
+        .and_then(|m| m.source.clone())
+        .unwrap_or_default();
+    let difficulty_options = entry
+        .metadata
+        .as_ref()
+        .map(|m| {
+            m.difficulty_links()
+                .into_iter()
+                .map(|(name, beatmap_id)| DifficultyOption {
+                    selected: entry.is_difficulty_selected(&name),
+                    name: SharedString::from(name),
+                    beatmap_id: beatmap_id.unwrap_or(0),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let difficulty_settings = entry
+        .metadata
+        .as_ref()
+        .map(|m| m.difficulty_settings_lines().join("\n"))
+        .unwrap_or_default();
+    let can_select_difficulties = can_import && difficulty_options.len() > 1;
+    let has_difficulty_links = difficulty_options.iter().any(|d| d.beatmap_id > 0);
+    let has_set_link = entry
+        .metadata
+        .as_ref()
+        .and_then(|m| m.beatmap_set_id)
+        .is_some();
     BeatmapItem {
         id: entry.id as i32,
+        selected,
         title: SharedString::from(title),
         artist: SharedString::from(artist),
         creator: SharedString::from(
@@ -1831,7 +4201,27 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
                 .map(|m| m.difficulties.join(", "))
                 .unwrap_or_default(),
         ),
-        status: SharedString::from(entry.status.as_display()),
+        difficulty_count: SharedString::from(difficulty_count),
+        mode_label: SharedString::from(mode_label),
+        tags: SharedString::from(tags),
+        songs_target: SharedString::from(songs_target(entry, cfg).display().to_string()),
+        map_source: SharedString::from(map_source),
+        has_video,
+        difficulty_settings: SharedString::from(difficulty_settings),
+        difficulty_options: slint::ModelRc::new(Rc::new(slint::VecModel::from(difficulty_options))),
+        can_select_difficulties,
+        has_difficulty_links,
+        has_set_link,
+        status: SharedString::from(match (entry.status, entry.import_progress) {
+            (ImportStatus::Importing, Some(percent)) => format!("Importando {percent}%"),
+            (status, _) => status.as_display().to_string(),
+        }),
+        completed_ago: SharedString::from(
+            entry
+                .completed_at
+                .map(|at| relative_time_label(at, std::time::SystemTime::now()))
+                .unwrap_or_default(),
+        ),
         status_badge_color: status_badge_color(&entry.status).into(),
         message: SharedString::from(info_message),
         error_short: SharedString::from(error_short),
@@ -1839,11 +4229,14 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
         thumbnail: image,
         show_delete,
         can_delete_source: can_delete,
+        can_undo,
         delete_hint: SharedString::from(delete_hint),
         can_import,
         can_reimport,
         can_ignore,
+        can_cancel,
         audio_available,
+        audio_stoppable,
         audio_playing,
         audio_status: SharedString::from(audio_status),
         audio_enabled,
@@ -1851,12 +4244,21 @@ fn to_ui_item(entry: &BeatmapEntry, cfg: &AppConfig, path_warning: Option<&str>)
     }
 }
 
+fn difficulty_count_label(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        1 => "1 diff".into(),
+        n => format!("{n} diffs"),
+    }
+}
+
 fn status_badge_color(status: &ImportStatus) -> Color {
     match status {
         ImportStatus::Importing => Color::from_rgb_u8(93, 139, 255),
         ImportStatus::Completed => Color::from_rgb_u8(92, 193, 146),
         ImportStatus::DuplicateSkipped => Color::from_rgb_u8(245, 192, 107),
         ImportStatus::Failed => Color::from_rgb_u8(228, 123, 123),
+        ImportStatus::Cancelled => Color::from_rgb_u8(168, 150, 126),
         ImportStatus::ReadingMetadata | ImportStatus::WaitingStable => Color::from_rgb_u8(126, 138, 168),
         ImportStatus::Detected => Color::from_rgb_u8(110, 120, 140),
     }
@@ -1880,9 +4282,14 @@ fn audio_status_ui(entry: &BeatmapEntry) -> (String, bool, bool, bool) {
         .as_ref()
         .and_then(|m| m.audio_file.as_ref())
         .is_some();
+    let audio_missing = entry.metadata.as_ref().is_some_and(|m| m.audio_missing());
     let playing = matches!(entry.audio.status, AudioPreviewStatus::Playing);
-    let enabled = entry.metadata.is_some() && !matches!(entry.audio.status, AudioPreviewStatus::Unavailable);
-    let available = has_audio_meta && !matches!(entry.audio.status, AudioPreviewStatus::Unavailable);
+    let enabled = entry.metadata.is_some()
+        && !audio_missing
+        && !matches!(entry.audio.status, AudioPreviewStatus::Unavailable);
+    let available = has_audio_meta
+        && !audio_missing
+        && !matches!(entry.audio.status, AudioPreviewStatus::Unavailable);
     let status = match entry.audio.status {
         AudioPreviewStatus::Playing => "Tocando".to_string(),
         AudioPreviewStatus::Paused => "Pausado".to_string(),
@@ -1894,8 +4301,12 @@ fn audio_status_ui(entry: &BeatmapEntry) -> (String, bool, bool, bool) {
             .clone()
             .unwrap_or_else(|| "Sem audio".into()),
         AudioPreviewStatus::Unknown => {
-            if has_audio_meta {
+            if audio_missing {
+                "Audio ausente no .osz".into()
+            } else if has_audio_meta {
                 "Aguardando metadados".into()
+            } else if entry.metadata.is_some() {
+                "Sem audio (mapa sem musica)".into()
             } else {
                 "Sem audio".into()
             }
@@ -1925,6 +4336,22 @@ fn update_audio_state(
     let _ = ui_sender.send(UiMsg::Upsert(entry.clone()));
 }
 
+/// Marks a preview that was cut off by another one as ready again, so it stops showing "Tocando".
+fn reset_stopped_preview(
+    id: u64,
+    entries: &Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: &mpsc::Sender<UiMsg>,
+) {
+    let stopped = entries.lock().ok().and_then(|mut guard| {
+        let stored = guard.get_mut(&id)?;
+        stored.audio.status = AudioPreviewStatus::Ready;
+        Some(stored.clone())
+    });
+    if let Some(entry) = stopped {
+        let _ = ui_sender.send(UiMsg::Upsert(entry));
+    }
+}
+
 fn ensure_osz_hash(entry: &mut BeatmapEntry) -> Option<String> {
     if let Some(h) = entry.osz_hash.clone() {
         return Some(h);
@@ -1957,16 +4384,9 @@ fn extract_audio_to_cache(entry: &BeatmapEntry, hash: &str, audio_name: &str) ->
     }
     let file = fs::File::open(&entry.osz_path)?;
     let mut archive = zip::ZipArchive::new(file)?;
-    let lower_name = audio_name.to_lowercase();
     for i in 0..archive.len() {
         let mut item = archive.by_index(i)?;
-        let name_in_zip = item.name().to_lowercase();
-        let filename_only = Path::new(item.name())
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_default()
-            .to_lowercase();
-        if name_in_zip.ends_with(&lower_name) || filename_only == lower_name {
+        if audio::is_audio_entry(item.name(), audio_name) {
             if let Some(parent) = target_path.parent() {
                 fs::create_dir_all(parent)?;
             }
@@ -1978,10 +4398,12 @@ fn extract_audio_to_cache(entry: &BeatmapEntry, hash: &str, audio_name: &str) ->
     Err(anyhow::anyhow!("Audio nao encontrado dentro do .osz"))
 }
 
-fn resolve_audio_path(
+/// Picks the preview source: an existing file when there is one, the `.osz` entry itself the
+/// first time a map is auditioned, and a cache extraction once it is previewed again.
+fn resolve_audio_source(
     entry: &mut BeatmapEntry,
     cache_store: &CacheStore,
-) -> anyhow::Result<PathBuf> {
+) -> anyhow::Result<AudioSource> {
     let audio_file = entry
         .metadata
         .as_ref()
@@ -1993,30 +4415,185 @@ fn resolve_audio_path(
         .as_ref()
         .filter(|p| p.exists())
     {
-        return Ok(cached.clone());
+        return Ok(AudioSource::File(cached.clone()));
     }
     if let Some(dest) = entry.destination.as_ref() {
         let candidate = dest.join(&audio_file);
         if candidate.exists() {
-            return Ok(candidate);
+            return Ok(AudioSource::File(candidate));
         }
     }
+    if !entry.audio.streamed_from_archive && entry.osz_path.exists() {
+        entry.audio.streamed_from_archive = true;
+        return Ok(AudioSource::Archive {
+            osz: entry.osz_path.clone(),
+            audio_name: audio_file,
+        });
+    }
+    cache_audio(entry, cache_store, &audio_file).map(AudioSource::File)
+}
+
+/// Returns the cached audio for the entry's `.osz`, extracting it into the audio cache if needed.
+fn cache_audio(
+    entry: &mut BeatmapEntry,
+    cache_store: &CacheStore,
+    audio_file: &str,
+) -> anyhow::Result<PathBuf> {
     let hash = ensure_osz_hash(entry).ok_or_else(|| anyhow::anyhow!("Nao foi possivel calcular hash do .osz"))?;
     if let Some(cached) = cache_store.find_audio(&hash).filter(|p| p.exists()) {
         return Ok(cached);
     }
-    let extracted = extract_audio_to_cache(entry, &hash, &audio_file)?;
+    let extracted = extract_audio_to_cache(entry, &hash, audio_file)?;
     cache_store.register_audio(hash.clone(), extracted.clone());
     let _ = cache_store.save();
     Ok(extracted)
 }
 
+const PRECACHE_THREADS: usize = 3;
+
+/// Whether an entry still needs its audio extracted before a preview can start instantly.
+fn needs_audio_precache(entry: &BeatmapEntry) -> bool {
+    let Some(audio_file) = entry
+        .metadata
+        .as_ref()
+        .filter(|m| !m.audio_missing())
+        .and_then(|m| m.audio_file.as_deref())
+    else {
+        return false;
+    };
+    if audio::unsupported_format(audio_file).is_some() || !entry.osz_path.exists() {
+        return false;
+    }
+    let in_destination = entry
+        .destination
+        .as_ref()
+        .map(|d| d.join(audio_file).exists())
+        .unwrap_or(false);
+    let cached = entry
+        .audio
+        .cached_path
+        .as_ref()
+        .map(|p| p.exists())
+        .unwrap_or(false);
+    !in_destination && !cached
+}
+
+/// Extracts preview audio for every queued map on a small pool of threads, stopping once the
+/// audio cache reaches `audio_cache_max_mb`.
+fn spawn_audio_precache(
+    entries: Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
+    ui_sender: mpsc::Sender<UiMsg>,
+    cache_store: Arc<CacheStore>,
+    cfg: AppConfig,
+) {
+    let pending = entries
+        .lock()
+        .map(|m| {
+            let mut ids = m
+                .values()
+                .filter(|e| needs_audio_precache(e))
+                .map(|e| e.id)
+                .collect::<Vec<_>>();
+            ids.sort_unstable_by(|a, b| b.cmp(a));
+            ids
+        })
+        .unwrap_or_default();
+    if pending.is_empty() {
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Info,
+            "Nenhum audio para pre-carregar.".into(),
+        ));
+        return;
+    }
+    let total = pending.len();
+    let _ = ui_sender.send(UiMsg::Log(
+        LogLevel::Info,
+        format!("Pre-carregando audio de {total} item(ns)"),
+    ));
+    // Past the shared audio+thumbnail cap, new extractions would only evict earlier ones.
+    let budget_mb = match (cfg.audio_cache_max_mb, cfg.media_cache_max_mb) {
+        (0, media) => media,
+        (audio, 0) => audio,
+        (audio, media) => audio.min(media),
+    };
+    let budget = budget_mb.saturating_mul(1024 * 1024);
+    let queue = Arc::new(Mutex::new(pending));
+    let done = Arc::new(AtomicUsize::new(0));
+    let over_budget = Arc::new(AtomicBool::new(false));
+    for _ in 0..PRECACHE_THREADS.min(total) {
+        let queue = queue.clone();
+        let done = done.clone();
+        let over_budget = over_budget.clone();
+        let entries = entries.clone();
+        let ui_sender = ui_sender.clone();
+        let cache_store = cache_store.clone();
+        thread::spawn(move || loop {
+            if over_budget.load(Ordering::SeqCst) {
+                break;
+            }
+            let Some(id) = queue.lock().ok().and_then(|mut q| q.pop()) else {
+                break;
+            };
+            if budget > 0 && cache::dir_size(&cache::audio_cache_dir()) >= budget {
+                if !over_budget.swap(true, Ordering::SeqCst) {
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Warn,
+                        format!(
+                            "Cache de audio atingiu {} MB; pre-carregamento interrompido",
+                            budget_mb
+                        ),
+                    ));
+                }
+                break;
+            }
+            let Some(mut entry) = entries.lock().ok().and_then(|m| m.get(&id).cloned()) else {
+                continue;
+            };
+            let Some(audio_file) = entry.metadata.as_ref().and_then(|m| m.audio_file.clone()) else {
+                continue;
+            };
+            let result = cache_audio(&mut entry, &cache_store, &audio_file);
+            let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+            match result {
+                Ok(path) => {
+                    let updated = entries.lock().ok().and_then(|mut guard| {
+                        let stored = guard.get_mut(&id)?;
+                        stored.osz_hash = entry.osz_hash.clone();
+                        stored.audio.cached_path = Some(path);
+                        if stored.audio.status == AudioPreviewStatus::Unknown {
+                            stored.audio.status = AudioPreviewStatus::Ready;
+                        }
+                        Some(stored.clone())
+                    });
+                    if let Some(updated) = updated {
+                        let _ = ui_sender.send(UiMsg::Upsert(updated));
+                    }
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Info,
+                        format!("Audio pre-carregado {finished}/{total}: {}", entry.source_file_name()),
+                    ));
+                }
+                Err(err) => {
+                    let _ = ui_sender.send(UiMsg::Log(
+                        LogLevel::Warn,
+                        format!(
+                            "Falha ao pre-carregar audio {finished}/{total}: {} ({err:#})",
+                            entry.source_file_name()
+                        ),
+                    ));
+                }
+            }
+        });
+    }
+}
+
 fn handle_audio_preview(
     mut entry: BeatmapEntry,
     entries: Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
     ui_sender: mpsc::Sender<UiMsg>,
     cache_store: Arc<CacheStore>,
     player: AudioPlayer,
+    normalize: bool,
 ) {
     if entry.metadata.is_none() {
         update_audio_state(
@@ -2029,6 +4606,51 @@ fn handle_audio_preview(
         );
         return;
     }
+    let no_audio = entry.metadata.as_ref().and_then(|m| {
+        if m.audio_file.is_none() {
+            Some("Sem audio (mapa sem musica)")
+        } else if m.audio_missing() {
+            Some("Audio ausente no .osz")
+        } else {
+            None
+        }
+    });
+    if let Some(reason) = no_audio {
+        update_audio_state(
+            &mut entry,
+            &entries,
+            &ui_sender,
+            AudioPreviewStatus::Unavailable,
+            None,
+            Some(reason.into()),
+        );
+        return;
+    }
+    let unsupported = entry
+        .metadata
+        .as_ref()
+        .and_then(|m| m.audio_file.as_deref())
+        .and_then(audio::unsupported_format);
+    if let Some(ext) = unsupported {
+        let reason = if ext.is_empty() {
+            "Formato nao suportado (sem extensao)".to_string()
+        } else {
+            format!("Formato nao suportado (.{ext})")
+        };
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!("{}: preview de audio indisponivel ({reason})", entry.source_file_name()),
+        ));
+        update_audio_state(
+            &mut entry,
+            &entries,
+            &ui_sender,
+            AudioPreviewStatus::Unavailable,
+            None,
+            Some(reason),
+        );
+        return;
+    }
     update_audio_state(
         &mut entry,
         &entries,
@@ -2037,14 +4659,22 @@ fn handle_audio_preview(
         None,
         None,
     );
-    match resolve_audio_path(&mut entry, &cache_store) {
-        Ok(path) => {
+    match resolve_audio_source(&mut entry, &cache_store) {
+        Ok(source) => {
+            let path = match &source {
+                AudioSource::File(path) => Some(path.clone()),
+                AudioSource::Archive { .. } => None,
+            };
             if entry.audio.cached_path.is_none() {
-                entry.audio.cached_path = Some(path.clone());
+                entry.audio.cached_path = path.clone();
             }
-            match player.toggle(entry.id, &path) {
-                Ok(status) => {
-                    update_audio_state(&mut entry, &entries, &ui_sender, status, Some(path.clone()), None);
+            let start_ms = entry.metadata.as_ref().and_then(|m| m.preview_time_ms);
+            match player.toggle(entry.id, &source, normalize, start_ms) {
+                Ok(outcome) => {
+                    if let Some(stopped_id) = outcome.stopped {
+                        reset_stopped_preview(stopped_id, &entries, &ui_sender);
+                    }
+                    update_audio_state(&mut entry, &entries, &ui_sender, outcome.status, path, None);
                 }
                 Err(err) => {
                     let _ = ui_sender.send(UiMsg::Log(
@@ -2084,7 +4714,7 @@ fn handle_preview_map(
     entries: Arc<Mutex<HashMap<u64, BeatmapEntry>>>,
     ui_sender: mpsc::Sender<UiMsg>,
     _cache_store: Arc<CacheStore>,
-    _cfg: AppConfig,
+    cfg: AppConfig,
 ) {
     if entry.metadata.is_none() {
         let _ = ui_sender.send(UiMsg::Log(
@@ -2121,7 +4751,33 @@ fn handle_preview_map(
             *stored = entry.clone();
         }
     }
-    let server = match preview::ensure_server(viewer_root, cache::preview_dir()) {
+    cache::touch_preview(&prep.folder);
+    let budget = cfg.preview_cache_max_mb.saturating_mul(1024 * 1024);
+    match cache::enforce_preview_budget(&cache::preview_dir(), budget, &prep.hash) {
+        Ok(evicted) if !evicted.is_empty() => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Info,
+                format!(
+                    "Cache de preview acima de {} MB; {} preview(s) antigo(s) removido(s)",
+                    cfg.preview_cache_max_mb,
+                    evicted.len()
+                ),
+            ));
+        }
+        Ok(_) => {}
+        Err(err) => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Warn,
+                format!("Falha ao limitar cache de preview: {err:#}"),
+            ));
+        }
+    }
+    let server = match preview::ensure_server(
+        viewer_root,
+        cache::preview_dir(),
+        &cfg.preview_bind,
+        cfg.preview_port,
+    ) {
         Ok(s) => s,
         Err(err) => {
             let _ = ui_sender.send(UiMsg::Log(
@@ -2131,8 +4787,18 @@ fn handle_preview_map(
             return;
         }
     };
+    if !server.bind.is_loopback() {
+        let _ = ui_sender.send(UiMsg::Log(
+            LogLevel::Warn,
+            format!(
+                "Servidor de preview acessivel na rede ({}:{}); use apenas em redes confiaveis",
+                server.bind, server.port
+            ),
+        ));
+    }
     let url = format!(
-        "http://127.0.0.1:{}/viewer/index.html?map=/beatmaps/{}/beatmap.osz&title={}",
+        "http://{}:{}/viewer/index.html?map=/beatmaps/{}/beatmap.osz&title={}",
+        server.url_host(),
         server.port,
         prep.hash,
         encode(&prep.title)
@@ -2147,6 +4813,7 @@ fn handle_preview_map(
             url
         ),
     ));
+    let _ = ui_sender.send(UiMsg::PreviewUrl(url.clone()));
     match open_preview_url(&url) {
         Ok(_) => {
             let _ = ui_sender.send(UiMsg::Log(
@@ -2157,7 +4824,43 @@ fn handle_preview_map(
         Err(err) => {
             let _ = ui_sender.send(UiMsg::Log(
                 LogLevel::Warn,
-                format!("Nao foi possivel abrir o navegador para preview: {err}"),
+                format!(
+                    "Nao foi possivel abrir o navegador para preview: {err}; copie a URL do preview e abra manualmente"
+                ),
+            ));
+        }
+    }
+}
+
+fn restart_preview_server(ui_sender: &mpsc::Sender<UiMsg>, cfg: &AppConfig) {
+    let viewer_root = match locate_viewer_assets() {
+        Ok(path) => path,
+        Err(err) => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Error,
+                format!("Assets do viewer ausentes: {err}"),
+            ));
+            return;
+        }
+    };
+    // Old URLs point at the previous port.
+    let _ = ui_sender.send(UiMsg::PreviewUrl(String::new()));
+    match preview::restart_server(
+        viewer_root,
+        cache::preview_dir(),
+        &cfg.preview_bind,
+        cfg.preview_port,
+    ) {
+        Ok(server) => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Info,
+                format!("Servidor de preview reiniciado na porta {}", server.port),
+            ));
+        }
+        Err(err) => {
+            let _ = ui_sender.send(UiMsg::Log(
+                LogLevel::Error,
+                format!("Nao foi possivel reiniciar servidor de preview: {err:#}"),
             ));
         }
     }
@@ -2189,7 +4892,14 @@ fn prepare_preview_files(
     entry: &mut BeatmapEntry,
     ui_sender: &mpsc::Sender<UiMsg>,
 ) -> anyhow::Result<PreviewReady> {
-    let hash = ensure_osz_hash(entry).ok_or_else(|| anyhow::anyhow!("hash do .osz ausente"))?;
+    let hash = match ensure_osz_hash(entry) {
+        Some(hash) => hash,
+        // Source gone and hash unknown: key the zipped destination by its content so it is reused.
+        None => match entry.destination.as_ref().filter(|p| p.is_dir()) {
+            Some(dest) => format!("dest-{}", destination_content_hash(dest)?),
+            None => return Err(anyhow::anyhow!("hash do .osz ausente")),
+        },
+    };
     let base = cache::preview_dir().join(&hash);
     app_state::ensure_dir(&base)?;
     let osz_target = base.join("beatmap.osz");
@@ -2216,7 +4926,10 @@ fn prepare_preview_files(
         PreviewOrigin::Cached(osz_target.clone())
     };
     let extract_target = base.join("extracted");
-    if !extract_target.exists() {
+    let already_extracted = fs::read_dir(&extract_target)
+        .map(|mut dir| dir.next().is_some())
+        .unwrap_or(false);
+    if !already_extracted {
         if let Err(err) = extract_osz_for_preview(&osz_target, &extract_target) {
             let _ = ui_sender.send(UiMsg::Log(
                 LogLevel::Warn,
@@ -2230,7 +4943,8 @@ fn prepare_preview_files(
     let title = entry
         .metadata
         .as_ref()
-        .map(|m| m.display_title())
+        // Romanized: the title ends up in the viewer URL.
+        .map(|m| m.display_title(false))
         .unwrap_or_else(|| entry.source_file_name());
     Ok(PreviewReady {
         hash,
@@ -2240,6 +4954,32 @@ fn prepare_preview_files(
     })
 }
 
+/// Hash of every file (relative path + contents) under `dir`, in a stable order.
+fn destination_content_hash(dir: &Path) -> anyhow::Result<String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for item in fs::read_dir(&current)? {
+            let path = item?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    let mut hasher = blake3::Hasher::new();
+    for path in files {
+        let rel = path.strip_prefix(dir).unwrap_or(&path);
+        hasher.update(rel.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.update(&[0]);
+        let mut file = fs::File::open(&path)?;
+        std::io::copy(&mut file, &mut hasher)?;
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
 fn extract_osz_for_preview(osz_path: &Path, target_dir: &Path) -> anyhow::Result<()> {
     app_state::ensure_dir(target_dir)?;
     let file = fs::File::open(osz_path)?;
@@ -2395,6 +5135,76 @@ mod safety_tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn entry_target_falls_back_to_default_songs_dir() {
+        let cfg = AppConfig {
+            downloads_dir: PathBuf::from("/dl"),
+            songs_dir: PathBuf::from("/a/Songs"),
+            songs_dirs: vec![PathBuf::from("/a/Songs"), PathBuf::from("/b/Songs")],
+            ..AppConfig::default()
+        };
+        let mut entry = BeatmapEntry {
+            id: 1,
+            osz_path: PathBuf::from("/dl/map.osz"),
+            status: ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: std::time::SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        assert_eq!(songs_target(&entry, &cfg), PathBuf::from("/a/Songs"));
+        entry.songs_target = Some(PathBuf::from("/b/Songs"));
+        assert_eq!(songs_target(&entry, &cfg), PathBuf::from("/b/Songs"));
+        entry.songs_target = Some(PathBuf::from("/removed/Songs"));
+        assert_eq!(songs_target(&entry, &cfg), PathBuf::from("/a/Songs"));
+
+        assert!(in_songs_destinations(&cfg, Path::new("/b/Songs/1 Artist - Title")));
+        assert!(!in_songs_destinations(&cfg, Path::new("/old/Songs/1 Artist - Title")));
+
+        entry.status = ImportStatus::Completed;
+        assert_eq!(undo_target(&entry, &cfg), None);
+        entry.destination = Some(PathBuf::from("/b/Songs/1 Artist - Title"));
+        assert_eq!(
+            undo_target(&entry, &cfg),
+            Some(PathBuf::from("/b/Songs/1 Artist - Title"))
+        );
+        entry.destination = Some(PathBuf::from("/b/Songs"));
+        assert_eq!(undo_target(&entry, &cfg), None);
+        entry.destination = Some(PathBuf::from("/old/Songs/1 Artist - Title"));
+        assert_eq!(undo_target(&entry, &cfg), None);
+        entry.destination = Some(PathBuf::from("/a/Songs/1 Artist - Title"));
+        entry.status = ImportStatus::DuplicateSkipped;
+        assert_eq!(undo_target(&entry, &cfg), None);
+    }
+
+    #[test]
+    fn secondary_songs_destinations_are_guarded_too() {
+        let mut cfg = AppConfig {
+            downloads_dir: PathBuf::from("/dl"),
+            songs_dir: PathBuf::from("/a/Songs"),
+            songs_dirs: vec![PathBuf::from("/a/Songs")],
+            ..AppConfig::default()
+        };
+        let source = Path::new("/dl/extra/map.osz");
+        assert!(songs_destinations_conflict(&cfg).is_none());
+        assert!(can_delete_source_in_all(&cfg, source));
+
+        cfg.songs_dirs.push(PathBuf::from("/dl/extra"));
+        assert!(songs_destinations_conflict(&cfg).is_some());
+        assert!(!can_delete_source_in_all(&cfg, source));
+        assert!(in_songs_destinations(&cfg, Path::new("/dl/extra/_arquivados")));
+    }
+
     #[test]
     fn enforce_safety_disables_flags_when_conflict() {
         let mut cfg = AppConfig {
@@ -2402,8 +5212,7 @@ mod safety_tests {
             songs_dir: PathBuf::from("C:/dl/Songs"),
             auto_import: true,
             auto_delete_source: true,
-            suppress_delete_prompt: false,
-            stability: app_state::StabilityConfig::default(),
+            ..AppConfig::default()
         };
         let warning = enforce_path_safety(&mut cfg);
         assert!(warning.is_some());
@@ -2413,98 +5222,817 @@ mod safety_tests {
 }
 
 #[cfg(test)]
-mod audio_resolution_tests {
+mod audio_resolution_tests {
+    use super::*;
+    use std::env;
+    use std::ffi::OsString;
+    use std::io::Write;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
+    use zip::write::FileOptions;
+
+    fn build_osz_with_audio(path: &Path, audio_name: &str) {
+        let file = fs::File::create(path).unwrap();
+        let mut writer = zip::ZipWriter::new(file);
+        let opts = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let osu = format!(
+            "[Metadata]\nTitle:Test\nArtist:Artist\nCreator:Mapper\nVersion:Hard\nBeatmapSetID:1\n\n[General]\nAudioFilename:{}\n",
+            audio_name
+        );
+        writer.start_file("test.osu", opts).unwrap();
+        writer.write_all(osu.as_bytes()).unwrap();
+        writer.start_file(audio_name, opts).unwrap();
+        writer.write_all(b"audio-bytes").unwrap();
+        writer.finish().unwrap();
+    }
+
+    fn restore_env(name: &str, val: Option<OsString>) {
+        unsafe {
+            if let Some(v) = val {
+                env::set_var(name, v);
+            } else {
+                env::remove_var(name);
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_audio_prefers_destination_then_streams_then_caches() {
+        let _lock = super::ENV_GUARD.lock().unwrap();
+        let tmp = tempdir().unwrap();
+        let home = tmp.path().join("home");
+        fs::create_dir_all(&home).unwrap();
+        let old_home = env::var_os("HOME");
+        let old_local = env::var_os("LOCALAPPDATA");
+        unsafe {
+            env::set_var("HOME", &home);
+            env::set_var("LOCALAPPDATA", &home);
+        }
+
+        let osz_path = tmp.path().join("map.osz");
+        build_osz_with_audio(&osz_path, "song.mp3");
+        let dest_dir = tmp.path().join("Songs").join("Map");
+        fs::create_dir_all(&dest_dir).unwrap();
+        let dest_audio = dest_dir.join("song.mp3");
+        fs::write(&dest_audio, b"dest audio").unwrap();
+
+        let metadata = app_state::BeatmapMetadata {
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Creator".into(),
+            difficulties: vec!["Easy".into()],
+            beatmap_set_id: Some(1),
+            beatmap_ids: vec![11],
+            background_file: None,
+            audio_file: Some("song.mp3".into()),
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
+        };
+
+        let mut entry = BeatmapEntry {
+            id: 1,
+            osz_path: osz_path.clone(),
+            status: ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: Some(metadata),
+            thumbnail_path: None,
+            detected_at: SystemTime::now(),
+            destination: Some(dest_dir.clone()),
+            osz_hash: None,
+            audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+
+        let cache_store = CacheStore::load();
+        let from_dest = resolve_audio_source(&mut entry, &cache_store).unwrap();
+        assert_eq!(from_dest, AudioSource::File(dest_audio.clone()));
+
+        fs::remove_file(&dest_audio).unwrap();
+        entry.audio.cached_path = None;
+        let streamed = resolve_audio_source(&mut entry, &cache_store).unwrap();
+        assert_eq!(
+            streamed,
+            AudioSource::Archive {
+                osz: osz_path.clone(),
+                audio_name: "song.mp3".into(),
+            }
+        );
+
+        let AudioSource::File(from_cache) = resolve_audio_source(&mut entry, &cache_store).unwrap() else {
+            panic!("repeat preview should use the audio cache");
+        };
+        assert!(from_cache.exists());
+        assert!(from_cache.starts_with(cache::audio_cache_dir()));
+
+        restore_env("HOME", old_home);
+        restore_env("LOCALAPPDATA", old_local);
+    }
+
+    #[test]
+    fn precache_skips_maps_with_playable_audio() {
+        let tmp = tempdir().unwrap();
+        let osz_path = tmp.path().join("map.osz");
+        build_osz_with_audio(&osz_path, "song.mp3");
+        let mut entry = BeatmapEntry {
+            id: 1,
+            osz_path,
+            status: ImportStatus::Detected,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: Some(app_state::BeatmapMetadata {
+                title: "Title".into(),
+                artist: "Artist".into(),
+                creator: "Creator".into(),
+                difficulties: vec!["Hard".into()],
+                beatmap_set_id: Some(1),
+                beatmap_ids: vec![],
+                background_file: None,
+                audio_file: Some("song.mp3".into()),
+                has_video: false,
+                audio_present: true,
+                modes: vec![],
+                preview_time_ms: None,
+                title_unicode: None,
+                artist_unicode: None,
+                tags: vec![],
+                source: None,
+                difficulty_info: vec![],
+            }),
+            thumbnail_path: None,
+            detected_at: SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+        assert!(needs_audio_precache(&entry));
+
+        let cached = tmp.path().join("cached.mp3");
+        fs::write(&cached, b"audio").unwrap();
+        entry.audio.cached_path = Some(cached);
+        assert!(!needs_audio_precache(&entry));
+
+        entry.audio.cached_path = None;
+        if let Some(meta) = entry.metadata.as_mut() {
+            meta.audio_file = Some("song.flac".into());
+        }
+        assert!(!needs_audio_precache(&entry));
+    }
+}
+
+#[cfg(test)]
+mod delete_error_tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn delete_failure_reason_distinguishes_lock_and_permission() {
+        assert_eq!(
+            delete_failure_reason(&Error::from(ErrorKind::PermissionDenied)),
+            Some("sem permissao")
+        );
+        assert_eq!(
+            delete_failure_reason(&Error::from(ErrorKind::ResourceBusy)),
+            Some("arquivo em uso")
+        );
+        assert_eq!(delete_failure_reason(&Error::other("x")), None);
+    }
+}
+
+#[cfg(test)]
+mod queue_count_tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn entry(status: ImportStatus, with_meta: bool) -> BeatmapEntry {
+        BeatmapEntry {
+            id: 1,
+            osz_path: PathBuf::from("a.osz"),
+            status,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: with_meta.then(|| app_state::BeatmapMetadata {
+                title: "T".into(),
+                artist: "A".into(),
+                creator: "C".into(),
+                difficulties: vec![],
+                beatmap_set_id: None,
+                beatmap_ids: vec![],
+                background_file: None,
+                audio_file: None,
+                has_video: false,
+                audio_present: true,
+                modes: vec![],
+                preview_time_ms: None,
+                title_unicode: None,
+                artist_unicode: None,
+                tags: vec![],
+                source: None,
+                difficulty_info: vec![],
+            }),
+            thumbnail_path: None,
+            detected_at: SystemTime::now(),
+            destination: None,
+            osz_hash: None,
+            audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        }
+    }
+
+    #[test]
+    fn queue_filter_matches_metadata_and_tags() {
+        let mut item = entry(ImportStatus::Detected, true);
+        if let Some(meta) = item.metadata.as_mut() {
+            meta.title = "Blue Zenith".into();
+            meta.artist = "xi".into();
+            meta.creator = "Asphyxia".into();
+            meta.tags = vec!["tech".into(), "Jumps".into()];
+            meta.source = Some("BMS".into());
+        }
+        assert!(matches_queue_filter(&item, ""));
+        assert!(matches_queue_filter(&item, "zenith"));
+        assert!(matches_queue_filter(&item, "asphyxia jumps"));
+        assert!(matches_queue_filter(&item, "bms"));
+        assert!(!matches_queue_filter(&item, "zenith stream"));
+
+        let pending = entry(ImportStatus::Detected, false);
+        assert!(matches_queue_filter(&pending, "A.OSZ"));
+        assert!(!matches_queue_filter(&pending, "zenith"));
+    }
+
+    #[test]
+    fn status_update_sends_one_upsert() {
+        let (tx, rx) = mpsc::channel();
+        let mut item = entry(ImportStatus::Detected, false);
+        let entries = Arc::new(Mutex::new(HashMap::from([(1, item.clone())])));
+        update_entry(&mut item, &entries, &tx, ImportStatus::WaitingStable, None, None);
+        update_entry(
+            &mut item,
+            &entries,
+            &tx,
+            ImportStatus::Failed,
+            Some("Falhou".into()),
+            None,
+        );
+        let upserts: Vec<ImportStatus> = rx
+            .try_iter()
+            .filter_map(|msg| match msg {
+                UiMsg::Upsert(e) => Some(e.status),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(upserts, vec![ImportStatus::WaitingStable, ImportStatus::Failed]);
+        assert_eq!(entries.lock().unwrap()[&1].status, ImportStatus::Failed);
+    }
+
+    #[test]
+    fn repeated_source_reuses_existing_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("map.osz");
+        std::fs::write(&file, b"osz").unwrap();
+        let mut first = entry(ImportStatus::WaitingStable, false);
+        first.osz_path = file.clone();
+        let mut other = entry(ImportStatus::Detected, false);
+        other.id = 2;
+        other.osz_path = dir.path().join("other.osz");
+        let entries = HashMap::from([(1, first), (2, other)]);
+        let found = entry_for_source(&entries, &dir.path().join(".").join("map.osz")).unwrap();
+        assert_eq!(found.id, 1);
+        assert!(entry_for_source(&entries, &dir.path().join("new.osz")).is_none());
+    }
+
+    #[test]
+    fn import_progress_is_throttled() {
+        assert_eq!(next_progress_percent(1, 300, None), Some(0));
+        assert_eq!(next_progress_percent(10, 300, Some(0)), None);
+        assert_eq!(next_progress_percent(15, 300, Some(0)), Some(5));
+        assert_eq!(next_progress_percent(300, 300, Some(98)), Some(100));
+        assert_eq!(next_progress_percent(300, 300, Some(100)), None);
+        assert_eq!(next_progress_percent(0, 0, None), None);
+    }
+
+    #[test]
+    fn failures_are_grouped_by_kind() {
+        let mut items = vec![
+            entry(ImportStatus::Failed, true),
+            entry(ImportStatus::Failed, true),
+            entry(ImportStatus::Failed, false),
+            entry(ImportStatus::Failed, true),
+            entry(ImportStatus::Completed, true),
+        ];
+        items[0].failure_kind = Some(FailureKind::Permission);
+        items[1].failure_kind = Some(FailureKind::Permission);
+        items[2].failure_kind = Some(FailureKind::Metadata);
+        assert_eq!(
+            failure_breakdown(items.iter()),
+            "2 de permissao, 1 de metadados, 1 por outros motivos"
+        );
+        assert_eq!(failure_breakdown(items[4..].iter()), "");
+        assert!(BulkScope::Failed.includes(&items[0]));
+        assert!(!BulkScope::Failed.includes(&items[2]));
+    }
+
+    #[test]
+    fn new_only_excludes_failures() {
+        let items = [
+            entry(ImportStatus::ReadingMetadata, true),
+            entry(ImportStatus::Failed, true),
+            entry(ImportStatus::Failed, false),
+            entry(ImportStatus::Completed, true),
+            entry(ImportStatus::WaitingStable, false),
+        ];
+        assert_eq!(
+            queue_counts(items.iter()),
+            QueueCounts {
+                ready: 2,
+                new: 1,
+                failed: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn selected_scope_skips_unticked_and_finished() {
+        let mut ready = entry(ImportStatus::Failed, true);
+        ready.id = 7;
+        let mut done = entry(ImportStatus::Completed, true);
+        done.id = 8;
+        let other = entry(ImportStatus::ReadingMetadata, true);
+        let scope = BulkScope::Selected(vec![7, 8]);
+        assert!(scope.includes(&ready));
+        assert!(!scope.includes(&done));
+        assert!(!scope.includes(&other));
+        assert!(BulkScope::Ready.includes(&other));
+    }
+
+    #[test]
+    fn analysis_classifies_ready_entries_by_set_then_hash() {
+        let with = |set_id: Option<i32>, hash: &str| {
+            let mut e = entry(ImportStatus::ReadingMetadata, true);
+            if let Some(meta) = e.metadata.as_mut() {
+                meta.beatmap_set_id = set_id;
+            }
+            e.osz_hash = Some(hash.into());
+            e
+        };
+        let items = [
+            with(Some(1), "aaa"),
+            with(Some(2), "known"),
+            with(None, "known"),
+            with(None, "fresh"),
+            entry(ImportStatus::Completed, true),
+        ];
+        let analysis = analyze_queue(items.iter(), |id| id == 1, |h| h == "known");
+        assert_eq!(
+            analysis,
+            QueueAnalysis {
+                new: 1,
+                duplicate_set: 1,
+                duplicate_hash: 2,
+            }
+        );
+        assert_eq!(
+            analysis.summary(),
+            "1 novos, 3 duplicados (1 por BeatmapSetID, 2 por hash)"
+        );
+    }
+
+    #[test]
+    fn forced_scope_takes_finished_and_failed_only() {
+        assert!(BulkScope::Forced.includes(&entry(ImportStatus::Completed, true)));
+        assert!(BulkScope::Forced.includes(&entry(ImportStatus::DuplicateSkipped, true)));
+        assert!(BulkScope::Forced.includes(&entry(ImportStatus::Failed, true)));
+        assert!(!BulkScope::Forced.includes(&entry(ImportStatus::Failed, false)));
+        assert!(!BulkScope::Forced.includes(&entry(ImportStatus::ReadingMetadata, true)));
+    }
+
+    #[test]
+    fn relative_time_uses_coarsest_unit() {
+        let now = SystemTime::now();
+        let ago = |secs| relative_time_label(now - std::time::Duration::from_secs(secs), now);
+        assert_eq!(ago(10), "agora");
+        assert_eq!(ago(150), "ha 2 min");
+        assert_eq!(ago(7_300), "ha 2 h");
+        assert_eq!(ago(3 * 86_400), "ha 3 d");
+    }
+
+    #[test]
+    fn sort_recent_first_puts_pending_last() {
+        let now = SystemTime::now();
+        let mut old = entry(ImportStatus::Completed, true);
+        old.id = 1;
+        old.completed_at = Some(now - std::time::Duration::from_secs(600));
+        let mut pending = entry(ImportStatus::Detected, true);
+        pending.id = 2;
+        let mut recent = entry(ImportStatus::DuplicateSkipped, true);
+        recent.id = 3;
+        recent.completed_at = Some(now);
+        let mut items = vec![&old, &pending, &recent];
+        sort_recent_first(&mut items);
+        assert_eq!(items.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn auto_clear_waits_for_threshold() {
+        let now = SystemTime::now();
+        let max_age = Some(std::time::Duration::from_secs(60));
+        let mut done = entry(ImportStatus::Completed, true);
+        done.completed_at = Some(now - std::time::Duration::from_secs(30));
+        assert!(!should_clear(&done, max_age, now));
+        assert!(should_clear(&done, None, now));
+        done.completed_at = Some(now - std::time::Duration::from_secs(90));
+        assert!(should_clear(&done, max_age, now));
+
+        let mut failed = entry(ImportStatus::Failed, true);
+        failed.completed_at = Some(now - std::time::Duration::from_secs(90));
+        assert!(!should_clear(&failed, max_age, now));
+        assert!(!should_clear(&failed, None, now));
+    }
+}
+
+#[cfg(test)]
+mod download_progress_tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn mirrors_start_with_the_result_source() {
+        let mut result = BeatmapSearchResult {
+            id: 1,
+            set_id: 42,
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Mapper".into(),
+            source: BeatmapSource::Catboy,
+            download_url: "https://catboy.best/d/42".into(),
+            stats: SearchStats::default(),
+            expected_size: None,
+            cover_path: None,
+        };
+        let mirrors = download_mirrors(&result);
+        assert_eq!(
+            mirrors,
+            vec![
+                ("Catboy.best", "https://catboy.best/d/42".to_string()),
+                ("Nerinyan", "https://api.nerinyan.moe/d/42".to_string()),
+                ("Gatari", "https://osu.gatari.pw/d/42".to_string()),
+                ("osu.direct", "https://osu.direct/api/d/42".to_string()),
+                ("Chimu", "https://api.chimu.moe/v1/download/42".to_string()),
+            ]
+        );
+        result.set_id = 0;
+        assert_eq!(download_mirrors(&result).len(), 1);
+    }
+
+    #[test]
+    fn api_size_only_applies_to_the_source_mirror() {
+        let mut result = BeatmapSearchResult {
+            id: 1,
+            set_id: 42,
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Mapper".into(),
+            source: BeatmapSource::Nerinyan,
+            download_url: "https://api.nerinyan.moe/d/42".into(),
+            stats: SearchStats::default(),
+            expected_size: Some(1234),
+            cover_path: None,
+        };
+        assert_eq!(mirror_expected_size(&result, 0), Some(1234));
+        assert_eq!(mirror_expected_size(&result, 1), None);
+        result.expected_size = None;
+        assert_eq!(mirror_expected_size(&result, 0), None);
+    }
+
+    #[test]
+    fn api_size_mismatch_is_only_a_warning() {
+        let check = DownloadCheck {
+            bytes: 1000,
+            size_checked: true,
+        };
+        assert_eq!(
+            size_check_note(&check, Some(1000)),
+            ("tamanho conferido com o Content-Length; confere com a API do espelho".to_string(), false)
+        );
+        let (note, mismatch) = size_check_note(&check, Some(900));
+        assert!(mismatch);
+        assert!(note.ends_with("API do espelho informou 900 bytes"));
+        let unchecked = DownloadCheck {
+            bytes: 1000,
+            size_checked: false,
+        };
+        assert_eq!(
+            size_check_note(&unchecked, None),
+            ("servidor nao informou Content-Length".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn nerinyan_result_falls_back_to_every_other_mirror() {
+        let result = BeatmapSearchResult {
+            id: 1,
+            set_id: 7,
+            title: "Title".into(),
+            artist: "Artist".into(),
+            creator: "Mapper".into(),
+            source: BeatmapSource::Nerinyan,
+            download_url: "https://api.nerinyan.moe/d/7".into(),
+            stats: SearchStats::default(),
+            expected_size: None,
+            cover_path: None,
+        };
+        let labels: Vec<&str> = download_mirrors(&result).iter().map(|(l, _)| *l).collect();
+        assert_eq!(labels, ["Nerinyan", "Catboy.best", "Gatari", "osu.direct", "Chimu"]);
+        let urls: Vec<String> = download_mirrors(&result).into_iter().map(|(_, u)| u).collect();
+        assert!(urls.contains(&"https://osu.direct/api/d/7".to_string()));
+        assert!(urls.contains(&"https://api.chimu.moe/v1/download/7".to_string()));
+    }
+
+    #[test]
+    fn verify_download_rejects_truncated_or_non_zip_files() {
+        use std::io::Write;
+        let dir = tempfile::tempdir().unwrap();
+        let osz = dir.path().join("map.osz");
+        {
+            let mut zip = zip::ZipWriter::new(fs::File::create(&osz).unwrap());
+            zip.start_file("map.osu", zip::write::FileOptions::default()).unwrap();
+            zip.write_all(b"osu file format v14").unwrap();
+            zip.finish().unwrap();
+        }
+        let len = fs::metadata(&osz).unwrap().len();
+        let check = verify_download(&osz, Some(len)).unwrap();
+        assert!(check.size_checked);
+        assert_eq!(check.bytes, len);
+        assert!(!verify_download(&osz, None).unwrap().size_checked);
+        assert!(verify_download(&osz, Some(len + 10)).is_err());
+
+        let truncated = dir.path().join("truncated.osz");
+        fs::write(&truncated, &fs::read(&osz).unwrap()[..(len as usize / 2)]).unwrap();
+        assert!(verify_download(&truncated, None).is_err());
+
+        let html = dir.path().join("page.osz");
+        fs::write(&html, b"<!doctype html><html></html>").unwrap();
+        assert!(verify_download(&html, None).is_err());
+    }
+
+    #[test]
+    fn speed_meter_averages_over_window() {
+        let mut meter = SpeedMeter::new(Duration::from_secs(2));
+        let start = Instant::now();
+        assert_eq!(meter.record(start, 0), 0.0);
+        assert_eq!(meter.record(start + Duration::from_secs(1), 1_000), 1_000.0);
+        assert_eq!(meter.record(start + Duration::from_secs(2), 3_000), 1_500.0);
+        // The first sample falls out of the window; rate now reflects the last two seconds only.
+        assert_eq!(meter.record(start + Duration::from_secs(3), 7_000), 3_000.0);
+    }
+
+    #[test]
+    fn eta_formats_minutes_and_hours() {
+        assert_eq!(format_eta(Duration::from_secs(45)), "0:45");
+        assert_eq!(format_eta(Duration::from_secs(125)), "2:05");
+        assert_eq!(format_eta(Duration::from_secs(3_725)), "1:02:05");
+    }
+}
+
+#[cfg(test)]
+mod search_error_tests {
+    use super::*;
+
+    #[test]
+    fn reason_reports_http_status_and_invalid_format() {
+        let http = anyhow::anyhow!(
+            "Falha na busca Nerinyan: Status HTTP 503 Service Unavailable - <html>down</html>"
+        );
+        assert_eq!(search_error_reason(&http), "HTTP 503 Service Unavailable");
+        let format = anyhow::anyhow!(
+            "O formato da resposta da API Nerinyan era inválido: expected value\n\nResposta:\n{{"
+        );
+        assert_eq!(search_error_reason(&format), "formato invalido");
+        let limited = anyhow::anyhow!("Limite de taxa atingido em api.nerinyan.moe, tente novamente em 300s");
+        assert_eq!(search_error_reason(&limited), "limite de taxa atingido");
+        assert_eq!(search_error_reason(&anyhow::anyhow!("dns")), "erro de rede");
+    }
+
+    fn found(set_id: u64, source: BeatmapSource) -> BeatmapFound {
+        BeatmapFound {
+            set_id,
+            title: format!("Set {set_id}"),
+            artist: "Artist".into(),
+            creator: "Mapper".into(),
+            source,
+            download_url: String::new(),
+            stats: SearchStats::default(),
+            expected_size: None,
+        }
+    }
+
+    #[test]
+    fn covers_are_keyed_by_set_id() {
+        assert_eq!(cover_key(1234), "cover-1234");
+        assert_eq!(cover_url(1234), "https://assets.ppy.sh/beatmaps/1234/covers/card.jpg");
+    }
+
+    #[test]
+    fn stored_results_skip_listed_sets_and_stop_at_the_cap() {
+        let mut results = HashMap::new();
+        let mut next_id = 1;
+        let page = vec![found(1, BeatmapSource::Nerinyan), found(2, BeatmapSource::Nerinyan)];
+        assert_eq!(store_search_results(&mut results, &mut next_id, page), (2, false));
+        let next_page = vec![found(2, BeatmapSource::Catboy), found(3, BeatmapSource::Catboy)];
+        assert_eq!(store_search_results(&mut results, &mut next_id, next_page), (1, false));
+        assert_eq!(results.get(&3).map(|r| r.set_id), Some(3));
+        assert_eq!(next_id, 4);
+
+        let flood = (10..10 + MAX_SEARCH_RESULTS as u64)
+            .map(|id| found(id, BeatmapSource::Nerinyan))
+            .collect();
+        let (added, capped) = store_search_results(&mut results, &mut next_id, flood);
+        assert!(capped);
+        assert_eq!(added, MAX_SEARCH_RESULTS - 3);
+        assert_eq!(results.len(), MAX_SEARCH_RESULTS);
+    }
+
+    #[test]
+    fn merged_results_keep_the_first_copy_of_each_set() {
+        let first = vec![found(1, BeatmapSource::Catboy), found(2, BeatmapSource::Catboy)];
+        let second = vec![
+            found(2, BeatmapSource::Nerinyan),
+            found(3, BeatmapSource::Nerinyan),
+            found(3, BeatmapSource::Nerinyan),
+        ];
+        let merged = merge_search_results(first, second);
+        let ids: Vec<(u64, BeatmapSource)> = merged.into_iter().map(|b| (b.set_id, b.source)).collect();
+        assert_eq!(
+            ids,
+            vec![
+                (1, BeatmapSource::Catboy),
+                (2, BeatmapSource::Catboy),
+                (3, BeatmapSource::Nerinyan),
+            ]
+        );
+    }
+
+    #[test]
+    fn source_counts_and_failures_are_labelled() {
+        let results: Vec<BeatmapSearchResult> = [BeatmapSource::Nerinyan, BeatmapSource::Catboy, BeatmapSource::Nerinyan]
+            .into_iter()
+            .enumerate()
+            .map(|(i, source)| BeatmapSearchResult {
+                id: i as u64,
+                set_id: i as u64 + 1,
+                title: String::new(),
+                artist: String::new(),
+                creator: String::new(),
+                source,
+                download_url: String::new(),
+                stats: SearchStats::default(),
+                expected_size: None,
+                cover_path: None,
+            })
+            .collect();
+        assert_eq!(search_source_counts(&results), "Nerinyan: 2 | Catboy.best: 1");
+        assert_eq!(search_source_counts(&results[1..2]), "Catboy.best: 1");
+        assert_eq!(search_source_counts(&[]), "");
+        let failures = vec![(BeatmapSource::Catboy, "sem conexao".to_string())];
+        assert_eq!(search_failure_summary(&failures), "Catboy.best: sem conexao");
+    }
+}
+
+#[cfg(test)]
+mod search_filter_tests {
     use super::*;
-    use std::env;
-    use std::ffi::OsString;
-    use std::io::Write;
-    use std::time::SystemTime;
-    use tempfile::tempdir;
-    use zip::write::FileOptions;
 
-    fn build_osz_with_audio(path: &Path, audio_name: &str) {
-        let file = fs::File::create(path).unwrap();
-        let mut writer = zip::ZipWriter::new(file);
-        let opts = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
-        let osu = format!(
-            "[Metadata]\nTitle:Test\nArtist:Artist\nCreator:Mapper\nVersion:Hard\nBeatmapSetID:1\n\n[General]\nAudioFilename:{}\n",
-            audio_name
+    #[test]
+    fn search_urls_carry_the_page() {
+        assert_eq!(
+            nerinyan_search_url("blue zenith", 0),
+            "https://api.nerinyan.moe/search?q=blue%20zenith&p=0&ps=50"
+        );
+        assert_eq!(
+            nerinyan_search_url("blue zenith", 2),
+            "https://api.nerinyan.moe/search?q=blue%20zenith&p=2&ps=50"
+        );
+        assert_eq!(
+            catboy_search_url("a&b", 0),
+            "https://catboy.best/api/v2/search?q=a%26b&limit=50&offset=0"
+        );
+        assert_eq!(
+            catboy_search_url("a&b", 3),
+            "https://catboy.best/api/v2/search?q=a%26b&limit=50&offset=150"
         );
-        writer.start_file("test.osu", opts).unwrap();
-        writer.write_all(osu.as_bytes()).unwrap();
-        writer.start_file(audio_name, opts).unwrap();
-        writer.write_all(b"audio-bytes").unwrap();
-        writer.finish().unwrap();
     }
 
-    fn restore_env(name: &str, val: Option<OsString>) {
-        unsafe {
-            if let Some(v) = val {
-                env::set_var(name, v);
-            } else {
-                env::remove_var(name);
-            }
-        }
+    #[test]
+    fn parses_filter_inputs() {
+        assert_eq!(parse_length_input("1:30"), Some(90));
+        assert_eq!(parse_length_input(" 200 "), Some(200));
+        assert_eq!(parse_length_input(""), None);
+        assert_eq!(parse_length_input("0"), None);
+        let filter = SearchFilter::from_inputs("3", "", "4:00", "150,5", "abc");
+        assert_eq!(filter.min_difficulties, Some(3));
+        assert_eq!(filter.max_length_secs, Some(240));
+        assert_eq!(filter.min_bpm, Some(150.5));
+        assert_eq!(filter.max_bpm, None);
+        assert!(SearchFilter::from_inputs("", "", "", "", "").is_empty());
+        assert!(SearchFilter::default().with_mode_choice(0).is_empty());
+        assert_eq!(SearchFilter::default().with_mode_choice(4).mode, Some(3));
     }
 
     #[test]
-    fn resolve_audio_prefers_destination_and_falls_back_to_cache() {
-        let _lock = super::ENV_GUARD.lock().unwrap();
-        let tmp = tempdir().unwrap();
-        let home = tmp.path().join("home");
-        fs::create_dir_all(&home).unwrap();
-        let old_home = env::var_os("HOME");
-        let old_local = env::var_os("LOCALAPPDATA");
-        unsafe {
-            env::set_var("HOME", &home);
-            env::set_var("LOCALAPPDATA", &home);
-        }
-
-        let osz_path = tmp.path().join("map.osz");
-        build_osz_with_audio(&osz_path, "song.mp3");
-        let dest_dir = tmp.path().join("Songs").join("Map");
-        fs::create_dir_all(&dest_dir).unwrap();
-        let dest_audio = dest_dir.join("song.mp3");
-        fs::write(&dest_audio, b"dest audio").unwrap();
+    fn last_query_is_saved_once_per_change() {
+        let mut cfg = AppConfig::default();
+        assert!(remember_query(&mut cfg, "camellia"));
+        assert_eq!(cfg.last_query.as_deref(), Some("camellia"));
+        assert!(!remember_query(&mut cfg, "camellia"));
+        assert!(remember_query(&mut cfg, "xi"));
+        assert_eq!(cfg.last_query.as_deref(), Some("xi"));
+    }
 
-        let metadata = app_state::BeatmapMetadata {
-            title: "Title".into(),
-            artist: "Artist".into(),
-            creator: "Creator".into(),
-            difficulties: vec!["Easy".into()],
-            beatmap_set_id: Some(1),
-            beatmap_ids: vec![11],
-            background_file: None,
-            audio_file: Some("song.mp3".into()),
+    #[test]
+    fn mode_filter_keeps_sets_without_a_mode() {
+        let mania = SearchFilter::default().with_mode_choice(4);
+        let with_mode = |mode| SearchStats {
+            mode,
+            ..SearchStats::default()
         };
+        assert!(mania.matches(&with_mode(Some(3))));
+        assert!(!mania.matches(&with_mode(Some(0))));
+        assert!(mania.matches(&with_mode(None)));
+        assert!(SearchFilter::default().matches(&with_mode(Some(1))));
+    }
 
-        let mut entry = BeatmapEntry {
-            id: 1,
-            osz_path: osz_path.clone(),
-            status: ImportStatus::Detected,
-            message: None,
-            error_detail: None,
-            error_short: None,
-            metadata: Some(metadata),
-            thumbnail_path: None,
-            detected_at: SystemTime::now(),
-            destination: Some(dest_dir.clone()),
-            osz_hash: None,
-            audio: app_state::AudioPreview::default(),
+    #[test]
+    fn filter_checks_known_values_and_keeps_unknown() {
+        let filter = SearchFilter {
+            min_difficulties: Some(3),
+            max_length_secs: Some(180),
+            min_bpm: Some(160.0),
+            ..SearchFilter::default()
         };
+        let stats = |count, length, bpm| SearchStats {
+            difficulty_count: count,
+            length_secs: length,
+            bpm,
+            mode: None,
+        };
+        assert!(filter.matches(&stats(Some(4), Some(120), Some(180.0))));
+        assert!(!filter.matches(&stats(Some(2), Some(120), Some(180.0))));
+        assert!(!filter.matches(&stats(Some(4), Some(200), Some(180.0))));
+        assert!(!filter.matches(&stats(Some(4), Some(120), Some(140.0))));
+        assert!(filter.matches(&SearchStats::default()));
+    }
 
-        let cache_store = CacheStore::load();
-        let from_dest = resolve_audio_path(&mut entry, &cache_store).unwrap();
-        assert_eq!(from_dest, dest_audio);
+    #[test]
+    fn nerinyan_stats_from_difficulties() {
+        let set: NerinyanBeatmap = serde_json::from_str(
+            r#"{"id": 1, "artist": "a", "title": "t", "creator": "c", "mode": 0,
+                "beatmaps": [{"total_length": 95, "bpm": 170}, {"total_length": 120, "bpm": 175.4}]}"#,
+        )
+        .unwrap();
+        let stats = set.stats();
+        assert_eq!(stats.difficulty_count, Some(2));
+        assert_eq!(stats.length_secs, Some(120));
+        assert_eq!(stats.bpm, Some(175.4));
+        assert_eq!(stats.label(), "2 dif. | 2:00 | 175 BPM");
+    }
+}
 
-        fs::remove_file(&dest_audio).unwrap();
-        entry.audio.cached_path = None;
-        let from_cache = resolve_audio_path(&mut entry, &cache_store).unwrap();
-        assert!(from_cache.exists());
-        assert!(from_cache.starts_with(cache::audio_cache_dir()));
+#[cfg(test)]
+mod difficulty_selection_tests {
+    use super::*;
 
-        restore_env("HOME", old_home);
-        restore_env("LOCALAPPDATA", old_local);
+    #[test]
+    fn selection_collapses_to_all_and_rejects_empty() {
+        let all = vec!["Easy".to_string(), "Hard".to_string()];
+        let only_hard = apply_difficulty_selection(None, &all, "Easy", false).unwrap();
+        assert_eq!(only_hard, Some(vec!["Hard".to_string()]));
+        let back_to_all =
+            apply_difficulty_selection(only_hard.as_deref(), &all, "Easy", true).unwrap();
+        assert_eq!(back_to_all, None);
+        assert!(apply_difficulty_selection(Some(&["Hard".to_string()][..]), &all, "Hard", false).is_err());
     }
 }
 
@@ -2579,6 +6107,15 @@ mod preview_prepare_tests {
             beatmap_ids: vec![11],
             background_file: None,
             audio_file: Some("audio.mp3".into()),
+            has_video: false,
+            audio_present: true,
+            modes: vec![],
+            preview_time_ms: None,
+            title_unicode: None,
+            artist_unicode: None,
+            tags: vec![],
+            source: None,
+            difficulty_info: vec![],
         };
 
         let mut entry = BeatmapEntry {
@@ -2594,6 +6131,11 @@ mod preview_prepare_tests {
             destination: Some(dest_dir.clone()),
             osz_hash: Some("deadbeef".into()),
             audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
         };
 
         let (tx, _rx) = mpsc::channel();
@@ -2614,24 +6156,92 @@ mod preview_prepare_tests {
         restore_env("HOME", old_home);
         restore_env("LOCALAPPDATA", old_local);
     }
+
+    #[test]
+    fn zipped_destination_is_reused_without_source_hash() {
+        let _lock = super::ENV_GUARD.lock().unwrap();
+        let tmp = tempdir().unwrap();
+        let home = tmp.path().join("home");
+        std::fs::create_dir_all(&home).unwrap();
+        let old_home = env::var_os("HOME");
+        let old_local = env::var_os("LOCALAPPDATA");
+        unsafe {
+            env::set_var("HOME", &home);
+            env::set_var("LOCALAPPDATA", &home);
+        }
+
+        let dest_dir = tmp.path().join("Songs").join("Imported");
+        std::fs::create_dir_all(&dest_dir).unwrap();
+        std::fs::write(dest_dir.join("map.osu"), "osu data").unwrap();
+
+        let mut entry = BeatmapEntry {
+            id: 1,
+            osz_path: tmp.path().join("missing.osz"),
+            status: ImportStatus::Completed,
+            message: None,
+            error_detail: None,
+            error_short: None,
+            metadata: None,
+            thumbnail_path: None,
+            detected_at: SystemTime::now(),
+            destination: Some(dest_dir.clone()),
+            osz_hash: None,
+            audio: app_state::AudioPreview::default(),
+            selected_difficulties: None,
+            completed_at: None,
+            failure_kind: None,
+            import_progress: None,
+            songs_target: None,
+        };
+
+        let (tx, _rx) = mpsc::channel();
+        let first = prepare_preview_files(&mut entry, &tx).unwrap();
+        assert!(first.hash.starts_with("dest-"));
+        assert!(matches!(first.origin, PreviewOrigin::ZippedDestination(_)));
+        let second = prepare_preview_files(&mut entry, &tx).unwrap();
+        assert_eq!(second.hash, first.hash);
+        assert!(matches!(second.origin, PreviewOrigin::Cached(_)));
+
+        restore_env("HOME", old_home);
+        restore_env("LOCALAPPDATA", old_local);
+    }
+}
+
+/// The queued entry (lowest id) whose source is the same file as `path`, if any.
+fn entry_for_source(entries: &HashMap<u64, BeatmapEntry>, path: &Path) -> Option<BeatmapEntry> {
+    entries
+        .values()
+        .filter(|e| path_utils::same_file(&e.osz_path, path))
+        .min_by_key(|e| e.id)
+        .cloned()
 }
 
-fn seed_existing_osz(dir: &Path, tx: &mpsc::Sender<CommandMsg>) -> anyhow::Result<()> {
+/// Queues the `.osz` files already in `dir`, skipping sources processed before with the
+/// same size and mtime. Returns how many were skipped.
+fn seed_existing_osz(
+    dir: &Path,
+    extensions: &[String],
+    tx: &mpsc::Sender<CommandMsg>,
+    cache_store: &CacheStore,
+) -> anyhow::Result<usize> {
     if !dir.exists() {
-        return Ok(());
+        return Ok(0);
     }
+    cache_store.prune_processed();
+    let mut skipped = 0;
     for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path
-            .extension()
-            .map(|e| e.to_string_lossy().eq_ignore_ascii_case("osz"))
-            .unwrap_or(false)
-        {
-            let _ = tx.send(CommandMsg::AddFile(path));
+        if !watcher::should_queue(&path, extensions) {
+            continue;
         }
+        if cache_store.is_processed(&path) {
+            skipped += 1;
+            continue;
+        }
+        let _ = tx.send(CommandMsg::AddFile(path));
     }
-    Ok(())
+    Ok(skipped)
 }
 
 fn open_in_explorer(path: &PathBuf) {
@@ -2656,23 +6266,77 @@ fn open_url(url: &str) -> std::io::Result<()> {
     }
 }
 
+fn beatmap_set_url(set_id: i32) -> String {
+    format!("https://osu.ppy.sh/beatmapsets/{set_id}")
+}
+
 fn open_in_browser(set_id: i32) -> std::io::Result<()> {
-    let url = format!("https://osu.ppy.sh/beatmapsets/{set_id}");
+    open_url(&beatmap_set_url(set_id))
+}
+
+fn open_difficulty_in_browser(beatmap_id: i32) -> std::io::Result<()> {
+    let url = format!("https://osu.ppy.sh/b/{beatmap_id}");
     open_url(&url)
 }
 
-fn fetch_nerinyan(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("McOsuImporter/beatmap-search")
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+/// Appends to `search_log.txt` under `logs_dir()`; when that can't be opened the
+/// diagnostics are dropped rather than failing the search.
+fn open_search_log() -> Box<dyn Write> {
+    let path = cache::logs_dir().join("search_log.txt");
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => Box::new(file),
+        Err(err) => {
+            tracing::warn!("Nao foi possivel abrir {}: {err}", path.display());
+            Box::new(std::io::sink())
+        }
+    }
+}
+
+/// Results asked from each mirror per page; a full page means there may be more.
+const SEARCH_PAGE_SIZE: u32 = 50;
 
-    let encoded_query = encode(query);
-    let url = format!("https://api.nerinyan.moe/search?q={}", encoded_query);
-    let resp = client.get(&url).send()?;
+/// Nerinyan pages are zero-based (`p`) with `ps` results each.
+fn nerinyan_search_url(query: &str, page: u32) -> String {
+    format!(
+        "https://api.nerinyan.moe/search?q={}&p={}&ps={}",
+        encode(query),
+        page,
+        SEARCH_PAGE_SIZE
+    )
+}
+
+/// Catboy.best takes a result offset instead of a page number.
+fn catboy_search_url(query: &str, page: u32) -> String {
+    format!(
+        "https://catboy.best/api/v2/search?q={}&limit={}&offset={}",
+        urlencoding::encode(query),
+        SEARCH_PAGE_SIZE,
+        page * SEARCH_PAGE_SIZE
+    )
+}
+
+fn fetch_nerinyan(
+    query: &str,
+    page: u32,
+    user_agent: &str,
+    on_rate_limit: impl Fn(std::time::Duration),
+) -> anyhow::Result<Vec<BeatmapFound>> {
+    let client = http::client(user_agent)?;
+
+    let url = nerinyan_search_url(query, page);
+    let request = client
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(30));
+    let resp = rate_limit::send(request, on_rate_limit)?;
 
     if !resp.status().is_success() {
-        anyhow::bail!("Falha na busca Nerinyan: Status HTTP {}", resp.status());
+        let status = resp.status();
+        let error_text = resp.text().unwrap_or_default();
+        anyhow::bail!(
+            "Falha na busca Nerinyan: Status HTTP {} - {}",
+            status,
+            shorten_middle(error_text.trim(), SEARCH_ERROR_BODY_LIMIT)
+        );
     }
 
     // Lê o corpo da resposta como texto primeiro
@@ -2683,25 +6347,27 @@ fn fetch_nerinyan(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
         Ok(beatmaps) => {
             // Se funcionar, continua normalmente
             { // Abre um novo escopo para o log
-                if let Ok(mut log_file) = OpenOptions::new().append(true).open("logs/search_log.txt") {
-                    writeln!(log_file, "--- DIAGNÓSTICO PRÉ-FILTRO ---").unwrap();
-                    writeln!(log_file, "Inspecionando {} beatmaps recebidos da API:", beatmaps.len()).unwrap();
-                    for b in &beatmaps {
-                        writeln!(log_file, "  - ID: {}, Título: '{}', Modo: {:?}", b.set_id, b.title, b.mode).unwrap();
-                    }
-                    writeln!(log_file, "--- FIM DO DIAGNÓSTICO PRÉ-FILTRO ---").unwrap();
+                let mut log_file = open_search_log();
+                writeln!(log_file, "--- DIAGNÓSTICO PRÉ-FILTRO ---").ok();
+                writeln!(log_file, "Inspecionando {} beatmaps recebidos da API:", beatmaps.len()).ok();
+                for b in &beatmaps {
+                    writeln!(log_file, "  - ID: {}, Título: '{}', Modo: {:?}", b.set_id, b.title, b.mode).ok();
                 }
+                writeln!(log_file, "--- FIM DO DIAGNÓSTICO PRÉ-FILTRO ---").ok();
             }
             let items = beatmaps
                 .into_iter()
                 // O filtro foi removido. Agora apenas descartamos mapas com ID inválido.
                 .filter(|b| b.set_id > 0)
                 .map(|b| BeatmapFound {
+                    set_id: b.set_id,
                     title: b.title,
                     artist: b.artist,
                     creator: b.creator,
+                    stats: b.stats(),
                     source: BeatmapSource::Nerinyan,
                     download_url: format!("https://api.nerinyan.moe/d/{}", b.set_id),
+                    expected_size: b.file_size.filter(|size| *size > 0),
                 })
                 .collect();
             return Ok(items); // Retorna o sucesso imediatamente
@@ -2713,7 +6379,8 @@ fn fetch_nerinyan(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
             eprintln!("\nO corpo da resposta que causou o erro foi:\n---\n{}\n---", body_text);
 
             // --- ADIÇÃO CRÍTICA PARA LOGGING ---
-            if let Ok(mut log_file) = OpenOptions::new().append(true).open("logs/search_log.txt") {
+            {
+                let mut log_file = open_search_log();
                 writeln!(log_file, "--- ERRO FATAL DE DESSERIALIZAÇÃO (Nerinyan) ---").ok();
                 writeln!(log_file, "O erro do Serde foi: {:?}", e).ok();
                 writeln!(
@@ -2725,24 +6392,182 @@ fn fetch_nerinyan(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
             }
             // --- FIM DA ADIÇÃO ---
 
-            anyhow::bail!("O formato da resposta da API Nerinyan era inválido.")
+            anyhow::bail!(
+                "O formato da resposta da API Nerinyan era inválido: {}\n\nResposta:\n{}",
+                e,
+                shorten_middle(body_text.trim(), SEARCH_ERROR_BODY_LIMIT)
+            )
+        }
+    }
+}
+
+/// Upper bound on how much of an API response body goes into the search error detail.
+const SEARCH_ERROR_BODY_LIMIT: usize = 2000;
+
+/// Short reason for a failed search, shown next to the error message in the UI.
+fn search_error_reason(err: &anyhow::Error) -> String {
+    let text = err.to_string();
+    if let Some(rest) = text.split("Status HTTP ").nth(1) {
+        let status = rest.split(" - ").next().unwrap_or(rest).trim();
+        return format!("HTTP {}", status);
+    }
+    if text.starts_with("Limite de taxa atingido") {
+        return "limite de taxa atingido".into();
+    }
+    if text.contains("formato da resposta") {
+        return "formato invalido".into();
+    }
+    if let Some(req) = err.downcast_ref::<reqwest::Error>() {
+        if req.is_timeout() {
+            return "tempo esgotado".into();
+        }
+        if req.is_connect() {
+            return "sem conexao".into();
+        }
+    }
+    "erro de rede".into()
+}
+
+/// Thumbnail cache key of a beatmapset cover.
+fn cover_key(set_id: u64) -> String {
+    format!("cover-{set_id}")
+}
+
+/// Small cover image osu! publishes for every ranked or submitted beatmapset.
+fn cover_url(set_id: u64) -> String {
+    format!("https://assets.ppy.sh/beatmaps/{set_id}/covers/card.jpg")
+}
+
+/// Downloads the covers of `set_ids` one at a time into the thumbnail cache and tells the UI
+/// about each. Stops as soon as a newer search bumps `generation`; missing covers are skipped.
+fn spawn_cover_fetch(
+    set_ids: Vec<u64>,
+    user_agent: String,
+    cache_store: Arc<CacheStore>,
+    ui_sender: mpsc::Sender<UiMsg>,
+    generation: Arc<AtomicU64>,
+) {
+    let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if set_ids.is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        let Ok(client) = http::client(&user_agent) else {
+            return;
+        };
+        for set_id in set_ids {
+            if generation.load(Ordering::SeqCst) != current {
+                return;
+            }
+            let fetched = (|| -> anyhow::Result<PathBuf> {
+                let resp = client
+                    .get(cover_url(set_id))
+                    .timeout(std::time::Duration::from_secs(15))
+                    .send()?
+                    .error_for_status()?;
+                let img = image::load_from_memory(&resp.bytes()?)?;
+                osz_reader::cache_thumbnail(&img, &cover_key(set_id), &cache_store)
+            })();
+            match fetched {
+                Ok(path) => {
+                    let _ = ui_sender.send(UiMsg::SearchCover(set_id, path));
+                }
+                Err(err) => tracing::debug!("Capa do beatmapset {set_id} indisponivel: {err:#}"),
+            }
+        }
+    });
+}
+
+/// Most search results kept for one search across all its pages.
+const MAX_SEARCH_RESULTS: usize = 500;
+
+/// Adds `found` under fresh ids, skipping sets already stored and stopping at
+/// `MAX_SEARCH_RESULTS`. Returns how many were added and whether the cap was hit.
+fn store_search_results(
+    results: &mut HashMap<u64, BeatmapSearchResult>,
+    next_id: &mut u64,
+    found: Vec<BeatmapFound>,
+) -> (usize, bool) {
+    let mut listed: HashSet<u64> = results.values().map(|r| r.set_id).collect();
+    let mut added = 0;
+    for entry in found {
+        if entry.set_id != 0 && !listed.insert(entry.set_id) {
+            continue;
+        }
+        if results.len() >= MAX_SEARCH_RESULTS {
+            return (added, true);
+        }
+        let id = *next_id;
+        *next_id += 1;
+        results.insert(
+            id,
+            BeatmapSearchResult {
+                id,
+                set_id: entry.set_id,
+                title: entry.title,
+                artist: entry.artist,
+                creator: entry.creator,
+                source: entry.source,
+                download_url: entry.download_url,
+                stats: entry.stats,
+                expected_size: entry.expected_size,
+                cover_path: None,
+            },
+        );
+        added += 1;
+    }
+    (added, false)
+}
+
+/// Appends `more` to `found`, skipping sets already listed; entries without a set id are kept.
+fn merge_search_results(mut found: Vec<BeatmapFound>, more: Vec<BeatmapFound>) -> Vec<BeatmapFound> {
+    let mut seen: HashSet<u64> = found.iter().map(|b| b.set_id).collect();
+    for beatmap in more {
+        if beatmap.set_id == 0 || seen.insert(beatmap.set_id) {
+            found.push(beatmap);
         }
     }
+    found
+}
+
+/// "Nerinyan: HTTP 503; Catboy.best: sem conexao" for the mirrors that failed.
+fn search_failure_summary(failures: &[(BeatmapSource, String)]) -> String {
+    failures
+        .iter()
+        .map(|(source, reason)| format!("{}: {}", beatmap_source_label(source), reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// How many of the listed results came from each mirror, e.g. "Nerinyan: 12 | Catboy.best: 3".
+fn search_source_counts(results: &[BeatmapSearchResult]) -> String {
+    [BeatmapSource::Nerinyan, BeatmapSource::Catboy]
+        .iter()
+        .map(|source| {
+            let count = results.iter().filter(|r| &r.source == source).count();
+            (source, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .map(|(source, count)| format!("{}: {}", beatmap_source_label(source), count))
+        .collect::<Vec<_>>()
+        .join(" | ")
 }
 
-fn fetch_catboy(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("McOsuImporter/beatmap-search")
-        .build()?;
-    let encoded_query = urlencoding::encode(query);
-    let url = format!("https://catboy.best/api/v2/search?q={}", encoded_query);
+fn fetch_catboy(
+    query: &str,
+    page: u32,
+    user_agent: &str,
+    on_rate_limit: impl Fn(std::time::Duration),
+) -> anyhow::Result<Vec<BeatmapFound>> {
+    let client = http::client(user_agent)?;
+    let url = catboy_search_url(query, page);
     println!("--- URL SENDO CHAMADA: {} ---", url);
     // Envia a requisição e trata erros de conexão (DNS, etc.)
-    let resp = match client.get(&url).send() {
+    let resp = match rate_limit::send(client.get(&url), on_rate_limit) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("Erro de conexão ao tentar buscar em Catboy.best: {:?}", e);
-            anyhow::bail!("Falha ao enviar requisição para Catboy.best");
+            anyhow::bail!("Falha ao enviar requisição para Catboy.best: {e}");
         }
     };
 
@@ -2774,11 +6599,14 @@ fn fetch_catboy(query: &str) -> anyhow::Result<Vec<BeatmapFound>> {
                 .map(|b| {
                     let download_url = format!("https://catboy.best/d/{}", b.set_id);
                     BeatmapFound {
+                        set_id: b.set_id,
                         title: b.title,
                         artist: b.artist,
                         creator: b.creator,
                         source: BeatmapSource::Catboy,
                         download_url,
+                        stats: SearchStats::default(),
+                        expected_size: b.file_size.filter(|size| *size > 0),
                     }
                 })
                 .collect();
@@ -2823,44 +6651,241 @@ fn ensure_unique_path(base_dir: &Path, filename: &str) -> PathBuf {
     candidate
 }
 
-fn download_with_progress<F>(
+struct DownloadProgress {
+    done: u64,
+    total: Option<u64>,
+    bytes_per_sec: f64,
+    eta: Option<std::time::Duration>,
+}
+
+/// Moving-average transfer rate over the last `window` of samples, so the speed readout doesn't jitter.
+struct SpeedMeter {
+    window: std::time::Duration,
+    samples: std::collections::VecDeque<(std::time::Instant, u64)>,
+}
+
+impl SpeedMeter {
+    fn new(window: std::time::Duration) -> Self {
+        Self {
+            window,
+            samples: std::collections::VecDeque::new(),
+        }
+    }
+
+    /// Records the running byte total at `now` and returns bytes per second over the window.
+    fn record(&mut self, now: std::time::Instant, total_bytes: u64) -> f64 {
+        self.samples.push_back((now, total_bytes));
+        while self.samples.len() > 2
+            && self
+                .samples
+                .front()
+                .map(|(t, _)| now.duration_since(*t) > self.window)
+                .unwrap_or(false)
+        {
+            self.samples.pop_front();
+        }
+        let Some(&(first_at, first_bytes)) = self.samples.front() else {
+            return 0.0;
+        };
+        let secs = now.duration_since(first_at).as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+        total_bytes.saturating_sub(first_bytes) as f64 / secs
+    }
+}
+
+fn format_eta(eta: std::time::Duration) -> String {
+    let secs = eta.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Outcome of checking a finished download before it is queued.
+struct DownloadCheck {
+    bytes: u64,
+    /// Whether the mirror reported a size that the file was compared against.
+    size_checked: bool,
+}
+
+/// Log text saying which size the download was checked against, and whether the size the
+/// mirror API reported matched. The second value is true on a mismatch, which is only
+/// warned about: stale metadata must not reject a download Content-Length vouched for.
+fn size_check_note(check: &DownloadCheck, api_size: Option<u64>) -> (String, bool) {
+    let mut note = if check.size_checked {
+        "tamanho conferido com o Content-Length".to_string()
+    } else {
+        "servidor nao informou Content-Length".to_string()
+    };
+    let mismatch = api_size.is_some_and(|size| size != check.bytes);
+    match api_size {
+        Some(size) if mismatch => note.push_str(&format!("; API do espelho informou {size} bytes")),
+        Some(_) => note.push_str("; confere com a API do espelho"),
+        None => {}
+    }
+    (note, mismatch)
+}
+
+/// Rejects truncated or tampered downloads: the size must match what the mirror reported
+/// (when it did) and the file must open as a zip archive.
+fn verify_download(path: &Path, expected_size: Option<u64>) -> anyhow::Result<DownloadCheck> {
+    let bytes = fs::metadata(path)?.len();
+    if let Some(expected) = expected_size {
+        if bytes != expected {
+            anyhow::bail!(
+                "Download corrompido: {bytes} bytes recebidos, servidor informou {expected}"
+            );
+        }
+    }
+    zip::ZipArchive::new(fs::File::open(path)?)
+        .map_err(|err| anyhow::anyhow!("Download corrompido: arquivo nao e um .osz valido ({err})"))?;
+    Ok(DownloadCheck {
+        bytes,
+        size_checked: expected_size.is_some(),
+    })
+}
+
+fn download_with_progress<W, F>(
     client: &reqwest::blocking::Client,
     url: &str,
     temp_path: &Path,
     final_path: &Path,
+    max_kbps: Option<u32>,
+    on_rate_limit: W,
     progress: F,
-) -> anyhow::Result<()>
+) -> anyhow::Result<DownloadCheck>
 where
-    F: Fn(u64, Option<u64>),
+    W: Fn(std::time::Duration),
+    F: Fn(&DownloadProgress),
 {
+    let mut throttle = throttle::Throttle::new(max_kbps);
+    // Set when the transfer breaks mid-stream; the `.part` then stays for the next attempt.
+    let mut keep_part = false;
     let res = (|| {
         if let Some(parent) = temp_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let mut resp = client.get(url).send()?.error_for_status()?;
-        let total = resp.content_length();
-        let mut file = std::fs::File::create(temp_path)?;
+        let existing = fs::metadata(temp_path).map(|m| m.len()).unwrap_or(0);
+        let request = downloader::download_request(client, url, existing);
+        let mut resp = rate_limit::send(request, &on_rate_limit)?.error_for_status()?;
+        let resume_from = if existing > 0 {
+            downloader::resume_offset(
+                resp.status(),
+                resp.headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok()),
+                existing,
+            )?
+        } else {
+            0
+        };
+        let total = resp.content_length().map(|len| len + resume_from);
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let mut file = if resume_from > 0 {
+            OpenOptions::new().append(true).open(temp_path)?
+        } else {
+            std::fs::File::create(temp_path)?
+        };
         let mut buf = [0u8; 32 * 1024];
-        let mut downloaded = 0u64;
+        let mut downloaded = resume_from;
+        let mut first_chunk = true;
+        let mut meter = SpeedMeter::new(std::time::Duration::from_secs(3));
         loop {
-            let n = resp.read(&mut buf)?;
+            let n = match resp.read(&mut buf) {
+                Ok(n) => n,
+                Err(err) => {
+                    keep_part = true;
+                    return Err(anyhow::Error::from(err));
+                }
+            };
             if n == 0 {
                 break;
             }
+            // Checked on every response, so an error page sent with a `206` isn't appended to the `.part`.
+            if std::mem::take(&mut first_chunk)
+                && downloader::looks_like_html(&buf[..n.min(512)], &content_type)
+            {
+                anyhow::bail!("Resposta nao parece um .osz (pagina HTML)");
+            }
             file.write_all(&buf[..n])?;
             downloaded += n as u64;
-            progress(downloaded, total);
+            let bytes_per_sec = meter.record(std::time::Instant::now(), downloaded);
+            let eta = total
+                .filter(|_| bytes_per_sec > 0.0)
+                .map(|t| {
+                    std::time::Duration::from_secs_f64(t.saturating_sub(downloaded) as f64 / bytes_per_sec)
+                });
+            progress(&DownloadProgress {
+                done: downloaded,
+                total,
+                bytes_per_sec,
+                eta,
+            });
+            if let Some(throttle) = throttle.as_mut() {
+                throttle.consume(n);
+            }
         }
         file.flush()?;
+        drop(file);
+        let check = verify_download(temp_path, total)?;
         std::fs::rename(temp_path, final_path)?;
-        Ok::<(), anyhow::Error>(())
+        Ok::<DownloadCheck, anyhow::Error>(check)
     })();
-    if res.is_err() {
+    if res.is_err() && !keep_part {
         let _ = std::fs::remove_file(temp_path);
     }
     res
 }
 
+/// Empty, zero or unparsable input means no limit.
+fn parse_download_limit(text: &str) -> Option<u32> {
+    text.trim().parse::<u32>().ok().filter(|k| *k > 0)
+}
+
+fn download_limit_text(limit: Option<u32>) -> SharedString {
+    limit.map(|k| k.to_string()).unwrap_or_default().into()
+}
+
+/// API-reported size to compare the download from the `attempt`-th mirror with: only the
+/// source mirror (tried first) serves the file its API described.
+fn mirror_expected_size(result: &BeatmapSearchResult, attempt: usize) -> Option<u64> {
+    result.expected_size.filter(|_| attempt == 0)
+}
+
+/// Mirrors to try for a search result, the one it came from first; the others are only
+/// known by set id, so results without one get no fallback.
+fn download_mirrors(result: &BeatmapSearchResult) -> Vec<(&'static str, String)> {
+    let mut mirrors = vec![(beatmap_source_label(&result.source), result.download_url.clone())];
+    if result.set_id == 0 {
+        return mirrors;
+    }
+    for source in [BeatmapSource::Nerinyan, BeatmapSource::Catboy] {
+        if source == result.source {
+            continue;
+        }
+        let url = match source {
+            BeatmapSource::Nerinyan => format!("https://api.nerinyan.moe/d/{}", result.set_id),
+            BeatmapSource::Catboy => format!("https://catboy.best/d/{}", result.set_id),
+        };
+        mirrors.push((beatmap_source_label(&source), url));
+    }
+    let set_id = result.set_id.to_string();
+    for provider in downloader::DownloadProvider::SET_ID_MIRRORS {
+        if let Ok(resolved) = downloader::resolve_download(provider, &set_id) {
+            mirrors.push((provider.as_str(), resolved.url));
+        }
+    }
+    mirrors
+}
+
 fn beatmap_source_label(source: &BeatmapSource) -> &'static str {
     match source {
         BeatmapSource::Catboy => "Catboy.best",
@@ -2870,10 +6895,17 @@ fn beatmap_source_label(source: &BeatmapSource) -> &'static str {
 
 fn to_search_item(result: &BeatmapSearchResult) -> BeatmapSearchItem {
     let source_label = beatmap_source_label(&result.source);
+    let cover = result
+        .cover_path
+        .as_ref()
+        .and_then(|p| slint::Image::load_from_path(p).ok());
     BeatmapSearchItem {
         id: result.id as i32,
         title: SharedString::from(&result.title),
         artist_mapper: SharedString::from(format!("{} | {}", result.artist, result.creator)),
         source: SharedString::from(source_label),
+        details: SharedString::from(result.stats.label()),
+        cover: cover.clone().unwrap_or_default(),
+        has_cover: cover.is_some(),
     }
 }
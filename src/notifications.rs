@@ -0,0 +1,83 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify_rust::Notification;
+use once_cell::sync::OnceCell;
+use tracing::warn;
+
+const COALESCE_WINDOW: Duration = Duration::from_secs(3);
+const MAX_LISTED: usize = 5;
+
+static QUEUE: OnceCell<mpsc::Sender<String>> = OnceCell::new();
+
+/// Queues an "imported" toast; titles arriving within the coalesce window share one notification.
+pub fn notify_imported(title: String) {
+    let tx = QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<String>();
+        thread::spawn(move || run(rx));
+        tx
+    });
+    let _ = tx.send(title);
+}
+
+fn run(rx: mpsc::Receiver<String>) {
+    while let Ok(first) = rx.recv() {
+        let mut titles = vec![first];
+        let deadline = Instant::now() + COALESCE_WINDOW;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match rx.recv_timeout(deadline - now) {
+                Ok(title) => titles.push(title),
+                Err(_) => break,
+            }
+        }
+        let (summary, body) = summarize(&titles);
+        if let Err(err) = Notification::new()
+            .appname("McOsu Importer")
+            .summary(&summary)
+            .body(&body)
+            .show()
+        {
+            warn!("Falha ao exibir notificacao: {err}");
+        }
+    }
+}
+
+pub(crate) fn summarize(titles: &[String]) -> (String, String) {
+    if let [single] = titles {
+        return (
+            format!("Importado: {single}"),
+            "Beatmap adicionado a pasta Songs".into(),
+        );
+    }
+    let mut lines = titles.iter().take(MAX_LISTED).cloned().collect::<Vec<_>>();
+    if titles.len() > MAX_LISTED {
+        lines.push(format!("... e mais {}", titles.len() - MAX_LISTED));
+    }
+    (
+        format!("{} beatmaps importados", titles.len()),
+        lines.join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_coalesces_batches() {
+        let (summary, _) = summarize(&["A - B".to_string()]);
+        assert_eq!(summary, "Importado: A - B");
+
+        let many = (0..7).map(|i| format!("Map {i}")).collect::<Vec<_>>();
+        let (summary, body) = summarize(&many);
+        assert_eq!(summary, "7 beatmaps importados");
+        assert!(body.contains("Map 4"));
+        assert!(!body.contains("Map 5"));
+        assert!(body.ends_with("... e mais 2"));
+    }
+}
@@ -3,16 +3,67 @@ use std::collections::HashMap;
 use anyhow::Result;
 use regex::Regex;
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+use crate::app_state::DifficultySettings;
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ParsedOsu {
+    /// Romanized `Title`, or `TitleUnicode` when the file has no romanized one.
     pub title: String,
     pub artist: String,
+    /// `TitleUnicode` when it differs from the romanized title.
+    pub title_unicode: Option<String>,
+    pub artist_unicode: Option<String>,
     pub creator: String,
     pub version: String,
+    /// Space-separated `Tags`, split into words.
+    pub tags: Vec<String>,
+    /// `Source` (the game, anime or album the song comes from).
+    pub source: Option<String>,
     pub beatmap_set_id: Option<i32>,
     pub beatmap_id: Option<i32>,
     pub background_file: Option<String>,
     pub audio_file: Option<String>,
+    /// `N` from the `osu file format vN` header; `None` when the header is missing.
+    pub format_version: Option<u32>,
+    /// `Mode` from `[General]` (0 osu!, 1 taiko, 2 catch, 3 mania); `None` when absent or unknown.
+    pub mode: Option<u8>,
+    /// `PreviewTime` in milliseconds; `None` when missing or `-1` (no preview point set).
+    pub preview_time: Option<u32>,
+    /// HP, CS, OD, AR and slider multiplier from `[Difficulty]`.
+    pub difficulty: DifficultySettings,
+}
+
+/// Files older than this predate `[Metadata]` IDs and the current timing/hit object
+/// layouts; McOsu may render them oddly.
+pub const LEGACY_FORMAT_BELOW: u32 = 5;
+
+/// First version that stores `BeatmapID`/`BeatmapSetID` in `[Metadata]`.
+const METADATA_IDS_SINCE: u32 = 10;
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "bmp", "gif"];
+
+/// Background formats maps ship with; decoding still auto-detects the real format.
+pub fn is_image_file(name: &str) -> bool {
+    std::path::Path::new(name)
+        .extension()
+        .map(|ext| {
+            let ext = ext.to_string_lossy();
+            IMAGE_EXTENSIONS.iter().any(|i| ext.eq_ignore_ascii_case(i))
+        })
+        .unwrap_or(false)
+}
+
+/// Reads `osu file format v14` from the first non-empty line (tolerating a UTF-8 BOM).
+fn parse_format_version(content: &str) -> Option<u32> {
+    let first = content
+        .lines()
+        .map(|l| l.trim_start_matches('\u{feff}').trim())
+        .find(|l| !l.is_empty())?;
+    first
+        .strip_prefix("osu file format v")?
+        .trim()
+        .parse::<u32>()
+        .ok()
 }
 
 pub fn parse_osu(content: &str) -> Result<ParsedOsu> {
@@ -33,24 +84,36 @@ pub fn parse_osu(content: &str) -> Result<ParsedOsu> {
     let metadata = sections.get("Metadata").cloned().unwrap_or_default();
     let general = sections.get("General").cloned().unwrap_or_default();
     let events = sections.get("Events").cloned().unwrap_or_default();
+    let difficulty = sections.get("Difficulty").cloned().unwrap_or_default();
 
-    let mut parsed = ParsedOsu::default();
+    let mut parsed = ParsedOsu {
+        format_version: parse_format_version(content),
+        ..ParsedOsu::default()
+    };
+    // Very old files never carry online IDs; anything that looks like one is not trustworthy.
+    let ids_supported = parsed
+        .format_version
+        .is_none_or(|v| v >= METADATA_IDS_SINCE);
     let kv_re = Regex::new(r"^([A-Za-z]+)\s*:\s*(.*)$").unwrap();
     for line in metadata {
         if let Some(caps) = kv_re.captures(line) {
             let key = caps.get(1).unwrap().as_str();
             let val = caps.get(2).unwrap().as_str().trim().to_string();
             match key {
-                "Title" | "TitleUnicode" if parsed.title.is_empty() => parsed.title = val,
-                "Artist" | "ArtistUnicode" if parsed.artist.is_empty() => parsed.artist = val,
+                "Title" => parsed.title = val,
+                "Artist" => parsed.artist = val,
+                "TitleUnicode" if !val.is_empty() => parsed.title_unicode = Some(val),
+                "ArtistUnicode" if !val.is_empty() => parsed.artist_unicode = Some(val),
                 "Creator" => parsed.creator = val,
+                "Tags" => parsed.tags = val.split_whitespace().map(str::to_string).collect(),
+                "Source" if !val.is_empty() => parsed.source = Some(val),
                 "Version" => parsed.version = val,
-                "BeatmapSetID" => {
+                "BeatmapSetID" if ids_supported => {
                     if let Ok(id) = val.parse::<i32>() {
                         parsed.beatmap_set_id = Some(id);
                     }
                 }
-                "BeatmapID" => {
+                "BeatmapID" if ids_supported => {
                     if let Ok(id) = val.parse::<i32>() {
                         parsed.beatmap_id = Some(id);
                     }
@@ -67,8 +130,35 @@ pub fn parse_osu(content: &str) -> Result<ParsedOsu> {
             if key == "AudioFilename" && parsed.audio_file.is_none() && !val.is_empty() {
                 parsed.audio_file = Some(val);
             }
+            if key == "PreviewTime" {
+                parsed.preview_time = val.parse::<i64>().ok().and_then(|ms| u32::try_from(ms).ok());
+            }
+            if key == "Mode" {
+                parsed.mode = val.parse::<u8>().ok().filter(|m| *m <= 3);
+            }
+        }
+    }
+
+    for line in difficulty {
+        if let Some(caps) = kv_re.captures(line) {
+            let key = caps.get(1).unwrap().as_str();
+            let Ok(val) = caps.get(2).unwrap().as_str().trim().parse::<f32>() else {
+                continue;
+            };
+            let settings = &mut parsed.difficulty;
+            match key {
+                "HPDrainRate" => settings.hp_drain = Some(val),
+                "CircleSize" => settings.circle_size = Some(val),
+                "OverallDifficulty" => settings.overall_difficulty = Some(val),
+                "ApproachRate" => settings.approach_rate = Some(val),
+                "SliderMultiplier" => settings.slider_multiplier = Some(val),
+                _ => {}
+            }
         }
     }
+    if parsed.difficulty.approach_rate.is_none() {
+        parsed.difficulty.approach_rate = parsed.difficulty.overall_difficulty;
+    }
 
     // Parse background event: 0,0,"bg.jpg",0,0
     for line in events {
@@ -76,9 +166,9 @@ pub fn parse_osu(content: &str) -> Result<ParsedOsu> {
             // split respecting quoted filename
             let parts: Vec<&str> = line.split(',').collect();
             for part in parts {
-                if part.contains(".jpg") || part.contains(".png") {
-                    let cleaned = part.trim().trim_matches('"').to_string();
-                    parsed.background_file = Some(cleaned);
+                let cleaned = part.trim().trim_matches('"');
+                if is_image_file(cleaned) {
+                    parsed.background_file = Some(cleaned.to_string());
                     break;
                 }
             }
@@ -88,6 +178,17 @@ pub fn parse_osu(content: &str) -> Result<ParsedOsu> {
         }
     }
 
+    for (romanized, unicode) in [
+        (&mut parsed.title, &mut parsed.title_unicode),
+        (&mut parsed.artist, &mut parsed.artist_unicode),
+    ] {
+        if romanized.is_empty() {
+            *romanized = unicode.take().unwrap_or_default();
+        } else if unicode.as_deref() == Some(romanized.as_str()) {
+            *unicode = None;
+        }
+    }
+
     // Basic validation
     if parsed.title.is_empty() && parsed.artist.is_empty() {
         return Err(anyhow::anyhow!("Incomplete metadata"));
@@ -120,5 +221,128 @@ mod tests {
         assert_eq!(parsed.version, "Hard");
         assert_eq!(parsed.beatmap_set_id, Some(123));
         assert_eq!(parsed.background_file.as_deref(), Some("bg.jpg"));
+        assert_eq!(parsed.format_version, None);
+    }
+
+    #[test]
+    fn parse_difficulty_settings() {
+        let text = r#"osu file format v14
+
+        [General]
+        AudioFilename: audio.mp3
+        Mode: 0
+
+        [Metadata]
+        Title:Blue Zenith
+        Artist:xi
+        Creator:Asphyxia
+        Version:FOUR DIMENSIONS
+
+        [Difficulty]
+        HPDrainRate:6
+        CircleSize:4
+        OverallDifficulty:9
+        ApproachRate:9.6
+        SliderMultiplier:1.8
+        SliderTickRate:1
+        "#;
+        let parsed = parse_osu(text).unwrap();
+        assert_eq!(
+            parsed.difficulty,
+            DifficultySettings {
+                hp_drain: Some(6.0),
+                circle_size: Some(4.0),
+                overall_difficulty: Some(9.0),
+                approach_rate: Some(9.6),
+                slider_multiplier: Some(1.8),
+            }
+        );
+        assert_eq!(parsed.difficulty.summary(), "AR 9.6 OD 9 CS 4 HP 6 SV 1.8");
+
+        // Old files without ApproachRate use OD for it.
+        let legacy = parse_osu(
+            "osu file format v5\n[Metadata]\nTitle:Old\n[Difficulty]\nOverallDifficulty:5\nCircleSize:3\n",
+        )
+        .unwrap();
+        assert_eq!(legacy.difficulty.approach_rate, Some(5.0));
+        assert_eq!(legacy.difficulty.hp_drain, None);
+
+        let none = parse_osu("[Metadata]\nTitle:Song\n").unwrap();
+        assert_eq!(none.difficulty.summary(), "");
+    }
+
+    #[test]
+    fn background_accepts_other_image_formats() {
+        for (event, expected) in [
+            ("0,0,\"bg.webp\",0,0", "bg.webp"),
+            ("0,0,\"Cover Art.JPEG\",0,0", "Cover Art.JPEG"),
+            ("0,0,bg.bmp", "bg.bmp"),
+        ] {
+            let text = format!("[Metadata]\nTitle:Song\n[Events]\n{event}\n");
+            let parsed = parse_osu(&text).unwrap();
+            assert_eq!(parsed.background_file.as_deref(), Some(expected));
+        }
+        let video_only = parse_osu("[Metadata]\nTitle:Song\n[Events]\n0,0,\"intro.mp4\",0,0\n").unwrap();
+        assert_eq!(video_only.background_file, None);
+    }
+
+    #[test]
+    fn parse_keeps_romanized_and_unicode_metadata() {
+        let text = "[Metadata]\nTitleUnicode:\u{5343}\u{672c}\u{685c}\nTitle:Senbonzakura\nArtist:Kurousa-P\nArtistUnicode:\u{9ed2}\u{3046}\u{3055}P\n";
+        let parsed = parse_osu(text).unwrap();
+        assert_eq!(parsed.title, "Senbonzakura");
+        assert_eq!(parsed.title_unicode.as_deref(), Some("\u{5343}\u{672c}\u{685c}"));
+        assert_eq!(parsed.artist, "Kurousa-P");
+        assert_eq!(parsed.artist_unicode.as_deref(), Some("\u{9ed2}\u{3046}\u{3055}P"));
+
+        let same = parse_osu("[Metadata]\nTitle:Song\nTitleUnicode:Song\n").unwrap();
+        assert_eq!(same.title_unicode, None);
+
+        let only_unicode = parse_osu("[Metadata]\nTitleUnicode:\u{66f2}\n").unwrap();
+        assert_eq!(only_unicode.title, "\u{66f2}");
+        assert_eq!(only_unicode.title_unicode, None);
+    }
+
+    #[test]
+    fn parse_tags_and_source() {
+        let text = "[Metadata]\nTitle:Song\nSource:Touhou\nTags:  stream  jump farm\n";
+        let parsed = parse_osu(text).unwrap();
+        assert_eq!(parsed.tags, vec!["stream", "jump", "farm"]);
+        assert_eq!(parsed.source.as_deref(), Some("Touhou"));
+
+        let bare = parse_osu("[Metadata]\nTitle:Song\nSource:\nTags:\n").unwrap();
+        assert!(bare.tags.is_empty());
+        assert_eq!(bare.source, None);
+    }
+
+    #[test]
+    fn parse_preview_time_ignores_unset() {
+        let set = "[General]\nPreviewTime: 61234\n[Metadata]\nTitle:Song\n";
+        assert_eq!(parse_osu(set).unwrap().preview_time, Some(61234));
+        let unset = "[General]\nPreviewTime: -1\n[Metadata]\nTitle:Song\n";
+        assert_eq!(parse_osu(unset).unwrap().preview_time, None);
+    }
+
+    #[test]
+    fn parse_mode_from_general() {
+        let mania = "[General]\nMode: 3\n[Metadata]\nTitle:Keys\n";
+        assert_eq!(parse_osu(mania).unwrap().mode, Some(3));
+        let unknown = "[General]\nMode: 9\n[Metadata]\nTitle:Odd\n";
+        assert_eq!(parse_osu(unknown).unwrap().mode, None);
+        assert_eq!(parse_osu("[Metadata]\nTitle:Plain\n").unwrap().mode, None);
+    }
+
+    #[test]
+    fn parse_format_version_header() {
+        let modern = "\u{feff}osu file format v14\n\n[Metadata]\nTitle:New\nBeatmapSetID:99\n";
+        let parsed = parse_osu(modern).unwrap();
+        assert_eq!(parsed.format_version, Some(14));
+        assert_eq!(parsed.beatmap_set_id, Some(99));
+
+        let old = "osu file format v3\n[General]\nAudioFilename: a.mp3\n[Metadata]\nTitle:Old\nBeatmapSetID:5\n";
+        let parsed = parse_osu(old).unwrap();
+        assert_eq!(parsed.format_version, Some(3));
+        assert_eq!(parsed.beatmap_set_id, None);
+        assert_eq!(parsed.audio_file.as_deref(), Some("a.mp3"));
     }
 }
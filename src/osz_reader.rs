@@ -5,9 +5,16 @@ use std::path::{Path, PathBuf};
 use anyhow::Result;
 use zip::ZipArchive;
 
-use crate::app_state::BeatmapMetadata;
+use crate::app_state::{BeatmapMetadata, DifficultyInfo, sanitize_path_component};
 use crate::cache::{CacheStore, thumbnails_dir};
-use crate::osu_parser::parse_osu;
+use crate::osu_parser::{is_image_file, parse_osu};
+
+/// The archive holds no `.osu` but bundles other `.osz` files (a map pack).
+#[derive(Debug, thiserror::Error)]
+#[error("pacote com {} arquivo(s) .osz", inner.len())]
+pub struct BeatmapPack {
+    pub inner: Vec<String>,
+}
 
 #[derive(Debug)]
 pub struct OszMetadata {
@@ -16,14 +23,25 @@ pub struct OszMetadata {
     pub hash: String,
 }
 
-pub fn read_osz_metadata(path: &Path, cache: &CacheStore) -> Result<OszMetadata> {
+/// With `strict` off, an archive without a parseable `.osu` is still accepted and named after the file.
+pub fn read_osz_metadata(path: &Path, cache: &CacheStore, strict: bool) -> Result<OszMetadata> {
     let mut file = File::open(path)?;
     let mut buf = Vec::new();
     file.read_to_end(&mut buf)?;
     let hash = blake3::hash(&buf).to_hex().to_string();
+    let fallback_name = (!strict).then(|| {
+        path.file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    });
 
-    if let Some(cached) = cache.get_thumbnail(&hash) {
-        let metadata = extract_metadata_from_archive(&buf)?;
+    // Missing files and thumbnails cached before the fixed 16:9 frame are regenerated.
+    let cached = cache
+        .get_thumbnail(&hash)
+        .filter(|p| image::image_dimensions(p).ok() == Some(THUMBNAIL_SIZE));
+    if let Some(cached) = cached {
+        let metadata = extract_metadata_from_archive(&buf, fallback_name.as_deref())?;
         return Ok(OszMetadata {
             metadata,
             thumbnail_path: Some(cached),
@@ -31,18 +49,11 @@ pub fn read_osz_metadata(path: &Path, cache: &CacheStore) -> Result<OszMetadata>
         });
     }
 
-    let metadata = extract_metadata_from_archive(&buf)?;
+    let metadata = extract_metadata_from_archive(&buf, fallback_name.as_deref())?;
     let thumb = if let Some(bg) = metadata.background_file.clone() {
         let tmp = load_image_from_archive(&buf, &bg)?;
         if let Some(img) = tmp {
-            let thumb = create_thumbnail(&img)?;
-            let dir = thumbnails_dir();
-            std::fs::create_dir_all(&dir)?;
-            let path = dir.join(format!("{hash}.png"));
-            thumb.save(&path)?;
-            cache.insert_thumbnail(hash.clone(), path.clone());
-            let _ = cache.save();
-            Some(path)
+            Some(cache_thumbnail(&img, &hash, cache)?)
         } else {
             None
         }
@@ -57,29 +68,77 @@ pub fn read_osz_metadata(path: &Path, cache: &CacheStore) -> Result<OszMetadata>
     })
 }
 
-fn extract_metadata_from_archive(buf: &[u8]) -> Result<BeatmapMetadata> {
+fn extract_metadata_from_archive(buf: &[u8], fallback_name: Option<&str>) -> Result<BeatmapMetadata> {
     let cursor = std::io::Cursor::new(buf);
     let mut zip = ZipArchive::new(cursor)?;
     let mut parsed_files = Vec::new();
+    let mut nested = Vec::new();
+    let mut has_video = false;
+    let mut entry_names = Vec::new();
     for i in 0..zip.len() {
         let mut file = zip.by_index(i)?;
+        entry_names.push(normalize_entry_name(file.name()));
+        if is_video_file(file.name()) {
+            has_video = true;
+        }
+        if is_osz_name(file.name()) {
+            nested.push(file.name().to_string());
+        }
         if file.name().ends_with(".osu") {
+            let file_name = file.name().to_string();
             let mut contents = String::new();
             file.read_to_string(&mut contents)?;
             if let Ok(parsed) = parse_osu(&contents) {
-                parsed_files.push(parsed);
+                parsed_files.push((file_name, parsed));
             }
         }
     }
     if parsed_files.is_empty() {
+        if !nested.is_empty() {
+            return Err(BeatmapPack { inner: nested }.into());
+        }
+        if let Some(name) = fallback_name {
+            return Ok(unparsed_metadata(name, has_video));
+        }
         return Err(anyhow::anyhow!("Nenhum .osu encontrado"));
     }
-    let main = parsed_files.first().cloned().unwrap();
-    let difficulties = parsed_files.iter().map(|p| p.version.clone()).collect();
+    let main = parsed_files.first().map(|(_, p)| p.clone()).unwrap();
+    let audio_present = main
+        .audio_file
+        .as_deref()
+        .map(|audio| entry_names.contains(&normalize_entry_name(audio)))
+        .unwrap_or(false);
+    let difficulties = parsed_files.iter().map(|(_, p)| p.version.clone()).collect();
     let beatmap_ids = parsed_files
         .iter()
-        .filter_map(|p| p.beatmap_id)
+        .filter_map(|(_, p)| p.beatmap_id)
         .collect::<Vec<_>>();
+    let difficulty_info = parsed_files
+        .iter()
+        .map(|(file_name, p)| DifficultyInfo {
+            version: p.version.clone(),
+            file_name: file_name.clone(),
+            beatmap_id: p.beatmap_id,
+            format_version: p.format_version,
+            mode: p.mode,
+            settings: p.difficulty,
+        })
+        .collect();
+    // A missing `Mode` means osu!standard; sets can mix modes, so every file counts.
+    let mut modes = parsed_files
+        .iter()
+        .map(|(_, p)| p.mode.unwrap_or(0))
+        .collect::<Vec<_>>();
+    modes.sort_unstable();
+    modes.dedup();
+    let preview_time_ms = parsed_files.iter().find_map(|(_, p)| p.preview_time);
+    let mut tags: Vec<String> = Vec::new();
+    for tag in parsed_files.iter().flat_map(|(_, p)| p.tags.iter()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    let source = parsed_files.iter().find_map(|(_, p)| p.source.clone());
 
     Ok(BeatmapMetadata {
         title: main.title,
@@ -90,25 +149,337 @@ fn extract_metadata_from_archive(buf: &[u8]) -> Result<BeatmapMetadata> {
         beatmap_ids,
         background_file: main.background_file,
         audio_file: main.audio_file,
+        has_video,
+        difficulty_info,
+        audio_present,
+        modes,
+        preview_time_ms,
+        title_unicode: main.title_unicode,
+        artist_unicode: main.artist_unicode,
+        tags,
+        source,
     })
 }
 
+/// Archive paths compared the way osu! resolves them on Windows: case-insensitive, either slash.
+fn normalize_entry_name(name: &str) -> String {
+    name.trim().replace('\\', "/").trim_start_matches("./").to_lowercase()
+}
+
+/// Best-effort metadata for an archive accepted without any `.osu`; it has no difficulties.
+fn unparsed_metadata(name: &str, has_video: bool) -> BeatmapMetadata {
+    BeatmapMetadata {
+        title: name.to_string(),
+        artist: String::new(),
+        creator: String::new(),
+        difficulties: Vec::new(),
+        beatmap_set_id: None,
+        beatmap_ids: Vec::new(),
+        background_file: None,
+        audio_file: None,
+        has_video,
+        difficulty_info: Vec::new(),
+        audio_present: false,
+        modes: Vec::new(),
+        preview_time_ms: None,
+        title_unicode: None,
+        artist_unicode: None,
+        tags: vec![],
+        source: None,
+    }
+}
+
+fn is_osz_name(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().eq_ignore_ascii_case("osz"))
+        .unwrap_or(false)
+}
+
+/// Cheap check that a zip holds a beatmap (`.osu`) or a pack of them (`.osz`), reading only
+/// the central directory. Used before queueing `.zip` downloads, which may be anything.
+pub fn contains_beatmaps(path: &Path) -> bool {
+    let Some(zip) = File::open(path).ok().and_then(|f| ZipArchive::new(f).ok()) else {
+        return false;
+    };
+    zip.file_names()
+        .any(|name| is_osz_name(name) || name.to_ascii_lowercase().ends_with(".osu"))
+}
+
+/// Writes every `.osz` inside a pack into `target_dir` and returns their paths. A file left
+/// there by an earlier expansion (same name and size) is reused; other name clashes get a
+/// numbered name, so nothing already on disk is overwritten.
+pub fn extract_pack(path: &Path, target_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    std::fs::create_dir_all(target_dir)?;
+    let mut written = Vec::new();
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        if file.is_dir() || !is_osz_name(file.name()) {
+            continue;
+        }
+        // Flatten to the bare file name so entries like `../x.osz` stay inside `target_dir`.
+        let base = Path::new(file.name())
+            .file_name()
+            .map(|n| sanitize_path_component(&n.to_string_lossy()))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("mapa-{i}.osz"));
+        let mut n = 0;
+        let (target, reused) = loop {
+            let candidate = match n {
+                0 => target_dir.join(&base),
+                n => target_dir.join(format!("{n}-{base}")),
+            };
+            n += 1;
+            if written.contains(&candidate) {
+                continue;
+            }
+            match std::fs::metadata(&candidate) {
+                Ok(meta) if meta.is_file() && meta.len() == file.size() => break (candidate, true),
+                Ok(_) => continue,
+                Err(_) => break (candidate, false),
+            }
+        };
+        if reused {
+            written.push(target);
+            continue;
+        }
+        let mut out = File::create_new(&target)?;
+        std::io::copy(&mut file, &mut out)?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "avi", "flv", "m4v", "mkv", "webm", "wmv"];
+
+pub fn is_video_file(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|ext| {
+            let ext = ext.to_string_lossy();
+            VIDEO_EXTENSIONS.iter().any(|v| ext.eq_ignore_ascii_case(v))
+        })
+        .unwrap_or(false)
+}
+
+/// Decodes the background named by the map, falling back to the other images in the archive
+/// (largest first, so skin elements come last) when it is missing or can't be decoded.
 fn load_image_from_archive(buf: &[u8], file_name: &str) -> Result<Option<image::DynamicImage>> {
     let cursor = std::io::Cursor::new(buf);
     let mut zip = ZipArchive::new(cursor)?;
+    let mut named = Vec::new();
+    let mut others = Vec::new();
     for i in 0..zip.len() {
-        let mut file = zip.by_index(i)?;
+        let file = zip.by_index(i)?;
         if file.name().ends_with(file_name) || file.name().contains(file_name) {
-            let mut data = Vec::new();
-            file.read_to_end(&mut data)?;
-            let img = image::load_from_memory(&data).ok();
-            return Ok(img);
+            named.push(i);
+        } else if is_image_file(file.name()) {
+            others.push((file.size(), i));
+        }
+    }
+    others.sort_by(|a, b| b.0.cmp(&a.0));
+    for i in named.into_iter().chain(others.into_iter().map(|(_, i)| i)) {
+        let mut file = zip.by_index(i)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        if let Ok(img) = image::load_from_memory(&data) {
+            return Ok(Some(img));
         }
     }
     Ok(None)
 }
 
-fn create_thumbnail(img: &image::DynamicImage) -> Result<image::DynamicImage> {
-    let thumb = img.thumbnail(256, 256);
-    Ok(thumb)
+/// Width and height of every cached thumbnail (16:9), so cards line up.
+pub const THUMBNAIL_SIZE: (u32, u32) = (256, 144);
+
+/// Saves `img` as `<key>.png` in the thumbnail cache and indexes it under `key`.
+pub fn cache_thumbnail(img: &image::DynamicImage, key: &str, cache: &CacheStore) -> Result<PathBuf> {
+    let dir = thumbnails_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{key}.png"));
+    create_thumbnail(img).save(&path)?;
+    cache.insert_thumbnail(key.to_string(), path.clone());
+    let _ = cache.save();
+    Ok(path)
+}
+
+/// Center-crops to 16:9 and scales to `THUMBNAIL_SIZE`.
+fn create_thumbnail(img: &image::DynamicImage) -> image::DynamicImage {
+    let (width, height) = THUMBNAIL_SIZE;
+    img.resize_to_fill(width, height, image::imageops::FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    fn build_archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let opts = FileOptions::default();
+        for (name, body) in entries {
+            writer.start_file(*name, opts).unwrap();
+            writer.write_all(body.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn thumbnails_share_one_frame() {
+        for (width, height) in [(300, 1200), (1920, 400), (1366, 768), (10, 10)] {
+            let img = image::DynamicImage::new_rgb8(width, height);
+            let thumb = create_thumbnail(&img);
+            assert_eq!((thumb.width(), thumb.height()), THUMBNAIL_SIZE);
+        }
+    }
+
+    #[test]
+    fn broken_background_falls_back_to_another_image() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(4, 4)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let opts = FileOptions::default();
+        writer.start_file("bg.webp", opts).unwrap();
+        writer.write_all(b"not really an image").unwrap();
+        writer.start_file("cover.png", opts).unwrap();
+        writer.write_all(png.get_ref()).unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        let img = load_image_from_archive(&archive, "bg.webp").unwrap().unwrap();
+        assert_eq!((img.width(), img.height()), (4, 4));
+        let only_broken = build_archive(&[("bg.webp", "broken")]);
+        assert!(load_image_from_archive(&only_broken, "bg.webp").unwrap().is_none());
+    }
+
+    #[test]
+    fn only_zips_with_beatmaps_are_accepted() {
+        let dir = tempfile::tempdir().unwrap();
+        let map = dir.path().join("map.zip");
+        std::fs::write(&map, build_archive(&[("Song/map [Hard].osu", "osu file format v14")])).unwrap();
+        let pack = dir.path().join("pack.zip");
+        std::fs::write(&pack, build_archive(&[("a.osz", "osz")])).unwrap();
+        let other = dir.path().join("photos.zip");
+        std::fs::write(&other, build_archive(&[("cat.jpg", "jpg")])).unwrap();
+        let broken = dir.path().join("broken.zip");
+        std::fs::write(&broken, b"not a zip").unwrap();
+
+        assert!(contains_beatmaps(&map));
+        assert!(contains_beatmaps(&pack));
+        assert!(!contains_beatmaps(&other));
+        assert!(!contains_beatmaps(&broken));
+        assert!(!contains_beatmaps(&dir.path().join("missing.zip")));
+    }
+
+    #[test]
+    fn metadata_reports_video_and_difficulties() {
+        let osu_easy = "[Metadata]\nTitle:Song\nArtist:Artist\nCreator:Mapper\nVersion:Easy\n";
+        let osu_hard = "[Metadata]\nTitle:Song\nArtist:Artist\nCreator:Mapper\nVersion:Hard\n";
+        let with_video = build_archive(&[
+            ("easy.osu", osu_easy),
+            ("hard.osu", osu_hard),
+            ("bg.MP4", "video"),
+        ]);
+        let meta = extract_metadata_from_archive(&with_video, None).unwrap();
+        assert_eq!(meta.difficulties.len(), 2);
+        assert!(meta.has_video);
+
+        let without_video = build_archive(&[("easy.osu", osu_easy)]);
+        let meta = extract_metadata_from_archive(&without_video, None).unwrap();
+        assert!(!meta.has_video);
+    }
+
+    #[test]
+    fn pack_of_osz_is_detected_and_extracted() {
+        let pack = build_archive(&[("a/first.osz", "osz one"), ("../second.osz", "osz two")]);
+        let err = extract_metadata_from_archive(&pack, None).unwrap_err();
+        let detected = err.downcast_ref::<BeatmapPack>().unwrap();
+        assert_eq!(detected.inner.len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let pack_path = dir.path().join("pack.zip");
+        std::fs::write(&pack_path, &pack).unwrap();
+        let out = dir.path().join("out");
+        let files = extract_pack(&pack_path, &out).unwrap();
+        assert_eq!(files, vec![out.join("first.osz"), out.join("second.osz")]);
+    }
+
+    #[test]
+    fn pack_name_clashes_never_overwrite() {
+        let pack = build_archive(&[("a/map.osz", "first"), ("b/map.osz", "second!"), ("c/map.osz", "third!!!")]);
+        let dir = tempfile::tempdir().unwrap();
+        let pack_path = dir.path().join("pack.zip");
+        std::fs::write(&pack_path, &pack).unwrap();
+        let out = dir.path().join("out");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::write(out.join("1-map.osz"), "unrelated").unwrap();
+
+        let files = extract_pack(&pack_path, &out).unwrap();
+        assert_eq!(files, vec![out.join("map.osz"), out.join("2-map.osz"), out.join("3-map.osz")]);
+        assert_eq!(std::fs::read_to_string(out.join("1-map.osz")).unwrap(), "unrelated");
+        assert_eq!(std::fs::read_to_string(out.join("3-map.osz")).unwrap(), "third!!!");
+
+        // Expanding again reuses what is already there instead of adding copies.
+        assert_eq!(extract_pack(&pack_path, &out).unwrap(), files);
+        assert_eq!(std::fs::read_dir(&out).unwrap().count(), 4);
+    }
+
+    #[test]
+    fn difficulties_keep_their_beatmap_ids() {
+        let osu_easy = "[Metadata]\nTitle:Song\nVersion:Easy\nBeatmapID:101\n";
+        let osu_hard = "[Metadata]\nTitle:Song\nVersion:Hard\n";
+        let archive = build_archive(&[("easy.osu", osu_easy), ("hard.osu", osu_hard)]);
+        let meta = extract_metadata_from_archive(&archive, None).unwrap();
+        assert_eq!(
+            meta.difficulty_links(),
+            vec![("Easy".to_string(), Some(101)), ("Hard".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn modes_cover_every_difficulty() {
+        let std = "[General]\nMode: 0\n[Metadata]\nTitle:Song\nVersion:Normal\n";
+        let mania = "[General]\nMode: 3\n[Metadata]\nTitle:Song\nVersion:4K\n";
+        let plain = "[Metadata]\nTitle:Song\nVersion:Old\n";
+        let archive = build_archive(&[("a.osu", mania), ("b.osu", std), ("c.osu", plain)]);
+        let meta = extract_metadata_from_archive(&archive, None).unwrap();
+        assert_eq!(meta.modes, vec![0, 3]);
+        assert_eq!(meta.mode_label(), "osu!/mania");
+        assert_eq!(meta.difficulty_info[0].mode, Some(3));
+    }
+
+    #[test]
+    fn audio_reference_is_checked_against_archive() {
+        let osu = "[General]\nAudioFilename: Audio.MP3\n[Metadata]\nTitle:Song\nVersion:Easy\n";
+        let present = build_archive(&[("map.osu", osu), ("audio.mp3", "mp3")]);
+        let meta = extract_metadata_from_archive(&present, None).unwrap();
+        assert!(meta.audio_present);
+        assert!(!meta.audio_missing());
+
+        let missing = build_archive(&[("map.osu", osu)]);
+        let meta = extract_metadata_from_archive(&missing, None).unwrap();
+        assert!(meta.audio_missing());
+
+        let silent = build_archive(&[("map.osu", "[Metadata]\nTitle:Song\nVersion:Easy\n")]);
+        let meta = extract_metadata_from_archive(&silent, None).unwrap();
+        assert!(meta.audio_file.is_none());
+        assert!(!meta.audio_missing());
+    }
+
+    #[test]
+    fn relaxed_validation_names_archive_without_osu() {
+        let skin = build_archive(&[("skin.ini", "[General]\nName:Test\n")]);
+        assert!(extract_metadata_from_archive(&skin, None).is_err());
+        let meta = extract_metadata_from_archive(&skin, Some("My Skin")).unwrap();
+        assert_eq!(meta.title, "My Skin");
+        assert_eq!(meta.display_title(false), "My Skin");
+        assert!(meta.difficulties.is_empty());
+
+        let pack = build_archive(&[("inner.osz", "osz")]);
+        let err = extract_metadata_from_archive(&pack, Some("pack")).unwrap_err();
+        assert!(err.downcast_ref::<BeatmapPack>().is_some());
+    }
 }
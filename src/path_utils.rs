@@ -1,4 +1,5 @@
 use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Normalize a path by removing `.` and resolving `..` components without hitting the filesystem.
 fn normalize_path(path: &Path) -> PathBuf {
@@ -70,11 +71,148 @@ pub fn can_delete_source(downloads: &Path, songs: &Path, source: &Path) -> bool
     is_within_dir(downloads, source)
 }
 
+const PROTECTED_ROOT_VARS: [&str; 4] = ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432", "SystemRoot"];
+
+pub const PERMISSION_GUIDANCE: &str = "Mova a pasta Songs para fora de diretorios protegidos (ex.: Program Files) ou ajuste as permissoes da pasta.";
+
+/// Warns when Songs lives under a system-protected folder (Program Files, Windows) and cannot be written.
+/// Steam usually grants write access under Program Files, so only an actual failed write triggers it.
+pub fn protected_songs_warning(songs: &Path) -> Option<String> {
+    let roots: Vec<PathBuf> = PROTECTED_ROOT_VARS
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
+    let root = protected_root_for(songs, &roots)?;
+    if probe_writable(songs) {
+        return None;
+    }
+    Some(format!(
+        "A pasta Songs esta em um local protegido do sistema ({}) e nao permite gravacao. {}",
+        root.display(),
+        PERMISSION_GUIDANCE
+    ))
+}
+
+/// Returns the protected root containing `path`, comparing case-insensitively like Windows does.
+fn protected_root_for<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    let lower = |p: &Path| PathBuf::from(normalize_path(p).to_string_lossy().to_lowercase());
+    let path = lower(path);
+    roots.iter().find(|root| path.starts_with(lower(root)))
+}
+
+/// Tries to create a scratch file in `dir` (or its nearest existing ancestor).
+fn probe_writable(dir: &Path) -> bool {
+    let Some(existing) = dir.ancestors().find(|p| p.is_dir()) else {
+        return false;
+    };
+    let probe = existing.join(".mcosu-importer-write-test");
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(err) => err.kind() == std::io::ErrorKind::AlreadyExists,
+    }
+}
+
+/// Warns when Songs sits inside a cloud-synced folder (OneDrive, Dropbox, ...), where every
+/// import triggers sync churn and synced copies can fight with McOsu over the files.
+pub fn cloud_sync_warning(songs: &Path, markers: &[String]) -> Option<String> {
+    let marker = cloud_sync_marker(songs, markers)?;
+    Some(format!(
+        "A pasta Songs parece estar em uma pasta sincronizada na nuvem ({marker}). Importacoes vao gerar sincronizacao constante; prefira uma pasta local."
+    ))
+}
+
+fn cloud_sync_marker<'a>(path: &Path, markers: &'a [String]) -> Option<&'a str> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .find_map(|name| {
+            markers
+                .iter()
+                .find(|m| !m.is_empty() && name.starts_with(&m.to_lowercase()))
+        })
+        .map(|m| m.as_str())
+}
+
+/// Folder used to keep imported sources: `<downloads>/_imported/YYYY-MM-DD` (UTC date).
+pub fn dated_archive_dir(downloads: &Path, now: SystemTime) -> PathBuf {
+    let days = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(days as i64);
+    downloads
+        .join("_imported")
+        .join(format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Suffix appended to a source `.osz` to mark it imported while keeping the file.
+pub const IMPORTED_MARKER_EXT: &str = "imported";
+
+/// `map.osz` -> `map.osz.imported`; the watcher and startup scan only pick up `.osz` files.
+pub fn imported_marker_path(source: &Path) -> PathBuf {
+    let mut name = source.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(IMPORTED_MARKER_EXT);
+    source.with_file_name(name)
+}
+
+/// True for files whose extension is one of `extensions` (case-insensitive, leading dot
+/// optional); sources already marked imported never match.
+pub fn is_queueable_archive(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|accepted| accepted.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        })
+}
+
+/// True when both paths name the same file, resolving symlinks and `..` when the files
+/// exist and comparing normalized paths otherwise (e.g. a source already deleted).
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    let resolve = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| normalize_path(p));
+    resolve(a) == resolve(b)
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = (if z >= 0 { z } else { z - 146_096 }) / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn same_file_resolves_relative_components() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("map.osz");
+        std::fs::write(&file, b"osz").unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        assert!(same_file(&file, &sub.join("..").join("map.osz")));
+        assert!(!same_file(&file, &dir.path().join("other.osz")));
+        let gone = dir.path().join("gone.osz");
+        assert!(same_file(&gone, &dir.path().join(".").join("gone.osz")));
+    }
+
     #[test]
     fn delete_allowed_only_within_downloads() {
         let downloads = PathBuf::from("/home/user/Downloads");
@@ -104,6 +242,63 @@ mod tests {
         assert!(downloads_songs_conflict(&downloads, &separate).is_none());
     }
 
+    #[test]
+    fn protected_root_matches_case_insensitively() {
+        let roots = vec![PathBuf::from("C:/Program Files (x86)")];
+        assert!(protected_root_for(Path::new("c:/program files (x86)/Steam/McOsu/Songs"), &roots).is_some());
+        assert!(protected_root_for(Path::new("D:/Games/McOsu/Songs"), &roots).is_none());
+    }
+
+    #[test]
+    fn cloud_sync_marker_matches_component_prefix() {
+        let markers = vec!["OneDrive".to_string(), "Dropbox".to_string()];
+        assert_eq!(
+            cloud_sync_marker(Path::new("C:/Users/me/OneDrive - Contoso/McOsu/Songs"), &markers),
+            Some("OneDrive")
+        );
+        assert_eq!(
+            cloud_sync_marker(Path::new("/home/me/dropbox/Songs"), &markers),
+            Some("Dropbox")
+        );
+        assert!(cloud_sync_marker(Path::new("D:/Games/McOsu/Songs"), &markers).is_none());
+        assert!(cloud_sync_warning(Path::new("D:/MyDropboxBackup/Songs"), &markers).is_none());
+    }
+
+    #[test]
+    fn writable_probe_uses_existing_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(probe_writable(&dir.path().join("missing").join("Songs")));
+        assert!(!dir.path().join(".mcosu-importer-write-test").exists());
+    }
+
+    #[test]
+    fn dated_archive_dir_uses_utc_date() {
+        let downloads = PathBuf::from("/tmp/dl");
+        let leap_day = UNIX_EPOCH + std::time::Duration::from_secs(19_782 * 86_400 + 3_600);
+        assert_eq!(
+            dated_archive_dir(&downloads, leap_day),
+            PathBuf::from("/tmp/dl/_imported/2024-02-29")
+        );
+        assert_eq!(
+            dated_archive_dir(&downloads, UNIX_EPOCH),
+            PathBuf::from("/tmp/dl/_imported/1970-01-01")
+        );
+    }
+
+    #[test]
+    fn imported_marker_is_not_queued_again() {
+        let source = PathBuf::from("/tmp/dl/123 Artist - Title.osz");
+        let marked = imported_marker_path(&source);
+        assert_eq!(marked, PathBuf::from("/tmp/dl/123 Artist - Title.osz.imported"));
+        let exts = vec!["osz".to_string(), ".zip".to_string()];
+        assert!(is_queueable_archive(&source, &exts));
+        assert!(is_queueable_archive(Path::new("/tmp/dl/MAP.OSZ"), &exts));
+        assert!(is_queueable_archive(Path::new("/tmp/dl/map.zip"), &exts));
+        assert!(!is_queueable_archive(Path::new("/tmp/dl/map.zip"), &exts[..1]));
+        assert!(!is_queueable_archive(Path::new("/tmp/dl/notes.txt"), &exts));
+        assert!(!is_queueable_archive(&marked, &exts));
+    }
+
     #[test]
     fn validate_songs_choice_blocks_overlap() {
         let downloads = PathBuf::from("/tmp/dl");
@@ -1,36 +1,154 @@
-use std::net::TcpListener;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 use anyhow::Result;
 use mime_guess::MimeGuess;
-use once_cell::sync::OnceCell;
-use tiny_http::{Header, Response, Server};
-use tracing::warn;
+use tiny_http::{Header, Response, Server, StatusCode};
+use tracing::{info, warn};
+
+use crate::path_utils::is_within_dir;
 
-#[derive(Debug)]
 pub struct PreviewServer {
     pub port: u16,
+    pub bind: IpAddr,
+    server: Arc<Server>,
+    running: Arc<AtomicBool>,
+    viewer_root: PathBuf,
+    beatmaps_root: PathBuf,
+}
+
+impl PreviewServer {
+    /// Serving thread still running and the port accepts connections.
+    pub fn is_healthy(&self) -> bool {
+        if !self.running.load(Ordering::SeqCst) {
+            return false;
+        }
+        let ip = if self.bind.is_unspecified() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            self.bind
+        };
+        TcpStream::connect_timeout(&SocketAddr::new(ip, self.port), Duration::from_millis(500)).is_ok()
+    }
+
+    /// Stops accepting requests and lets the serving thread exit.
+    pub fn shutdown(&self) {
+        self.server.unblock();
+    }
+
+    /// Serves files from these folders; the roots are fixed when the server starts.
+    fn serves(&self, viewer_root: &Path, beatmaps_root: &Path) -> bool {
+        self.viewer_root == viewer_root && self.beatmaps_root == beatmaps_root
+    }
+
+    /// Host to put in preview URLs; wildcard binds are still reachable via loopback.
+    pub fn url_host(&self) -> String {
+        if self.bind.is_unspecified() {
+            Ipv4Addr::LOCALHOST.to_string()
+        } else {
+            match self.bind {
+                IpAddr::V6(v6) => format!("[{v6}]"),
+                IpAddr::V4(v4) => v4.to_string(),
+            }
+        }
+    }
+}
+
+static SERVER: Mutex<Option<Arc<PreviewServer>>> = Mutex::new(None);
+
+/// Returns the running preview server, starting (or rebuilding a dead) one when needed.
+/// A server started for other viewer/beatmap folders is replaced as well.
+pub fn ensure_server(
+    viewer_root: PathBuf,
+    beatmaps_root: PathBuf,
+    bind: &str,
+    port: Option<u16>,
+) -> Result<Arc<PreviewServer>> {
+    let mut slot = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(server) = slot.as_ref() {
+        if !server.serves(&viewer_root, &beatmaps_root) {
+            info!(
+                "Pastas do preview mudaram ({}); reiniciando servidor",
+                viewer_root.display()
+            );
+        } else if server.is_healthy() {
+            return Ok(server.clone());
+        } else {
+            warn!("Servidor de preview nao responde; reiniciando");
+        }
+        server.shutdown();
+    }
+    let server = start_server(viewer_root, beatmaps_root, bind, port)?;
+    *slot = Some(server.clone());
+    Ok(server)
 }
 
-static SERVER: OnceCell<Arc<PreviewServer>> = OnceCell::new();
+/// Stops the current preview server (if any) and starts a fresh one, e.g. after viewer assets change.
+pub fn restart_server(
+    viewer_root: PathBuf,
+    beatmaps_root: PathBuf,
+    bind: &str,
+    port: Option<u16>,
+) -> Result<Arc<PreviewServer>> {
+    let mut slot = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(old) = slot.take() {
+        old.shutdown();
+    }
+    let server = start_server(viewer_root, beatmaps_root, bind, port)?;
+    *slot = Some(server.clone());
+    Ok(server)
+}
 
-pub fn ensure_server(viewer_root: PathBuf, beatmaps_root: PathBuf) -> Result<Arc<PreviewServer>> {
-    SERVER
-        .get_or_try_init(|| start_server(viewer_root, beatmaps_root))
-        .cloned()
+/// Stops the preview server, if one was started, so the port is released on exit.
+pub fn shutdown_server() {
+    let mut slot = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(server) = slot.take() {
+        server.shutdown();
+    }
 }
 
-fn start_server(viewer_root: PathBuf, beatmaps_root: PathBuf) -> Result<Arc<PreviewServer>> {
-    let listener = TcpListener::bind("127.0.0.1:0")?;
+/// Binds `ip:port`, falling back to an OS-assigned port when the fixed one is taken.
+fn bind_listener(ip: IpAddr, port: Option<u16>) -> Result<TcpListener> {
+    if let Some(port) = port.filter(|p| *p != 0) {
+        match TcpListener::bind((ip, port)) {
+            Ok(listener) => return Ok(listener),
+            Err(err) => warn!("Porta de preview {port} indisponivel ({err}); usando porta automatica"),
+        }
+    }
+    Ok(TcpListener::bind((ip, 0))?)
+}
+
+fn start_server(
+    viewer_root: PathBuf,
+    beatmaps_root: PathBuf,
+    bind: &str,
+    port: Option<u16>,
+) -> Result<Arc<PreviewServer>> {
+    let ip: IpAddr = bind
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Endereco de preview invalido: {bind}"))?;
+    if !ip.is_loopback() {
+        warn!("Servidor de preview exposto fora do localhost ({ip})");
+    }
+    let listener = bind_listener(ip, port)?;
     let port = listener.local_addr()?.port();
+    let roots = (viewer_root.clone(), beatmaps_root.clone());
     let viewer_root = Arc::new(viewer_root);
     let beatmaps_root = Arc::new(beatmaps_root);
-    let server = Server::from_listener(listener, None)
-        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let server = Arc::new(
+        Server::from_listener(listener, None).map_err(|e| anyhow::anyhow!(e.to_string()))?,
+    );
+    let running = Arc::new(AtomicBool::new(true));
+    let worker_server = server.clone();
+    let worker_running = running.clone();
     thread::spawn(move || {
-        for request in server.incoming_requests() {
+        for request in worker_server.incoming_requests() {
             let path = request
                 .url()
                 .split('?')
@@ -52,8 +170,16 @@ fn start_server(viewer_root: PathBuf, beatmaps_root: PathBuf) -> Result<Arc<Prev
             }
             let _ = request.respond(Response::empty(404));
         }
+        worker_running.store(false, Ordering::SeqCst);
     });
-    Ok(Arc::new(PreviewServer { port }))
+    Ok(Arc::new(PreviewServer {
+        port,
+        bind: ip,
+        server,
+        running,
+        viewer_root: roots.0,
+        beatmaps_root: roots.1,
+    }))
 }
 
 fn serve_from_root(
@@ -61,14 +187,37 @@ fn serve_from_root(
     root: &Path,
     rel: &str,
 ) -> std::io::Result<()> {
-    if rel.contains("..") {
+    let Some(target) = resolve_request_path(root, rel) else {
         return request.respond(Response::empty(403));
-    }
-    let target = root.join(rel);
+    };
     let mime = mime_guess::from_path(&target);
     serve_path(request, &target, mime)
 }
 
+/// Maps a raw request path onto `root`, rejecting anything that could escape it:
+/// encoded or backslash `..`, absolute paths and drive prefixes.
+fn resolve_request_path(root: &Path, rel: &str) -> Option<PathBuf> {
+    let decoded = urlencoding::decode(rel).ok()?;
+    if decoded.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut clean = PathBuf::new();
+    for part in decoded.split(['/', '\\']) {
+        match Path::new(part).components().next() {
+            None | Some(Component::CurDir) => {}
+            Some(Component::Normal(name)) if !part.contains(':') && !part.contains('\0') => {
+                clean.push(name)
+            }
+            _ => return None,
+        }
+    }
+    if clean.as_os_str().is_empty() {
+        return None;
+    }
+    let target = root.join(clean);
+    is_within_dir(root, &target).then_some(target)
+}
+
 fn serve_path(
     request: tiny_http::Request,
     path: &Path,
@@ -77,19 +226,102 @@ fn serve_path(
     if !path.exists() || !path.is_file() {
         return request.respond(Response::empty(404));
     }
-    let file = std::fs::File::open(path)?;
-    let mut response = Response::from_file(file);
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let range = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Range"))
+        .map(|h| h.value.as_str().to_string());
+    let mut headers = vec![header("Accept-Ranges", "bytes")];
     if let Some(mt) = mime.first_raw() {
-        if let Ok(header) = Header::from_bytes(&b"Content-Type"[..], mt.as_bytes()) {
-            response = response.with_header(header);
-        }
+        headers.push(header("Content-Type", mt));
     }
-    if let Err(err) = request.respond(response) {
+    let result = match parse_range(range.as_deref(), len) {
+        ByteRange::Full => {
+            let mut response = Response::from_file(file);
+            for h in headers {
+                response = response.with_header(h);
+            }
+            request.respond(response)
+        }
+        ByteRange::Partial { start, end } => {
+            file.seek(SeekFrom::Start(start))?;
+            let count = end - start + 1;
+            headers.push(header("Content-Range", &format!("bytes {start}-{end}/{len}")));
+            let response = Response::new(
+                StatusCode(206),
+                headers,
+                file.take(count),
+                Some(count as usize),
+                None,
+            );
+            request.respond(response)
+        }
+        ByteRange::Unsatisfiable => request.respond(
+            Response::empty(416).with_header(header("Content-Range", &format!("bytes */{len}"))),
+        ),
+    };
+    if let Err(err) = result {
         warn!("Falha ao responder preview: {err}");
     }
     Ok(())
 }
 
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("cabecalho HTTP valido")
+}
+
+/// Part of a file a request asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    /// Inclusive byte offsets.
+    Partial { start: u64, end: u64 },
+    Unsatisfiable,
+}
+
+/// Reads a single `bytes=` range (`a-b`, `a-` or `-suffix`) for a file of `len` bytes.
+/// Missing, malformed or multi-part ranges get the whole file, as RFC 9110 allows.
+fn parse_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let (start, end) = if start.is_empty() {
+        let Ok(suffix) = end.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        (len.saturating_sub(suffix), len.saturating_sub(1))
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = if end.is_empty() {
+            len.saturating_sub(1)
+        } else {
+            match end.parse::<u64>() {
+                Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+                _ => return ByteRange::Full,
+            }
+        };
+        (start, end)
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial { start, end }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,8 +336,13 @@ mod tests {
         std::fs::write(viewer.path().join("index.html"), "viewer").unwrap();
         std::fs::write(beatmaps.path().join("sample.txt"), "beatmap").unwrap();
 
-        let server =
-            ensure_server(viewer.path().to_path_buf(), beatmaps.path().to_path_buf()).unwrap();
+        let server = ensure_server(
+            viewer.path().to_path_buf(),
+            beatmaps.path().to_path_buf(),
+            "127.0.0.1",
+            None,
+        )
+        .unwrap();
         let mut stream = TcpStream::connect(("127.0.0.1", server.port)).unwrap();
         write!(
             stream,
@@ -118,4 +355,127 @@ mod tests {
         let body = String::from_utf8_lossy(&buf[..len]);
         assert!(body.contains("beatmap"));
     }
+
+    #[test]
+    fn range_requests_get_partial_content() {
+        let viewer = tempdir().unwrap();
+        let beatmaps = tempdir().unwrap();
+        std::fs::write(beatmaps.path().join("audio.mp3"), "0123456789").unwrap();
+        let server = start_server(
+            viewer.path().to_path_buf(),
+            beatmaps.path().to_path_buf(),
+            "127.0.0.1",
+            None,
+        )
+        .unwrap();
+        let mut stream = TcpStream::connect(("127.0.0.1", server.port)).unwrap();
+        write!(
+            stream,
+            "GET /beatmaps/audio.mp3 HTTP/1.1\r\nHost: localhost\r\nRange: bytes=2-5\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        stream.flush().unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        server.shutdown();
+        assert!(response.starts_with("HTTP/1.1 206"), "{response}");
+        assert!(response.contains("Content-Range: bytes 2-5/10"), "{response}");
+        assert!(response.ends_with("\r\n\r\n2345"), "{response}");
+    }
+
+    #[test]
+    fn range_header_parsing() {
+        assert_eq!(parse_range(None, 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=2-5"), 10), ByteRange::Partial { start: 2, end: 5 });
+        assert_eq!(parse_range(Some("bytes=4-"), 10), ByteRange::Partial { start: 4, end: 9 });
+        assert_eq!(parse_range(Some("bytes=-3"), 10), ByteRange::Partial { start: 7, end: 9 });
+        assert_eq!(parse_range(Some("bytes=8-100"), 10), ByteRange::Partial { start: 8, end: 9 });
+        assert_eq!(parse_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=5-2"), 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-1"), 10), ByteRange::Full);
+    }
+
+    #[test]
+    fn request_paths_cannot_escape_root() {
+        let root = Path::new("/srv/preview");
+        assert_eq!(
+            resolve_request_path(root, "abc/beatmap%20file.osz"),
+            Some(root.join("abc").join("beatmap file.osz"))
+        );
+        for attack in [
+            "../secret.txt",
+            "%2e%2e/secret.txt",
+            "%2E%2E%2Fsecret.txt",
+            "abc/..\\..\\secret.txt",
+            "abc%5c..%5c..%5csecret.txt",
+            "/etc/passwd",
+            "%2fetc/passwd",
+            "C:/Windows/win.ini",
+            "c%3a%5cWindows",
+            "",
+        ] {
+            assert_eq!(resolve_request_path(root, attack), None, "{attack}");
+        }
+    }
+
+    #[test]
+    fn fixed_port_falls_back_when_taken() {
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let taken = TcpListener::bind((loopback, 0)).unwrap();
+        let busy_port = taken.local_addr().unwrap().port();
+        let listener = bind_listener(loopback, Some(busy_port)).unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), busy_port);
+    }
+
+    #[test]
+    fn wildcard_bind_uses_loopback_in_urls() {
+        let viewer = tempdir().unwrap();
+        let server = start_server(
+            viewer.path().to_path_buf(),
+            viewer.path().to_path_buf(),
+            "0.0.0.0",
+            None,
+        )
+        .unwrap();
+        assert_eq!(server.url_host(), "127.0.0.1");
+        server.shutdown();
+    }
+
+    #[test]
+    fn server_remembers_the_folders_it_serves() {
+        let viewer = tempdir().unwrap();
+        let beatmaps = tempdir().unwrap();
+        let server = start_server(
+            viewer.path().to_path_buf(),
+            beatmaps.path().to_path_buf(),
+            "127.0.0.1",
+            None,
+        )
+        .unwrap();
+        assert!(server.serves(viewer.path(), beatmaps.path()));
+        assert!(!server.serves(beatmaps.path(), beatmaps.path()));
+        server.shutdown();
+    }
+
+    #[test]
+    fn shutdown_marks_server_unhealthy() {
+        let viewer = tempdir().unwrap();
+        let server = start_server(
+            viewer.path().to_path_buf(),
+            viewer.path().to_path_buf(),
+            "127.0.0.1",
+            None,
+        )
+        .unwrap();
+        assert!(server.is_healthy());
+        server.shutdown();
+        for _ in 0..50 {
+            if !server.running.load(Ordering::SeqCst) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!server.is_healthy());
+    }
 }
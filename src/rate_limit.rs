@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+
+/// Wait used when a 429 comes without a usable `Retry-After`.
+const DEFAULT_RETRY: Duration = Duration::from_secs(30);
+/// Mirrors sometimes answer with absurd delays; past this we give up instead of hanging.
+const MAX_RETRY: Duration = Duration::from_secs(120);
+const MAX_ATTEMPTS: usize = 3;
+
+/// Per-host instant before which no new request should be sent, shared by search and downloads.
+static COOLDOWNS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Sends `request`, waiting out any cooldown for the host first. On 429 the host is put on
+/// cooldown for the `Retry-After` delay, `on_wait` is told how long, and the request is
+/// retried. Fails once the retries run out or the delay exceeds `MAX_RETRY`, reporting the
+/// (capped) cooldown the host was put on.
+pub fn send<F>(request: RequestBuilder, on_wait: F) -> anyhow::Result<Response>
+where
+    F: Fn(Duration),
+{
+    let probe = request
+        .try_clone()
+        .ok_or_else(|| anyhow::anyhow!("Requisicao nao pode ser repetida"))?
+        .build()?;
+    let host = probe.url().host_str().unwrap_or_default().to_string();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if let Some(wait) = remaining_cooldown(&host, Instant::now()) {
+            on_wait(wait);
+            std::thread::sleep(wait);
+        }
+        let current = request
+            .try_clone()
+            .ok_or_else(|| anyhow::anyhow!("Requisicao nao pode ser repetida"))?;
+        let resp = current.send()?;
+        if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(resp);
+        }
+        let delay = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after)
+            .unwrap_or(DEFAULT_RETRY);
+        if delay > MAX_RETRY || attempt >= MAX_ATTEMPTS {
+            let cooldown = delay.min(MAX_RETRY);
+            start_cooldown(&host, cooldown, Instant::now());
+            anyhow::bail!(
+                "Limite de taxa atingido em {host}, tente novamente em {}s",
+                cooldown.as_secs()
+            );
+        }
+        start_cooldown(&host, delay, Instant::now());
+    }
+}
+
+/// Status text shown while waiting for a rate limit to expire.
+pub fn wait_message(wait: Duration) -> String {
+    format!("Limite de taxa atingido, aguardando {}s", wait.as_secs().max(1))
+}
+
+/// `Retry-After` in delay-seconds form. HTTP-date values fall back to the default wait.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+fn start_cooldown(host: &str, delay: Duration, now: Instant) {
+    if let Ok(mut map) = COOLDOWNS.lock() {
+        let until = now + delay;
+        let slot = map.entry(host.to_string()).or_insert(until);
+        if *slot < until {
+            *slot = until;
+        }
+    }
+}
+
+fn remaining_cooldown(host: &str, now: Instant) -> Option<Duration> {
+    let mut map = COOLDOWNS.lock().ok()?;
+    let until = *map.get(host)?;
+    if until <= now {
+        map.remove(host);
+        return None;
+    }
+    Some(until - now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_delay_seconds_only() {
+        assert_eq!(parse_retry_after(" 12 "), Some(Duration::from_secs(12)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn cooldown_is_per_host_and_expires() {
+        let now = Instant::now();
+        start_cooldown("a.test", Duration::from_secs(10), now);
+        assert_eq!(
+            remaining_cooldown("a.test", now + Duration::from_secs(4)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(remaining_cooldown("b.test", now), None);
+        assert_eq!(remaining_cooldown("a.test", now + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn shorter_cooldown_does_not_shrink_existing_one() {
+        let now = Instant::now();
+        start_cooldown("c.test", Duration::from_secs(20), now);
+        start_cooldown("c.test", Duration::from_secs(5), now);
+        assert_eq!(remaining_cooldown("c.test", now), Some(Duration::from_secs(20)));
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::{Path, PathBuf};
+
+/// Steam install folders for the current platform: the registry entry plus the default
+/// install locations on Windows, `~/Library/Application Support/Steam` on macOS and the
+/// native/Flatpak locations on Linux.
+fn default_steam_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    #[cfg(windows)]
+    {
+        if let Some(path) = registry_steam_path() {
+            roots.push(path);
+        }
+        roots.extend(
+            ["ProgramFiles(x86)", "ProgramFiles"]
+                .iter()
+                .filter_map(std::env::var_os)
+                .map(|root| PathBuf::from(root).join("Steam")),
+        );
+    }
+    let home = directories::BaseDirs::new().map(|d| d.home_dir().to_path_buf());
+    if let Some(home) = home {
+        if cfg!(target_os = "macos") {
+            roots.push(home.join("Library").join("Application Support").join("Steam"));
+        } else if cfg!(target_os = "linux") {
+            roots.push(home.join(".steam").join("steam"));
+            roots.push(home.join(".local").join("share").join("Steam"));
+            roots.push(
+                home.join(".var")
+                    .join("app")
+                    .join("com.valvesoftware.Steam")
+                    .join(".local")
+                    .join("share")
+                    .join("Steam"),
+            );
+        }
+    }
+    roots
+}
+
+#[cfg(windows)]
+fn registry_steam_path() -> Option<PathBuf> {
+    let output = std::process::Command::new("reg")
+        .args(["query", r"HKCU\Software\Valve\Steam", "/v", "SteamPath"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_reg_value(&String::from_utf8_lossy(&output.stdout), "SteamPath")
+}
+
+/// Reads a `REG_SZ` value from `reg query` output
+/// (`    SteamPath    REG_SZ    c:/program files (x86)/steam`).
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_reg_value(output: &str, name: &str) -> Option<PathBuf> {
+    output.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(name)?;
+        let value = rest.trim_start().strip_prefix("REG_SZ")?.trim();
+        (!value.is_empty()).then(|| PathBuf::from(value))
+    })
+}
+
+/// All Steam library roots: the install folders plus every library listed in
+/// `steamapps/libraryfolders.vdf`.
+pub fn library_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for steam in default_steam_roots() {
+        // ~/.steam/steam is usually a symlink to one of the other Linux roots.
+        let steam = if cfg!(unix) {
+            std::fs::canonicalize(&steam).unwrap_or(steam)
+        } else {
+            steam
+        };
+        if !steam.is_dir() {
+            continue;
+        }
+        let vdf = steam.join("steamapps").join("libraryfolders.vdf");
+        let libraries = std::fs::read_to_string(&vdf)
+            .map(|text| parse_library_folders(&text))
+            .unwrap_or_default();
+        push_unique(&mut roots, steam);
+        for library in libraries {
+            push_unique(&mut roots, library);
+        }
+    }
+    roots
+}
+
+/// McOsu `Songs` folders found in the Steam libraries.
+pub fn find_mcosu_songs() -> Vec<PathBuf> {
+    songs_in_libraries(&library_roots())
+}
+
+fn songs_in_libraries(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for root in roots {
+        let songs = mcosu_songs_dir(root);
+        if songs.is_dir() {
+            push_unique(&mut found, songs);
+        }
+    }
+    found
+}
+
+fn mcosu_songs_dir(library: &Path) -> PathBuf {
+    library
+        .join("steamapps")
+        .join("common")
+        .join("McOsu")
+        .join("Songs")
+}
+
+fn push_unique(list: &mut Vec<PathBuf>, path: PathBuf) {
+    let key = path.to_string_lossy().to_lowercase();
+    if !list.iter().any(|p| p.to_string_lossy().to_lowercase() == key) {
+        list.push(path);
+    }
+}
+
+/// Extracts library paths from `libraryfolders.vdf`. Handles both the current format
+/// (`"0" { "path" "D:\\SteamLibrary" ... }`) and the legacy one (`"1" "D:\\SteamLibrary"`).
+pub(crate) fn parse_library_folders(vdf: &str) -> Vec<PathBuf> {
+    let tokens = vdf_tokens(vdf);
+    let mut paths = Vec::new();
+    for pair in tokens.windows(2) {
+        let (Token::Str(key), Token::Str(value)) = (&pair[0], &pair[1]) else {
+            continue;
+        };
+        let legacy = key.chars().all(|c| c.is_ascii_digit()) && value.contains(['\\', '/']);
+        if key.eq_ignore_ascii_case("path") || legacy {
+            paths.push(PathBuf::from(value));
+        }
+    }
+    paths
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+fn vdf_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        '"' => break,
+                        _ => value.push(c),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_current_library_format() {
+        let vdf = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+		"apps"
+		{
+			"228980"		"370847913"
+		}
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+		"apps"
+		{
+			"607260"		"1043162042"
+		}
+	}
+}
+"#;
+        assert_eq!(
+            parse_library_folders(vdf),
+            vec![
+                PathBuf::from("C:\\Program Files (x86)\\Steam"),
+                PathBuf::from("D:\\SteamLibrary")
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_legacy_library_format() {
+        let vdf = r#"
+"LibraryFolders"
+{
+	"TimeNextStatsReport"		"1561832855"
+	"ContentStatsID"		"-2372014206866217617"
+	"1"		"E:\\Games\\Steam"
+}
+"#;
+        assert_eq!(
+            parse_library_folders(vdf),
+            vec![PathBuf::from("E:\\Games\\Steam")]
+        );
+    }
+
+    #[test]
+    fn reads_steam_path_from_reg_query() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Valve\\Steam\r\n    SteamPath    REG_SZ    c:/program files (x86)/steam\r\n\r\n";
+        assert_eq!(
+            parse_reg_value(output, "SteamPath"),
+            Some(PathBuf::from("c:/program files (x86)/steam"))
+        );
+        assert_eq!(parse_reg_value(output, "SteamExe"), None);
+    }
+
+    #[test]
+    fn finds_songs_only_where_mcosu_is_installed() {
+        let dir = tempfile::tempdir().unwrap();
+        let with_mcosu = dir.path().join("lib1");
+        let without = dir.path().join("lib2");
+        std::fs::create_dir_all(mcosu_songs_dir(&with_mcosu)).unwrap();
+        std::fs::create_dir_all(without.join("steamapps").join("common")).unwrap();
+        let found = songs_in_libraries(&[with_mcosu.clone(), without, with_mcosu.clone()]);
+        assert_eq!(found, vec![mcosu_songs_dir(&with_mcosu)]);
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+/// Token bucket that paces a download loop to roughly `kbps` kilobits per second.
+/// The bucket only holds a quarter second of budget, so bursts stay small and the rate smooth.
+#[derive(Debug)]
+pub struct Throttle {
+    bytes_per_sec: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Throttle {
+    /// `None` (or 0) means unlimited.
+    pub fn new(kbps: Option<u32>) -> Option<Self> {
+        let kbps = kbps.filter(|k| *k > 0)?;
+        let bytes_per_sec = f64::from(kbps) * 1000.0 / 8.0;
+        let capacity = bytes_per_sec / 4.0;
+        Some(Self {
+            bytes_per_sec,
+            capacity,
+            tokens: capacity,
+            last: Instant::now(),
+        })
+    }
+
+    /// Accounts for `bytes` just transferred and sleeps long enough to stay under the limit.
+    pub fn consume(&mut self, bytes: usize) {
+        let wait = self.delay_for(bytes, Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    fn delay_for(&mut self, bytes: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.bytes_per_sec).min(self.capacity);
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.bytes_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_when_unset_or_zero() {
+        assert!(Throttle::new(None).is_none());
+        assert!(Throttle::new(Some(0)).is_none());
+    }
+
+    #[test]
+    fn paces_to_configured_rate() {
+        // 800 kbps = 100_000 bytes/s, bucket of 25_000 bytes.
+        let mut throttle = Throttle::new(Some(800)).unwrap();
+        let start = throttle.last;
+        assert_eq!(throttle.delay_for(25_000, start), Duration::ZERO);
+        let wait = throttle.delay_for(50_000, start);
+        assert_eq!(wait, Duration::from_millis(500));
+        // After waiting the debt off, the next chunk only waits for its own share.
+        let after = start + wait;
+        let next = throttle.delay_for(10_000, after);
+        assert!((next.as_secs_f64() - 0.1).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,107 @@
+//! System tray icon with quick actions. The icon lives on the UI thread and is polled by a Slint timer.
+
+pub enum TrayAction {
+    SetAutoImport(bool),
+    ShowWindow,
+    Quit,
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod imp {
+    use super::TrayAction;
+    use anyhow::Result;
+    use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+    use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+    pub struct Tray {
+        _icon: TrayIcon,
+        auto_import: CheckMenuItem,
+        open: MenuItem,
+        quit: MenuItem,
+    }
+
+    impl Tray {
+        pub fn new(auto_import: bool) -> Result<Self> {
+            let menu = Menu::new();
+            let auto_item = CheckMenuItem::new("Auto-import", true, auto_import, None);
+            let open = MenuItem::new("Abrir janela", true, None);
+            let quit = MenuItem::new("Sair", true, None);
+            menu.append_items(&[
+                &auto_item,
+                &open,
+                &PredefinedMenuItem::separator(),
+                &quit,
+            ])?;
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip("McOsu Importer")
+                .with_icon(build_icon()?)
+                .build()?;
+            Ok(Self {
+                _icon: icon,
+                auto_import: auto_item,
+                open,
+                quit,
+            })
+        }
+
+        pub fn poll(&self) -> Vec<TrayAction> {
+            let mut actions = Vec::new();
+            while let Ok(event) = MenuEvent::receiver().try_recv() {
+                if event.id == *self.auto_import.id() {
+                    actions.push(TrayAction::SetAutoImport(self.auto_import.is_checked()));
+                } else if event.id == *self.open.id() {
+                    actions.push(TrayAction::ShowWindow);
+                } else if event.id == *self.quit.id() {
+                    actions.push(TrayAction::Quit);
+                }
+            }
+            actions
+        }
+
+        pub fn sync_auto_import(&self, state: bool) {
+            if self.auto_import.is_checked() != state {
+                self.auto_import.set_checked(state);
+            }
+        }
+    }
+
+    fn build_icon() -> Result<Icon> {
+        const SIZE: u32 = 32;
+        let center = (SIZE as f32 - 1.0) / 2.0;
+        let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let dist = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+                if dist <= center {
+                    rgba.extend_from_slice(&[255, 102, 170, 255]);
+                } else {
+                    rgba.extend_from_slice(&[0, 0, 0, 0]);
+                }
+            }
+        }
+        Ok(Icon::from_rgba(rgba, SIZE, SIZE)?)
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod imp {
+    use super::TrayAction;
+    use anyhow::{Result, anyhow};
+
+    pub struct Tray;
+
+    impl Tray {
+        pub fn new(_auto_import: bool) -> Result<Self> {
+            Err(anyhow!("bandeja do sistema nao suportada nesta plataforma"))
+        }
+
+        pub fn poll(&self) -> Vec<TrayAction> {
+            Vec::new()
+        }
+
+        pub fn sync_auto_import(&self, _state: bool) {}
+    }
+}
+
+pub use imp::Tray;
@@ -1,35 +1,229 @@
+use std::collections::HashMap;
 use std::io::Read;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::app_state::StabilityConfig;
 use anyhow::Result;
-use notify::{Event, RecursiveMode, Watcher};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::warn;
 
-pub fn start_watcher<F: Fn(PathBuf) + Send + 'static>(dir: PathBuf, callback: F) -> Result<()> {
+/// Live watch on a set of folders; dropping it stops the watcher thread.
+pub struct DirWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched: Mutex<Vec<PathBuf>>,
+    extensions: Arc<Mutex<Vec<String>>>,
+}
+
+impl DirWatcher {
+    /// Replaces the file extensions that are queued (see [`should_queue`]).
+    pub fn set_extensions(&self, extensions: &[String]) {
+        if let Ok(mut guard) = self.extensions.lock() {
+            *guard = extensions.to_vec();
+        }
+    }
+
+    /// Points the watch at `dirs`: folders no longer listed are unregistered and new ones
+    /// registered. Returns the folders that were added.
+    pub fn set_dirs(&self, dirs: &[PathBuf]) -> Vec<PathBuf> {
+        let wanted = unique_dirs(dirs.iter().cloned());
+        let (Ok(mut watcher), Ok(mut watched)) = (self.watcher.lock(), self.watched.lock()) else {
+            return Vec::new();
+        };
+        for dir in watched.iter() {
+            if !wanted.iter().any(|w| crate::path_utils::same_file(w, dir)) {
+                if let Err(err) = watcher.unwatch(dir) {
+                    warn!("Falha ao parar de observar {}: {err}", dir.display());
+                }
+            }
+        }
+        let mut added = Vec::new();
+        let mut now_watched = Vec::new();
+        for dir in wanted {
+            let known = watched.iter().any(|w| crate::path_utils::same_file(w, &dir));
+            if !known {
+                if let Err(err) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                    warn!("Falha ao observar {}: {err}", dir.display());
+                    continue;
+                }
+                added.push(dir.clone());
+            }
+            now_watched.push(dir);
+        }
+        *watched = now_watched;
+        added
+    }
+}
+
+/// Drops folders that resolve to one already listed, keeping the first spelling. Watches are
+/// not recursive, so a folder nested in another still needs its own entry.
+pub fn unique_dirs(dirs: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if dir.as_os_str().is_empty() {
+            continue;
+        }
+        if !unique.iter().any(|d| crate::path_utils::same_file(d, &dir)) {
+            unique.push(dir);
+        }
+    }
+    unique
+}
+
+/// True when a file in a watched folder should be queued: its extension is accepted and,
+/// unless it is an `.osz`, the archive actually holds beatmaps (so random `.zip`s are left alone).
+pub fn should_queue(path: &Path, extensions: &[String]) -> bool {
+    if !crate::path_utils::is_queueable_archive(path, extensions) {
+        return false;
+    }
+    let is_osz = path
+        .extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("osz"));
+    is_osz || crate::osz_reader::contains_beatmaps(path)
+}
+
+pub fn start_watcher<F: Fn(PathBuf) + Send + 'static>(
+    dirs: &[PathBuf],
+    extensions: &[String],
+    callback: F,
+) -> Result<DirWatcher> {
     let (event_tx, event_rx) = mpsc::channel();
-    let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+    let extensions = Arc::new(Mutex::new(extensions.to_vec()));
+    let event_exts = extensions.clone();
+    let watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
         if let Ok(event) = res {
             if let Some(path) = event.paths.first() {
-                if let Some(ext) = path.extension() {
-                    if ext.to_string_lossy().eq_ignore_ascii_case("osz") {
-                        let _ = event_tx.send(path.to_path_buf());
-                    }
+                let accepted = event_exts
+                    .lock()
+                    .map(|exts| crate::path_utils::is_queueable_archive(path, &exts))
+                    .unwrap_or(false);
+                // Renames (e.g. marking a source `.imported`) report the old name too; it's gone.
+                if accepted && path.exists() {
+                    let _ = event_tx.send(path.to_path_buf());
                 }
             }
         }
     })?;
-    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    let thread_exts = extensions.clone();
     thread::spawn(move || {
-        let _keep = watcher;
-        while let Ok(path) = event_rx.recv() {
-            callback(path);
+        let mut debouncer = Debouncer::new(DEBOUNCE_WINDOW);
+        loop {
+            let received = match debouncer.next_due(Instant::now()) {
+                Some(wait) => event_rx.recv_timeout(wait),
+                None => event_rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(path) => debouncer.note(path, Instant::now()),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            for path in debouncer.take_due(Instant::now()) {
+                // Peeking into zips happens here, once the burst of writes is over.
+                let exts = thread_exts.lock().map(|e| e.clone()).unwrap_or_default();
+                if path.exists() && should_queue(&path, &exts) {
+                    callback(path);
+                }
+            }
         }
-        drop(_keep);
     });
-    Ok(())
+    let dir_watcher = DirWatcher {
+        watcher: Mutex::new(watcher),
+        watched: Mutex::new(Vec::new()),
+        extensions,
+    };
+    dir_watcher.set_dirs(dirs);
+    Ok(dir_watcher)
+}
+
+/// How long a path must go without new events before the callback sees it.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Coalesces the burst of events a browser emits while writing one file into a single
+/// notification, fired once the path has been quiet for `window`.
+struct Debouncer {
+    window: Duration,
+    last_seen: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_seen: HashMap::new(),
+        }
+    }
+
+    fn note(&mut self, path: PathBuf, now: Instant) {
+        self.last_seen.insert(path, now);
+    }
+
+    /// Time until the next pending path is due, or None when nothing is pending.
+    fn next_due(&self, now: Instant) -> Option<Duration> {
+        self.last_seen
+            .values()
+            .map(|seen| (*seen + self.window).saturating_duration_since(now))
+            .min()
+    }
+
+    fn take_due(&mut self, now: Instant) -> Vec<PathBuf> {
+        let window = self.window;
+        let mut due: Vec<PathBuf> = self
+            .last_seen
+            .iter()
+            .filter(|(_, seen)| now.duration_since(**seen) >= window)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &due {
+            self.last_seen.remove(path);
+        }
+        due.sort();
+        due
+    }
+}
+
+/// True when the file is non-empty and was last modified at least `min_age` ago,
+/// so no download can still be writing it.
+pub fn is_settled(path: &Path, min_age: Duration) -> bool {
+    if min_age.is_zero() {
+        return false;
+    }
+    std::fs::metadata(path)
+        .ok()
+        .filter(|m| m.len() > 0)
+        .and_then(|m| m.modified().ok())
+        .and_then(|mtime| mtime.elapsed().ok())
+        .map(|age| age >= min_age)
+        .unwrap_or(false)
+}
+
+/// Waits until the file was last modified at least `min_age` ago, re-checking the mtime
+/// each time so a write in the meantime restarts the wait. Returns false if the file
+/// disappears or is still being touched after `timeout`.
+pub fn wait_for_min_age(path: &Path, min_age: Duration, timeout: Duration) -> bool {
+    let start = Instant::now();
+    loop {
+        let Some(age) = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|mtime| mtime.elapsed().unwrap_or(Duration::ZERO))
+        else {
+            return false;
+        };
+        let Some(remaining) = remaining_age(age, min_age) else {
+            return true;
+        };
+        if start.elapsed() + remaining > timeout {
+            return false;
+        }
+        std::thread::sleep(remaining);
+    }
+}
+
+/// How much longer a file of `age` must sit untouched to reach `min_age`.
+fn remaining_age(age: Duration, min_age: Duration) -> Option<Duration> {
+    min_age.checked_sub(age).filter(|d| !d.is_zero())
 }
 
 pub fn is_file_stable(path: &PathBuf, cfg: &StabilityConfig) -> bool {
@@ -40,6 +234,10 @@ pub fn is_file_stable(path: &PathBuf, cfg: &StabilityConfig) -> bool {
     let start = Instant::now();
     let interval = Duration::from_millis(cfg.interval_ms);
     let timeout = Duration::from_secs(cfg.timeout_secs);
+    // Untouched for longer than the whole timeout: the download is long finished.
+    if is_settled(path, timeout) {
+        return true;
+    }
     let mut stable_count = 0u32;
 
     while start.elapsed() < timeout {
@@ -99,6 +297,7 @@ mod tests {
             consecutive_checks: 2,
             interval_ms: 50,
             timeout_secs: 5,
+            settled_after_secs: 0,
         };
         assert!(is_file_stable(&file, &cfg));
         {
@@ -111,4 +310,101 @@ mod tests {
         // After new write it should eventually stabilize again
         assert!(is_file_stable(&file, &cfg));
     }
+
+    #[test]
+    fn duplicate_watch_dirs_are_dropped() {
+        let dir = tempdir().unwrap();
+        let downloads = dir.path().join("Downloads");
+        let nested = downloads.join("osu");
+        let dirs = unique_dirs([
+            downloads.clone(),
+            PathBuf::new(),
+            nested.clone(),
+            downloads.join("."),
+            nested.join("..").join("osu"),
+        ]);
+        assert_eq!(dirs, vec![downloads, nested]);
+    }
+
+    #[test]
+    fn debouncer_coalesces_repeated_events() {
+        let start = Instant::now();
+        let window = Duration::from_millis(500);
+        let mut debouncer = Debouncer::new(window);
+        assert_eq!(debouncer.next_due(start), None);
+
+        let a = PathBuf::from("a.osz");
+        let b = PathBuf::from("b.osz");
+        debouncer.note(a.clone(), start);
+        debouncer.note(a.clone(), start + Duration::from_millis(100));
+        debouncer.note(b.clone(), start + Duration::from_millis(300));
+        debouncer.note(a.clone(), start + Duration::from_millis(400));
+        assert!(debouncer.take_due(start + Duration::from_millis(600)).is_empty());
+        assert_eq!(
+            debouncer.next_due(start + Duration::from_millis(600)),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(debouncer.take_due(start + Duration::from_millis(800)), vec![b]);
+        assert_eq!(debouncer.take_due(start + Duration::from_millis(900)), vec![a]);
+        assert!(debouncer.take_due(start + Duration::from_secs(5)).is_empty());
+        assert_eq!(debouncer.next_due(start), None);
+    }
+
+    #[test]
+    fn min_age_wait_rechecks_mtime() {
+        assert_eq!(
+            remaining_age(Duration::from_secs(2), Duration::from_secs(5)),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(remaining_age(Duration::from_secs(5), Duration::from_secs(5)), None);
+        assert_eq!(remaining_age(Duration::from_secs(9), Duration::ZERO), None);
+
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("fresh.osz");
+        std::fs::write(&file, b"osz").unwrap();
+        assert!(!wait_for_min_age(&file, Duration::from_secs(60), Duration::from_secs(1)));
+        assert!(wait_for_min_age(&file, Duration::from_millis(50), Duration::from_secs(5)));
+        assert!(!wait_for_min_age(&dir.path().join("missing.osz"), Duration::ZERO, Duration::ZERO));
+    }
+
+    #[test]
+    fn settled_requires_old_mtime() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("old.osz");
+        std::fs::write(&file, b"osz").unwrap();
+        assert!(!is_settled(&file, Duration::from_secs(60)));
+        assert!(!is_settled(&file, Duration::ZERO));
+        let old = std::time::SystemTime::now() - Duration::from_secs(120);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert!(is_settled(&file, Duration::from_secs(60)));
+        assert!(!is_settled(&dir.path().join("missing.osz"), Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn old_file_is_stable_without_waiting() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("done.osz");
+        std::fs::write(&file, b"osz").unwrap();
+        let old = std::time::SystemTime::now() - Duration::from_secs(600);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let cfg = crate::app_state::StabilityConfig {
+            consecutive_checks: 3,
+            interval_ms: 5_000,
+            timeout_secs: 120,
+            settled_after_secs: 0,
+        };
+        let start = Instant::now();
+        assert!(is_file_stable(&file, &cfg));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
 }